@@ -0,0 +1,8 @@
+fn main() {
+    // Same Swift runtime rpath as the app: screencapturekit links
+    // @rpath/libswift_Concurrency.dylib
+    #[cfg(target_os = "macos")]
+    {
+        println!("cargo:rustc-link-arg=-Wl,-rpath,/usr/lib/swift");
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand};
+
+#[derive(Debug, Parser)]
+#[command(name = "momentum-cli", version, about = "Record the screen without the Momentum window")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Record until the duration elapses or Ctrl-C is pressed, then mux and save.
+    Record(RecordArgs),
+    /// List the capture devices the recorder can see.
+    Devices,
+    /// Read or change the settings shared with the app.
+    #[command(subcommand)]
+    Settings(SettingsCommand),
+    /// Play a flash+beep pattern, record it and measure the A/V offset of
+    /// each audio source.
+    Calibrate(CalibrateArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct RecordArgs {
+    /// Stop after this long, e.g. `30s`, `2m`, `1m30s`, `500ms`.
+    #[arg(long, value_parser = parse_duration)]
+    pub duration: Option<Duration>,
+    /// Record the microphone on its own track.
+    #[arg(long)]
+    pub mic: bool,
+    /// Use FFmpeg test sources instead of real devices.
+    #[arg(long)]
+    pub synthetic: bool,
+    /// Where to write the MP4. Defaults to the app's save location.
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+    /// What to record: `display:<id>`, `window:<id>` or
+    /// `region:<display id>:<x>,<y>,<width>x<height>` (in physical pixels).
+    /// Defaults to the first display.
+    #[arg(long)]
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct CalibrateArgs {
+    /// How long to record the pattern, e.g. `8s`.
+    #[arg(long, value_parser = parse_duration)]
+    pub duration: Option<Duration>,
+    /// Add the measured offsets to the devices' `deviceOffsets` in the settings.
+    #[arg(long)]
+    pub save: bool,
+    /// Measure the FFmpeg test sources instead of the real devices.
+    #[arg(long)]
+    pub synthetic: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SettingsCommand {
+    /// Print every setting, or a single one by key (e.g. `micEnabled`).
+    Get { key: Option<String> },
+    /// Set one setting. VALUE is parsed as JSON, falling back to a string.
+    Set { key: String, value: String },
+}
+
+/// Parses `<number><unit>` groups (`h`, `m`, `s`, `ms`); a bare number is seconds.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("duration is empty".to_string());
+    }
+    if let Ok(seconds) = input.parse::<f64>() {
+        return seconds_to_duration(seconds, input);
+    }
+
+    let mut total = 0.0;
+    let mut rest = input;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_len);
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+
+        let value: f64 = number
+            .parse()
+            .map_err(|_| format!("invalid duration '{}'", input))?;
+        let scale = match unit {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return Err(format!("unknown unit '{}' in '{}'", unit, input)),
+        };
+        total += value * scale;
+        rest = tail;
+    }
+    seconds_to_duration(total, input)
+}
+
+fn seconds_to_duration(seconds: f64, input: &str) -> Result<Duration, String> {
+    if !seconds.is_finite() || seconds <= 0.0 {
+        return Err(format!("duration '{}' must be positive", input));
+    }
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| format!("duration '{}' is too long", input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_duration, Cli};
+    use clap::CommandFactory;
+    use std::time::Duration;
+
+    #[test]
+    fn parses_duration_units() {
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1m30s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1.5"), Ok(Duration::from_millis(1500)));
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("1e30").is_err());
+        assert!(parse_duration("99999999999999999h").is_err());
+    }
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+}
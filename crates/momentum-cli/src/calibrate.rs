@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use momentum_core::calibration::{run_calibration, DEFAULT_CALIBRATION_DURATION};
+use momentum_core::models::{CaptureMode, RecordingOptions};
+use momentum_core::platform::macos::ffmpeg::FfmpegLocator;
+use momentum_core::{CoreError, CoreResult, SettingsStore};
+
+use crate::args::CalibrateArgs;
+use crate::record::new_recorder;
+
+pub fn run(args: CalibrateArgs) -> CoreResult<()> {
+    let duration = args.duration.unwrap_or(DEFAULT_CALIBRATION_DURATION);
+    let store = SettingsStore::new(None)?;
+    let mut settings = store.load()?;
+    let options = RecordingOptions {
+        include_microphone: true,
+        include_camera: false,
+        screen_target: None,
+        capture_mode: args.synthetic.then_some(CaptureMode::Synthetic),
+        device_offsets: settings.device_offsets.clone(),
+        camera_overlay: settings.camera_overlay,
+        frame_rate: settings.frame_rate,
+        output_scale: settings.output_scale,
+        encoder: settings.encoder,
+    };
+
+    let ffmpeg_locator = Arc::new(FfmpegLocator::new());
+    let recorder = new_recorder(ffmpeg_locator.clone());
+    let report = run_calibration(&recorder, ffmpeg_locator, options, duration)?;
+
+    if args.save {
+        if !report.apply_to(&mut settings.device_offsets) {
+            return Err(CoreError::Recording(
+                "Calibration could not measure every source; nothing saved".to_string(),
+            ));
+        }
+        store.save(&settings)?;
+        eprintln!("[CLI] Added the measured offsets to deviceOffsets");
+    }
+
+    let text = serde_json::to_string_pretty(&report)
+        .map_err(|e| CoreError::Recording(format!("Failed to serialize report: {}", e)))?;
+    println!("{}", text);
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use momentum_core::platform::selectable_backend::CAPTURE_MODE_ENV;
+use momentum_core::CoreResult;
+
+pub fn run() -> CoreResult<()> {
+    print_platform_devices()?;
+    println!();
+    println!(
+        "Pass --synthetic (or set {}=synthetic) to record FFmpeg test sources instead.",
+        CAPTURE_MODE_ENV
+    );
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn print_platform_devices() -> CoreResult<()> {
+    use momentum_core::platform::linux::{pulse, v4l2};
+
+    match std::env::var("DISPLAY") {
+        Ok(display) => println!("Screen: X11 display {}", display),
+        Err(_) => println!("Screen: DISPLAY is not set, screen capture is unavailable"),
+    }
+
+    println!();
+    match pulse::list_sources() {
+        Ok(sources) => {
+            println!("Audio sources:");
+            let system = pulse::resolve_system_audio_source(&sources);
+            let mic = pulse::resolve_mic_source(&sources);
+            for source in &sources {
+                let role = if source.name == system {
+                    " (system audio)"
+                } else if source.name == mic {
+                    " (microphone)"
+                } else {
+                    ""
+                };
+                println!("  {}  {}  [{}]{}", source.index, source.name, source.sample_spec, role);
+            }
+        }
+        Err(e) => println!("Audio sources: unavailable ({})", e),
+    }
+
+    println!();
+    match v4l2::list_devices() {
+        Ok(devices) if devices.is_empty() => println!("Cameras: none found"),
+        Ok(devices) => {
+            println!("Cameras:");
+            for device in &devices {
+                let formats: Vec<&str> = device.formats.iter().map(|f| f.fourcc.as_str()).collect();
+                println!(
+                    "  {}  {} ({})  [{}]",
+                    device.path.display(),
+                    device.card,
+                    device.driver,
+                    formats.join(", ")
+                );
+            }
+        }
+        Err(e) => println!("Cameras: unavailable ({})", e),
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn print_platform_devices() -> CoreResult<()> {
+    use momentum_core::platform::device_resolver::resolve_avf_indices;
+
+    let resolved = resolve_avf_indices()?;
+    let describe = |index: Option<i32>| match index {
+        Some(index) => format!("AVFoundation index {}", index),
+        None => "not found".to_string(),
+    };
+    println!("Screen: {}", describe(resolved.video_index_main_screen));
+    println!("Microphone: {}", describe(resolved.audio_index_builtin_mic));
+    println!("Camera: {}", describe(resolved.video_index_builtin_cam));
+    println!("System audio: {}", describe(resolved.audio_index_system_audio));
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn print_platform_devices() -> CoreResult<()> {
+    println!("Native capture is not supported on this platform.");
+    Ok(())
+}
@@ -0,0 +1,27 @@
+mod args;
+mod calibrate;
+mod devices;
+mod record;
+mod settings;
+
+use clap::Parser;
+use momentum_core::CoreResult;
+
+use args::{Cli, Command};
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(err) = run(cli) {
+        eprintln!("momentum-cli: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> CoreResult<()> {
+    match cli.command {
+        Command::Record(args) => record::run(args),
+        Command::Devices => devices::run(),
+        Command::Settings(command) => settings::run(command),
+        Command::Calibrate(args) => calibrate::run(args),
+    }
+}
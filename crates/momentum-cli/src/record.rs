@@ -0,0 +1,85 @@
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+
+use momentum_core::models::{CaptureMode, RecordingOptions};
+use momentum_core::output;
+use momentum_core::platform::macos::ffmpeg::FfmpegLocator;
+use momentum_core::platform::selectable_backend::SelectableCaptureBackend;
+use momentum_core::platform::synthetic_recorder::SyntheticRecorder;
+use momentum_core::platform::PlatformCaptureBackend;
+use momentum_core::{CameraSyncHandle, CoreError, CoreEvent, CoreResult, Recorder, SettingsStore};
+
+use crate::args::RecordArgs;
+
+/// The platform backend, or synthetic sources when the options ask for them.
+pub fn new_recorder(ffmpeg_locator: Arc<FfmpegLocator>) -> Recorder {
+    let backend = Arc::new(SelectableCaptureBackend::new(
+        PlatformCaptureBackend::new(ffmpeg_locator.clone()),
+        SyntheticRecorder::new(ffmpeg_locator),
+    ));
+    Recorder::new(backend, Arc::new(CameraSyncHandle::new()))
+}
+
+pub fn run(args: RecordArgs) -> CoreResult<()> {
+    let settings = SettingsStore::new(None)?.load().unwrap_or_default();
+    let final_path = match args.out {
+        Some(path) => path,
+        None => output::default_output_path(&settings)?,
+    };
+
+    let options = RecordingOptions {
+        include_microphone: args.mic,
+        include_camera: false,
+        screen_target: args.target,
+        capture_mode: args.synthetic.then_some(CaptureMode::Synthetic),
+        device_offsets: settings.device_offsets,
+        camera_overlay: settings.camera_overlay,
+        frame_rate: settings.frame_rate,
+        output_scale: settings.output_scale,
+        encoder: settings.encoder,
+    };
+
+    let recorder = new_recorder(Arc::new(FfmpegLocator::new()));
+
+    let (stop_tx, stop_rx) = mpsc::channel();
+    ctrlc::set_handler(move || {
+        let _ = stop_tx.send(());
+    })
+    .map_err(|e| CoreError::Recording(format!("Failed to install Ctrl-C handler: {}", e)))?;
+
+    recorder.start(options)?;
+    recorder.start_elapsed_task(Arc::new(|event: CoreEvent| {
+        if let CoreEvent::RecordingElapsed { elapsed_ms } = event {
+            eprintln!("[CLI] Recording {}s", elapsed_ms / 1000);
+        }
+    }));
+    match args.duration {
+        Some(duration) => eprintln!("[CLI] Recording for {:?}, Ctrl-C to stop early", duration),
+        None => eprintln!("[CLI] Recording, Ctrl-C to stop"),
+    }
+
+    let stopped_by_user = match args.duration {
+        Some(duration) => match stop_rx.recv_timeout(duration) {
+            Ok(()) => true,
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        },
+        None => {
+            let _ = stop_rx.recv();
+            true
+        }
+    };
+    if stopped_by_user {
+        eprintln!("[CLI] Ctrl-C received, stopping...");
+    }
+
+    let result = recorder.stop()?;
+    output::save_recording_file(&result.output_path, &final_path)?;
+    eprintln!(
+        "[CLI] Saved {:.1}s recording to {}",
+        result.elapsed_ms as f64 / 1000.0,
+        final_path.display()
+    );
+    println!("{}", final_path.display());
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use momentum_core::{CoreError, CoreResult, SettingsStore};
+use serde_json::Value;
+
+use crate::args::SettingsCommand;
+
+pub fn run(command: SettingsCommand) -> CoreResult<()> {
+    let store = SettingsStore::new(None)?;
+    match command {
+        SettingsCommand::Get { key: None } => print_json(&serde_json::to_value(store.load()?)),
+        SettingsCommand::Get { key: Some(key) } => print_json(&Ok(store.get_value(&key)?)),
+        SettingsCommand::Set { key, value } => {
+            let updated = store.set_value(&key, parse_value(&value))?;
+            print_json(&serde_json::to_value(updated))
+        }
+    }
+}
+
+/// `true`, `42` or `null` are taken as JSON; anything else is a plain string,
+/// so `settings set saveLocation ~/Movies` needs no quoting.
+fn parse_value(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+fn print_json(value: &serde_json::Result<Value>) -> CoreResult<()> {
+    let value = value
+        .as_ref()
+        .map_err(|e| CoreError::Settings(format!("Failed to serialize settings: {}", e)))?;
+    let text = serde_json::to_string_pretty(value)
+        .map_err(|e| CoreError::Settings(format!("Failed to serialize settings: {}", e)))?;
+    println!("{}", text);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_value;
+    use serde_json::json;
+
+    #[test]
+    fn values_fall_back_to_strings() {
+        assert_eq!(parse_value("true"), json!(true));
+        assert_eq!(parse_value("null"), json!(null));
+        assert_eq!(parse_value("/Users/me/Movies"), json!("/Users/me/Movies"));
+        assert_eq!(parse_value("Option+I"), json!("Option+I"));
+    }
+}
@@ -0,0 +1,469 @@
+//! A/V sync self-test. Records a flash+beep pattern through the full pipeline
+//! (capture, mixer, mux), decodes the MP4 and measures how far each audio
+//! source's beeps land from the flashes they were played with. In synthetic
+//! capture mode the pattern comes from the FFmpeg generators; on real devices
+//! `ffplay` shows it full screen and plays it through the speakers. The result
+//! can be added to the measured devices' [`DeviceOffsets`].
+
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::camera::CameraSyncHandle;
+use crate::error::{CoreError, CoreResult};
+use crate::models::{CaptureMode, DeviceOffsets, RecordingOptions};
+use crate::platform::ffmpeg_process;
+use crate::platform::macos::ffmpeg::FfmpegLocator;
+use crate::platform::selectable_backend::resolve_capture_mode;
+use crate::platform::synthetic_recorder::{
+    flash_beep_player_graph, SyntheticPattern, SyntheticRecorder, SyntheticTiming,
+    MIC_BEEP_DELAY_SECONDS,
+};
+use crate::platform::{AudioDeviceIds, CaptureBackend};
+use crate::Recorder;
+
+/// Video is resampled to this rate for flash detection (1 ms steps).
+const VIDEO_ANALYSIS_FPS: u32 = 1_000;
+const AUDIO_ANALYSIS_RATE: u32 = 48_000;
+/// Audio energy is measured over 1 ms windows.
+const AUDIO_WINDOW: usize = 48;
+const FLASH_LUMA_THRESHOLD: u8 = 128;
+const BEEP_LEVEL_THRESHOLD: f32 = 0.1;
+/// A new onset needs this much quiet (or dark) before it.
+const MIN_ONSET_SPACING: f64 = 0.3;
+/// Beeps further than this from where they belong are not paired.
+const MAX_PAIRING_DISTANCE: f64 = 0.25;
+/// Time for `ffplay` to open its window and start playing before recording.
+const PLAYER_WARM_UP: Duration = Duration::from_secs(1);
+
+pub const DEFAULT_CALIBRATION_DURATION: Duration = Duration::from_secs(8);
+
+/// Measured audio-video offsets. Positive means the audio plays late.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalibrationReport {
+    pub flashes: usize,
+    pub system_audio_beeps: usize,
+    pub mic_beeps: usize,
+    pub system_audio_offset_ms: Option<f64>,
+    pub mic_offset_ms: Option<f64>,
+    /// Device ids the offsets were measured on.
+    pub system_audio_device: String,
+    pub mic_device: Option<String>,
+}
+
+impl CalibrationReport {
+    /// Adds the measured offsets to the devices' entries in `offsets`. The
+    /// test records with the configured offsets applied, so what it measures
+    /// is what is left to compensate. Changes nothing and returns `false`
+    /// unless both sources could be measured.
+    pub fn apply_to(&self, offsets: &mut DeviceOffsets) -> bool {
+        let (Some(system_audio_ms), Some(mic_ms), Some(mic_device)) = (
+            self.system_audio_offset_ms,
+            self.mic_offset_ms,
+            self.mic_device.as_ref(),
+        ) else {
+            return false;
+        };
+        offsets
+            .entry(self.system_audio_device.clone())
+            .or_default()
+            .audio_ms += system_audio_ms;
+        offsets.entry(mic_device.clone()).or_default().audio_ms += mic_ms;
+        true
+    }
+}
+
+/// Measures the devices a session with `options` records from:
+/// [`run_synthetic_calibration`] in synthetic capture mode, otherwise
+/// [`run_device_calibration`] through `recorder`.
+pub fn run_calibration<B: CaptureBackend>(
+    recorder: &Recorder<B>,
+    ffmpeg_locator: Arc<FfmpegLocator>,
+    options: RecordingOptions,
+    duration: Duration,
+) -> CoreResult<CalibrationReport> {
+    match resolve_capture_mode(&options) {
+        CaptureMode::Synthetic => run_synthetic_calibration(ffmpeg_locator, options, duration),
+        CaptureMode::Native => run_device_calibration(recorder, &ffmpeg_locator, options, duration),
+    }
+}
+
+/// Records the synthetic flash+beep pattern for `duration` and measures it.
+/// Runs on its own synthetic recorder, so no devices are touched.
+pub fn run_synthetic_calibration(
+    ffmpeg_locator: Arc<FfmpegLocator>,
+    options: RecordingOptions,
+    duration: Duration,
+) -> CoreResult<CalibrationReport> {
+    let ffmpeg_path = ffmpeg_locator.resolve()?;
+    let timing = SyntheticTiming {
+        pattern: SyntheticPattern::FlashBeep,
+        ..SyntheticTiming::default()
+    };
+    let recorder = Recorder::new(
+        Arc::new(SyntheticRecorder::with_timing(ffmpeg_locator, timing)),
+        Arc::new(CameraSyncHandle::new()),
+    );
+    let options = calibration_options(options, CaptureMode::Synthetic);
+    let devices = recorder.audio_device_ids(&options)?;
+
+    println!(
+        "[Calibration] Recording flash+beep pattern for {:?}",
+        duration
+    );
+    let (flashes, beeps) = record_pattern(&recorder, &ffmpeg_path, options, duration)?;
+    let report = measure(&flashes, &beeps, devices);
+    println!("[Calibration] {:?}", report);
+    Ok(report)
+}
+
+/// Measures the devices `recorder` records with `options`. `ffplay`, next to
+/// FFmpeg, shows the pattern full screen and plays its beeps through the
+/// speakers. The mic hears the same beeps, so the pattern is recorded twice,
+/// once per audio source with the other one muted.
+pub fn run_device_calibration<B: CaptureBackend>(
+    recorder: &Recorder<B>,
+    ffmpeg_locator: &FfmpegLocator,
+    options: RecordingOptions,
+    duration: Duration,
+) -> CoreResult<CalibrationReport> {
+    let ffmpeg_path = ffmpeg_locator.resolve()?;
+    let options = calibration_options(options, CaptureMode::Native);
+    let devices = recorder.audio_device_ids(&options)?;
+
+    println!(
+        "[Calibration] Playing flash+beep pattern for {:?} per source ({}, {})",
+        duration,
+        devices.system_audio,
+        devices.mic.as_deref().unwrap_or("no mic")
+    );
+    let _player = PatternPlayer::start(&ffmpeg_path, options.frame_rate.fps())?;
+    thread::sleep(PLAYER_WARM_UP);
+    let (system_flashes, system_beeps) =
+        record_one_source(recorder, &ffmpeg_path, options.clone(), duration, false)?;
+    let (mic_flashes, mic_beeps) =
+        record_one_source(recorder, &ffmpeg_path, options, duration, true)?;
+
+    let report = build_report(
+        system_flashes.len() + mic_flashes.len(),
+        paired_offsets(&system_flashes, &system_beeps, 0.0),
+        paired_offsets(&mic_flashes, &mic_beeps, 0.0),
+        devices,
+    );
+    println!("[Calibration] {:?}", report);
+    Ok(report)
+}
+
+/// `options` on the first display, with the mic on and the camera off.
+fn calibration_options(options: RecordingOptions, capture_mode: CaptureMode) -> RecordingOptions {
+    RecordingOptions {
+        include_microphone: true,
+        include_camera: false,
+        screen_target: None,
+        capture_mode: Some(capture_mode),
+        ..options
+    }
+}
+
+/// Records the pattern with only the mic (`mic`) or only system audio
+/// audible; both are unmuted again afterwards.
+fn record_one_source<B: CaptureBackend>(
+    recorder: &Recorder<B>,
+    ffmpeg_path: &Path,
+    options: RecordingOptions,
+    duration: Duration,
+    mic: bool,
+) -> CoreResult<(Vec<f64>, Vec<f64>)> {
+    recorder.set_mic_muted(!mic);
+    recorder.set_system_audio_muted(mic);
+    let onsets = record_pattern(recorder, ffmpeg_path, options, duration);
+    recorder.set_mic_muted(false);
+    recorder.set_system_audio_muted(false);
+    onsets
+}
+
+/// Records for `duration` and returns the flash and beep onsets, in seconds.
+fn record_pattern<B: CaptureBackend>(
+    recorder: &Recorder<B>,
+    ffmpeg_path: &Path,
+    options: RecordingOptions,
+    duration: Duration,
+) -> CoreResult<(Vec<f64>, Vec<f64>)> {
+    recorder.start(options)?;
+    thread::sleep(duration);
+    let result = recorder.stop()?;
+
+    let onsets = detect_onsets(ffmpeg_path, &result.output_path);
+    let _ = std::fs::remove_file(&result.output_path);
+    onsets
+}
+
+/// `ffplay` showing [`flash_beep_player_graph`] full screen; closed on drop.
+struct PatternPlayer(Child);
+
+impl PatternPlayer {
+    fn start(ffmpeg_path: &Path, fps: u32) -> CoreResult<Self> {
+        let ffplay_path = ffmpeg_path.with_file_name("ffplay");
+        let mut cmd = Command::new(&ffplay_path);
+        cmd.args([
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-fs",
+            "-window_title",
+            "Momentum Calibration",
+            "-f",
+            "lavfi",
+            "-i",
+        ]);
+        cmd.arg(flash_beep_player_graph(1280, 720, fps));
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        ffmpeg_process::isolate_from_terminal(&mut cmd);
+        let mut child = cmd.spawn().map_err(|e| {
+            CoreError::Recording(format!(
+                "Failed to start {} to play the calibration pattern: {}",
+                ffplay_path.display(),
+                e
+            ))
+        })?;
+        ffmpeg_process::forward_stderr(&mut child, "ffplay");
+        Ok(Self(child))
+    }
+}
+
+impl Drop for PatternPlayer {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Flash and beep onsets of a recording of the pattern, in seconds.
+fn detect_onsets(ffmpeg_path: &Path, recording: &Path) -> CoreResult<(Vec<f64>, Vec<f64>)> {
+    let luma = decode(
+        ffmpeg_path,
+        recording,
+        &[
+            "-map",
+            "0:v",
+            "-vf",
+            &format!("fps={},scale=1:1,format=gray", VIDEO_ANALYSIS_FPS),
+            "-f",
+            "rawvideo",
+        ],
+    )?;
+    let audio = decode(
+        ffmpeg_path,
+        recording,
+        &[
+            "-map",
+            "0:a",
+            "-ac",
+            "1",
+            "-ar",
+            &AUDIO_ANALYSIS_RATE.to_string(),
+            "-f",
+            "f32le",
+        ],
+    )?;
+    let samples: Vec<f32> = audio
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+
+    Ok((
+        flash_onsets(&luma, VIDEO_ANALYSIS_FPS),
+        beep_onsets(&samples, AUDIO_ANALYSIS_RATE),
+    ))
+}
+
+/// Pairs every flash with the system beep near it and the mic beep
+/// [`MIC_BEEP_DELAY_SECONDS`] after it, as the synthetic sources play them.
+fn measure(flashes: &[f64], beeps: &[f64], devices: AudioDeviceIds) -> CalibrationReport {
+    build_report(
+        flashes.len(),
+        paired_offsets(flashes, beeps, 0.0),
+        paired_offsets(flashes, beeps, MIC_BEEP_DELAY_SECONDS),
+        devices,
+    )
+}
+
+fn build_report(
+    flashes: usize,
+    system: Vec<f64>,
+    mic: Vec<f64>,
+    devices: AudioDeviceIds,
+) -> CalibrationReport {
+    CalibrationReport {
+        flashes,
+        system_audio_beeps: system.len(),
+        mic_beeps: mic.len(),
+        system_audio_offset_ms: median(system).map(|offset| offset * 1_000.0),
+        mic_offset_ms: median(mic).map(|offset| offset * 1_000.0),
+        system_audio_device: devices.system_audio,
+        mic_device: devices.mic,
+    }
+}
+
+fn decode(ffmpeg_path: &Path, input: &Path, args: &[&str]) -> CoreResult<Vec<u8>> {
+    let mut cmd = Command::new(ffmpeg_path);
+    cmd.args(["-hide_banner", "-loglevel", "error", "-i"]);
+    cmd.arg(input);
+    cmd.args(args);
+    cmd.arg("-");
+    cmd.stdin(Stdio::null());
+    ffmpeg_process::isolate_from_terminal(&mut cmd);
+    let output = cmd
+        .output()
+        .map_err(|e| CoreError::Recording(format!("Failed to decode recording: {}", e)))?;
+    if !output.status.success() {
+        return Err(CoreError::Recording(format!(
+            "Failed to decode recording: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Seconds at which the (1x1, gray) frames turn bright.
+fn flash_onsets(luma: &[u8], fps: u32) -> Vec<f64> {
+    let frames = luma.iter().map(|&level| level >= FLASH_LUMA_THRESHOLD);
+    onsets(frames, f64::from(fps))
+}
+
+/// Seconds at which the audio turns loud, measured over 1 ms windows.
+fn beep_onsets(samples: &[f32], sample_rate: u32) -> Vec<f64> {
+    let windows = samples.chunks(AUDIO_WINDOW).map(|window| {
+        window
+            .iter()
+            .any(|sample| sample.abs() >= BEEP_LEVEL_THRESHOLD)
+    });
+    onsets(windows, f64::from(sample_rate) / AUDIO_WINDOW as f64)
+}
+
+fn onsets(active: impl Iterator<Item = bool>, rate: f64) -> Vec<f64> {
+    let mut onsets = Vec::new();
+    let mut last_active: Option<f64> = None;
+    for (index, is_active) in active.enumerate() {
+        if !is_active {
+            continue;
+        }
+        let at = index as f64 / rate;
+        if last_active.is_none_or(|last| at - last >= MIN_ONSET_SPACING) {
+            onsets.push(at);
+        }
+        last_active = Some(at);
+    }
+    onsets
+}
+
+fn paired_offsets(flashes: &[f64], beeps: &[f64], delay: f64) -> Vec<f64> {
+    flashes
+        .iter()
+        .filter_map(|&flash| {
+            let expected = flash + delay;
+            beeps
+                .iter()
+                .map(|&beep| beep - expected)
+                .filter(|offset| offset.abs() <= MAX_PAIRING_DISTANCE)
+                .min_by(|a, b| a.abs().total_cmp(&b.abs()))
+        })
+        .collect()
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{beep_onsets, flash_onsets, measure};
+    use crate::models::{DeviceOffset, DeviceOffsets};
+    use crate::platform::AudioDeviceIds;
+
+    #[test]
+    fn detects_flashes_and_beeps() {
+        // 1 kHz luma: flashes at 0.2 s and 1.2 s, 100 ms each.
+        let luma: Vec<u8> = (0..2_000)
+            .map(|ms| {
+                if ms % 1_000 >= 200 && ms % 1_000 < 300 {
+                    235
+                } else {
+                    16
+                }
+            })
+            .collect();
+        assert_eq!(flash_onsets(&luma, 1_000), vec![0.2, 1.2]);
+
+        // 48 kHz: a 50 ms tone at 0.25 s, silence, another at 0.75 s.
+        let samples: Vec<f32> = (0..48_000)
+            .map(|i| {
+                let t = i as f64 / 48_000.0;
+                let on = (0.25..0.30).contains(&t) || (0.75..0.80).contains(&t);
+                if on {
+                    (t * 2.0 * std::f64::consts::PI * 1_000.0).sin() as f32 * 0.8
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let beeps = beep_onsets(&samples, 48_000);
+        assert_eq!(beeps.len(), 2);
+        assert!((beeps[0] - 0.25).abs() < 0.001, "{:?}", beeps);
+        assert!((beeps[1] - 0.75).abs() < 0.001, "{:?}", beeps);
+    }
+
+    #[test]
+    fn measures_offsets_per_source() {
+        let devices = AudioDeviceIds {
+            system_audio: "test:system-audio".to_string(),
+            mic: Some("test:mic".to_string()),
+        };
+        let flashes = [1.0, 2.0, 3.0];
+        // System beeps 20 ms late (one missing), mic beeps 45 ms late plus an
+        // outlier that should not move the median much.
+        let beeps = [1.02, 1.545, 2.02, 2.545, 3.56];
+        let report = measure(&flashes, &beeps, devices.clone());
+        assert_eq!(report.flashes, 3);
+        assert_eq!(report.system_audio_beeps, 2);
+        assert_eq!(report.mic_beeps, 3);
+        assert!((report.system_audio_offset_ms.unwrap() - 20.0).abs() < 1e-6);
+        assert!((report.mic_offset_ms.unwrap() - 45.0).abs() < 1e-6);
+
+        // Added on top of what was already configured for the device.
+        let mut offsets = DeviceOffsets::new();
+        offsets.insert(
+            "test:mic".to_string(),
+            DeviceOffset {
+                audio_ms: 100.0,
+                video_ms: 5.0,
+            },
+        );
+        assert!(report.apply_to(&mut offsets));
+        assert!((offsets["test:system-audio"].audio_ms - 20.0).abs() < 1e-6);
+        assert!((offsets["test:mic"].audio_ms - 145.0).abs() < 1e-6);
+        assert_eq!(offsets["test:mic"].video_ms, 5.0);
+
+        let silent = measure(&flashes, &[], devices);
+        assert_eq!(silent.system_audio_offset_ms, None);
+        let mut untouched = DeviceOffsets::new();
+        assert!(!silent.apply_to(&mut untouched));
+        assert!(untouched.is_empty());
+    }
+}
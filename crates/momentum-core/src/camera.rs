@@ -0,0 +1,501 @@
+use crate::error::CoreResult;
+use crate::events::{CoreEvent, EventSink};
+use crate::camera_device::{self, CameraDevice, MjpegSplitter, MjpegStream};
+use crate::jitter_buffer::{JitterBuffer, JitterBufferConfig, JitterMetrics, Pull, Timestamped};
+use crate::models::{FrameRate, RecordingOptions};
+use crate::time::{host_clock, SharedClock};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::platform::macos::ffmpeg::FfmpegLocator;
+
+const CAMERA_BUFFER_CAPACITY: usize = 300;
+const CAMERA_INITIAL_OFFSET_NS: u64 = 30_000_000;
+const CAMERA_TARGET_LAG_NS: u64 = 5_000_000;
+const MAX_CAM_DELAY_NS: u64 = 120_000_000;
+/// Webcams rarely deliver more than this.
+const CAMERA_MAX_FPS: u32 = 30;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CameraFramePayload {
+    pub id: u64,
+    pub width: u32,
+    pub height: u32,
+    pub format: &'static str,
+    pub data_base64: String,
+    #[serde(rename = "ptsNs")]
+    pub pts_ns: u64,
+}
+
+impl Timestamped for CameraFramePayload {
+    fn pts_ns(&self) -> u64 {
+        self.pts_ns
+    }
+}
+
+#[derive(Clone)]
+pub struct CameraSyncHandle {
+    event_sink: Arc<Mutex<Option<Arc<dyn EventSink>>>>,
+    frame_buffer: Arc<Mutex<JitterBuffer<CameraFramePayload>>>,
+    sync_enabled: Arc<AtomicBool>,
+    last_screen_pts_ns: Arc<AtomicU64>,
+    screen_tick_count: Arc<AtomicU64>,
+    /// Id of the camera feeding the handle and its configured latency.
+    device_id: Arc<Mutex<Option<String>>>,
+    device_latency_ns: Arc<AtomicI64>,
+    /// Whether the `camera-overlay` window is showing; recordings composite
+    /// the camera only while it is.
+    overlay_visible: Arc<AtomicBool>,
+    /// Recording frame rate; the preview reads the camera at up to this rate.
+    frame_rate: Arc<Mutex<FrameRate>>,
+    clock: SharedClock,
+}
+
+impl CameraSyncHandle {
+    pub fn new() -> Self {
+        Self::with_clock(host_clock())
+    }
+
+    /// Camera frames are stamped with `clock`; screen PTS handed to
+    /// [`Self::emit_for_screen_pts`] must come from the same clock.
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            event_sink: Arc::new(Mutex::new(None)),
+            frame_buffer: Arc::new(Mutex::new(JitterBuffer::new(JitterBufferConfig {
+                capacity: CAMERA_BUFFER_CAPACITY,
+                initial_offset_ns: CAMERA_INITIAL_OFFSET_NS,
+                target_lag_ns: CAMERA_TARGET_LAG_NS,
+                max_offset_ns: MAX_CAM_DELAY_NS,
+                ..JitterBufferConfig::default()
+            }))),
+            sync_enabled: Arc::new(AtomicBool::new(false)),
+            last_screen_pts_ns: Arc::new(AtomicU64::new(0)),
+            screen_tick_count: Arc::new(AtomicU64::new(0)),
+            device_id: Arc::new(Mutex::new(None)),
+            device_latency_ns: Arc::new(AtomicI64::new(0)),
+            overlay_visible: Arc::new(AtomicBool::new(true)),
+            frame_rate: Arc::new(Mutex::new(FrameRate::default())),
+            clock,
+        }
+    }
+
+    pub fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
+    /// Records which camera is feeding frames, for [`Self::apply_device_offsets`].
+    pub fn set_device_id(&self, device_id: String) {
+        *self.device_id.lock().unwrap() = Some(device_id);
+    }
+
+    /// Takes the video latency configured for the current camera. Frames
+    /// stamped with [`Self::capture_pts_ns`] are moved earlier by it.
+    pub fn apply_device_offsets(&self, options: &RecordingOptions) {
+        let Some(device_id) = self.device_id.lock().unwrap().clone() else {
+            return;
+        };
+        let latency_ns = options.video_latency_ns(&device_id);
+        self.device_latency_ns.store(latency_ns, Ordering::Relaxed);
+        println!(
+            "[CameraSync] Camera device: {} (latency offset {:.1} ms)",
+            device_id,
+            latency_ns as f64 / 1_000_000.0
+        );
+    }
+
+    pub fn set_frame_rate(&self, frame_rate: FrameRate) {
+        *self.frame_rate.lock().unwrap() = frame_rate;
+    }
+
+    /// Rate to read the camera at: the recording frame rate, capped at what
+    /// webcams deliver. Faster recordings repeat camera frames.
+    pub fn camera_fps(&self) -> u32 {
+        self.frame_rate.lock().unwrap().fps().min(CAMERA_MAX_FPS)
+    }
+
+    /// When a frame arriving now was captured, on the handle's clock.
+    pub fn capture_pts_ns(&self) -> u64 {
+        let latency_ns = self.device_latency_ns.load(Ordering::Relaxed);
+        self.clock.now_ns().saturating_add_signed(-latency_ns)
+    }
+
+    pub fn set_event_sink(&self, sink: Arc<dyn EventSink>) {
+        *self.event_sink.lock().unwrap() = Some(sink);
+    }
+
+    pub fn set_overlay_visible(&self, visible: bool) {
+        let previous = self.overlay_visible.swap(visible, Ordering::Relaxed);
+        if visible != previous {
+            println!("[CameraSync] Overlay visible -> {}", visible);
+        }
+    }
+
+    pub fn overlay_visible(&self) -> bool {
+        self.overlay_visible.load(Ordering::Relaxed)
+    }
+
+    /// True while a recording is pairing camera frames with screen ticks.
+    pub fn is_sync_enabled(&self) -> bool {
+        self.sync_enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_sync_enabled(&self, enabled: bool) {
+        let previous = self.sync_enabled.swap(enabled, Ordering::Relaxed);
+        if enabled != previous {
+            let metrics = self.metrics();
+            println!(
+                "[CameraSync] State change -> enabled={} (frames_in={}, frames_out={}, repeated={}, missed={}, dropped={}, screen_ticks={}, last_lag={}µs, offset={}µs)",
+                enabled,
+                metrics.pushed,
+                metrics.emitted,
+                metrics.repeated,
+                metrics.missed,
+                metrics.dropped,
+                self.screen_tick_count.load(Ordering::Relaxed),
+                metrics.last_lag_ns as f64 / 1_000.0,
+                metrics.offset_ns as f64 / 1_000.0
+            );
+        }
+        if !enabled && previous {
+            {
+                let mut buffer = self.frame_buffer.lock().unwrap();
+                let metrics = buffer.metrics();
+                println!(
+                    "[CameraSync] Buffer stats before clear: queued={} min={} max={}",
+                    buffer.len(),
+                    metrics.min_queued,
+                    metrics.max_queued
+                );
+                buffer.reset();
+            }
+            self.screen_tick_count.store(0, Ordering::Relaxed);
+            self.device_latency_ns.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Jitter buffer counters for the current session.
+    pub fn metrics(&self) -> JitterMetrics {
+        self.frame_buffer.lock().unwrap().metrics()
+    }
+
+    pub fn push_frame(&self, frame: CameraFramePayload) {
+        let (in_count, buffered_len) = {
+            let mut buffer = self.frame_buffer.lock().unwrap();
+            buffer.push(frame.clone());
+            (buffer.metrics().pushed, buffer.len())
+        };
+
+        if in_count <= 5 || in_count.is_multiple_of(30) {
+            println!(
+                "[CameraSync] Buffered frame #{}, pts={}ns (buffer_len={})",
+                in_count, frame.pts_ns, buffered_len
+            );
+        }
+        if !self.sync_enabled.load(Ordering::Relaxed) {
+            self.emit(frame);
+        }
+    }
+
+    /// Emits the camera frame for the screen frame at `screen_pts_ns` and
+    /// returns it if it is new, for recording next to the screen video.
+    pub fn emit_for_screen_pts(&self, screen_pts_ns: u64) -> Option<CameraFramePayload> {
+        self.last_screen_pts_ns
+            .store(screen_pts_ns, Ordering::Relaxed);
+        let tick = self.screen_tick_count.fetch_add(1, Ordering::Relaxed) + 1;
+        let enabled = self.sync_enabled.load(Ordering::Relaxed);
+        if !enabled {
+            if tick <= 5 || tick.is_multiple_of(60) {
+                println!(
+                    "[CameraSync] Screen tick #{} (pts={}ns) ignored because sync not enabled",
+                    tick, screen_pts_ns
+                );
+            }
+            return None;
+        }
+
+        let (pulled, remaining, metrics) = {
+            let mut buffer = self.frame_buffer.lock().unwrap();
+            let pulled = buffer.pull(screen_pts_ns);
+            (pulled, buffer.len(), buffer.metrics())
+        };
+
+        match pulled {
+            Pull::Fresh(frame) => {
+                let lag_ns = metrics.last_lag_ns;
+                let out_count = metrics.emitted;
+                if out_count <= 5 || out_count.is_multiple_of(30) || lag_ns > 25_000_000 {
+                    println!(
+                        "[CameraSync] Emit frame #{}, screen_pts={}ns cam_pts={}ns Δ={}µs (buffer_len={})",
+                        out_count,
+                        screen_pts_ns,
+                        frame.pts_ns,
+                        lag_ns as f64 / 1_000.0,
+                        remaining
+                    );
+                }
+                self.emit(frame.clone());
+                Some(frame)
+            }
+            Pull::Repeated(frame) => {
+                println!(
+                    "[CameraSync] Reusing last frame for screen pts {}ns (buffer_len={}, offset={}µs)",
+                    screen_pts_ns,
+                    remaining,
+                    metrics.offset_ns as f64 / 1_000.0
+                );
+                self.emit(frame);
+                None
+            }
+            Pull::Empty => {
+                println!(
+                    "[CameraSync] ⚠️ No camera frame available for screen pts {}ns (buffer_len={})",
+                    screen_pts_ns, remaining
+                );
+                None
+            }
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut guard = self.frame_buffer.lock().unwrap();
+        let len = guard.len();
+        guard.clear();
+        println!("[CameraSync] Cleared frame buffer (dropped {} frames)", len);
+    }
+
+    fn emit(&self, frame: CameraFramePayload) {
+        self.emit_event(CoreEvent::CameraFrame(frame));
+    }
+
+    fn emit_event(&self, event: CoreEvent) {
+        if let Some(sink) = self.event_sink.lock().unwrap().as_ref() {
+            sink.emit(event);
+        }
+    }
+}
+
+impl Default for CameraSyncHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct CameraPreview {
+    is_running: Arc<Mutex<bool>>,
+    sync_handle: Arc<CameraSyncHandle>,
+    ffmpeg_locator: Arc<FfmpegLocator>,
+    device: Option<Arc<dyn CameraDevice>>,
+}
+
+impl CameraPreview {
+    pub fn new(ffmpeg_locator: Arc<FfmpegLocator>) -> Self {
+        let handle = Arc::new(CameraSyncHandle::new());
+        Self {
+            is_running: Arc::new(Mutex::new(false)),
+            sync_handle: handle,
+            ffmpeg_locator,
+            device: None,
+        }
+    }
+
+    /// Uses `device` instead of resolving the platform camera on each start.
+    pub fn with_device(ffmpeg_locator: Arc<FfmpegLocator>, device: Arc<dyn CameraDevice>) -> Self {
+        Self {
+            device: Some(device),
+            ..Self::new(ffmpeg_locator)
+        }
+    }
+
+    pub fn sync_handle(&self) -> Arc<CameraSyncHandle> {
+        self.sync_handle.clone()
+    }
+
+    pub fn set_event_sink(&mut self, sink: Arc<dyn EventSink>) {
+        self.sync_handle.set_event_sink(sink);
+    }
+
+    pub fn is_running(&self) -> bool {
+        *self.is_running.lock().unwrap()
+    }
+
+    pub fn start(&self) -> CoreResult<()> {
+        let mut is_running = self.is_running.lock().unwrap();
+
+        if *is_running {
+            // Already running, just return success
+            return Ok(());
+        }
+
+        let device = match &self.device {
+            Some(device) => device.clone(),
+            None => camera_device::default_device()?,
+        };
+        println!("[CameraPreview] Starting camera preview from {}", device.label());
+        self.sync_handle.set_device_id(device.id());
+        let camera_fps = self.sync_handle.camera_fps();
+
+        *is_running = true;
+
+        let is_running_clone = self.is_running.clone();
+        let sync_handle_clone = self.sync_handle.clone();
+        let ffmpeg_locator = self.ffmpeg_locator.clone();
+
+        // Read the device in a separate thread
+        thread::spawn(move || {
+            let stream = match device.open_mjpeg(&ffmpeg_locator, camera_fps) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    let error_msg = e.to_string();
+                    eprintln!("[CameraPreview] ERROR: {}", error_msg);
+                    *is_running_clone.lock().unwrap() = false;
+
+                    sync_handle_clone.emit_event(CoreEvent::CameraError {
+                        message: error_msg,
+                    });
+                    return;
+                }
+            };
+
+            let MjpegStream {
+                mut reader,
+                mut process,
+                width,
+                height,
+            } = stream;
+            let mut frame_id = 0u64;
+            let mut splitter = MjpegSplitter::new();
+            let mut buffer = [0u8; 65536]; // Larger buffer for better performance
+            let mut last_frame_time = std::time::Instant::now();
+
+            while *is_running_clone.lock().unwrap() {
+                match reader.read(&mut buffer) {
+                    Ok(0) => break, // EOF
+                    Ok(n) => {
+                        for jpeg_data in splitter.push(&buffer[..n]) {
+                            // Only emit if enough time has passed (throttle to the
+                            // camera rate, which follows recordings started later)
+                            let now = std::time::Instant::now();
+                            let min_interval_ms = 1000 / u128::from(sync_handle_clone.camera_fps());
+                            if now.duration_since(last_frame_time).as_millis() < min_interval_ms {
+                                continue;
+                            }
+                            let base64_frame = general_purpose::STANDARD.encode(&jpeg_data);
+                            let pts_ns = sync_handle_clone.capture_pts_ns();
+
+                            sync_handle_clone.push_frame(CameraFramePayload {
+                                id: frame_id,
+                                width,
+                                height,
+                                format: "jpeg",
+                                data_base64: base64_frame,
+                                pts_ns,
+                            });
+
+                            frame_id += 1;
+                            last_frame_time = now;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+
+            if let Some(process) = process.as_mut() {
+                let _ = process.kill();
+                let _ = process.wait();
+            }
+        });
+
+        Ok(())
+    }
+
+    pub fn stop(&self) -> CoreResult<()> {
+        let mut is_running = self.is_running.lock().unwrap();
+
+        if !*is_running {
+            // Already stopped, return success
+            return Ok(());
+        }
+
+        *is_running = false;
+        self.sync_handle.set_sync_enabled(false);
+        self.sync_handle.clear();
+
+        // Note: The reader thread will detect is_running=false, kill its FFmpeg
+        // process (if any) and exit on its own
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CameraFramePayload, CameraSyncHandle};
+    use crate::events::CoreEvent;
+    use crate::models::{DeviceOffset, RecordingOptions};
+    use crate::time::{MediaClock, MockClock};
+    use std::sync::{mpsc, Arc};
+    use std::time::Duration;
+
+    #[test]
+    fn pairs_screen_ticks_with_frames_on_the_shared_clock() {
+        let clock = Arc::new(MockClock::new(1_000_000_000));
+        let sync = CameraSyncHandle::with_clock(clock.clone());
+        let (tx, rx) = mpsc::channel();
+        sync.set_event_sink(Arc::new(tx));
+        sync.set_sync_enabled(true);
+
+        for id in 0..3 {
+            sync.push_frame(CameraFramePayload {
+                id,
+                width: 2,
+                height: 2,
+                format: "jpeg",
+                data_base64: String::new(),
+                pts_ns: sync.clock().now_ns(),
+            });
+            clock.advance(Duration::from_millis(33));
+        }
+
+        // Screen frame at 1.099 s; with the initial 30 ms offset the newest
+        // camera frame at or before 1.069 s is #2 (1.066 s).
+        sync.emit_for_screen_pts(clock.now_ns());
+        match rx.try_recv() {
+            Ok(CoreEvent::CameraFrame(frame)) => assert_eq!(frame.id, 2),
+            other => panic!("expected a camera frame, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn stamps_frames_earlier_by_the_camera_latency() {
+        let clock = Arc::new(MockClock::new(1_000_000_000));
+        let sync = CameraSyncHandle::with_clock(clock.clone());
+        let mut options = RecordingOptions {
+            include_camera: true,
+            ..RecordingOptions::default()
+        };
+        options.device_offsets.insert(
+            "/dev/video0".to_string(),
+            DeviceOffset {
+                audio_ms: 0.0,
+                video_ms: 80.0,
+            },
+        );
+
+        // Unknown camera: nothing to compensate.
+        sync.apply_device_offsets(&options);
+        assert_eq!(sync.capture_pts_ns(), 1_000_000_000);
+
+        sync.set_device_id("/dev/video0".to_string());
+        sync.apply_device_offsets(&options);
+        assert_eq!(sync.capture_pts_ns(), 920_000_000);
+
+        // Cleared with the rest of the session state.
+        sync.set_sync_enabled(true);
+        sync.set_sync_enabled(false);
+        assert_eq!(sync.capture_pts_ns(), 1_000_000_000);
+    }
+}
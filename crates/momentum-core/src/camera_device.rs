@@ -0,0 +1,443 @@
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::{CoreError, CoreResult};
+use crate::models::CaptureMode;
+use crate::platform::macos::ffmpeg::FfmpegLocator;
+use crate::platform::selectable_backend::capture_mode_from_env;
+
+/// Environment variable pointing at an MJPEG file to replay instead of opening
+/// a physical webcam (used by CI and headless test runs).
+pub const CAMERA_FIXTURE_ENV: &str = "MOMENTUM_CAMERA_FIXTURE";
+
+/// A byte stream of concatenated JPEG images plus the size of each image.
+pub struct MjpegStream {
+    pub reader: Box<dyn Read + Send>,
+    /// The producing FFmpeg process, killed when the preview stops.
+    pub process: Option<Child>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Source of camera preview frames. Implementations only need to produce MJPEG;
+/// frame splitting, timestamping and syncing stay in `CameraPreview`.
+pub trait CameraDevice: Send + Sync {
+    fn label(&self) -> String;
+
+    /// Stable key into [`DeviceOffsets`](crate::models::DeviceOffsets).
+    fn id(&self) -> String;
+
+    /// Starts reading at `fps` frames per second.
+    fn open_mjpeg(&self, ffmpeg_locator: &FfmpegLocator, fps: u32) -> CoreResult<MjpegStream>;
+}
+
+/// Returns the camera used when the preview was not given an explicit device:
+/// the fixture named by [`CAMERA_FIXTURE_ENV`] if set, the `testsrc2`
+/// generator in synthetic capture mode, else the platform default.
+pub fn default_device() -> CoreResult<Arc<dyn CameraDevice>> {
+    if let Ok(path) = std::env::var(CAMERA_FIXTURE_ENV) {
+        return Ok(Arc::new(MjpegFileCamera::new(PathBuf::from(path))?));
+    }
+    if capture_mode_from_env() == Some(CaptureMode::Synthetic) {
+        return Ok(Arc::new(SyntheticCamera));
+    }
+    platform_default_device()
+}
+
+#[cfg(target_os = "macos")]
+fn platform_default_device() -> CoreResult<Arc<dyn CameraDevice>> {
+    Ok(Arc::new(AvFoundationCamera::resolve()))
+}
+
+#[cfg(target_os = "linux")]
+fn platform_default_device() -> CoreResult<Arc<dyn CameraDevice>> {
+    use crate::platform::linux::v4l2::V4l2Camera;
+
+    match V4l2Camera::first_available()? {
+        Some(camera) => Ok(Arc::new(camera)),
+        None => Err(CoreError::Camera("No V4L2 capture device found".to_string())),
+    }
+}
+
+/// Spawns an FFmpeg command that writes MJPEG to stdout and wraps it as a stream.
+pub fn spawn_mjpeg_ffmpeg(mut cmd: Command, width: u32, height: u32) -> CoreResult<MjpegStream> {
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped()); // Capture stderr for debugging
+
+    let mut process = cmd.spawn().map_err(|e| {
+        CoreError::Camera(format!(
+            "Failed to spawn camera FFmpeg process: {}. FFmpeg path used: {}",
+            e,
+            cmd.get_program().to_string_lossy()
+        ))
+    })?;
+    println!(
+        "[CameraPreview] FFmpeg process spawned successfully (PID: {})",
+        process.id()
+    );
+
+    // Read stderr in a separate thread to capture errors (but don't log everything)
+    if let Some(mut stderr) = process.stderr.take() {
+        thread::spawn(move || {
+            let mut buffer = [0u8; 1024];
+            while let Ok(n) = stderr.read(&mut buffer) {
+                if n == 0 {
+                    break;
+                }
+                let error_msg = String::from_utf8_lossy(&buffer[..n]);
+                // Only log actual errors, not warnings or info
+                if error_msg.contains("Error") || error_msg.contains("error") {
+                    eprintln!("Camera FFmpeg error: {}", error_msg);
+                }
+            }
+        });
+    }
+
+    let stdout = process
+        .stdout
+        .take()
+        .ok_or_else(|| CoreError::Camera("Failed to capture camera FFmpeg stdout".to_string()))?;
+
+    Ok(MjpegStream {
+        reader: Box::new(stdout),
+        process: Some(process),
+        width,
+        height,
+    })
+}
+
+/// Built-in Mac camera through FFmpeg's AVFoundation input.
+#[cfg(target_os = "macos")]
+pub struct AvFoundationCamera {
+    index: i32,
+}
+
+#[cfg(target_os = "macos")]
+impl AvFoundationCamera {
+    /// Looks the built-in camera up with the Swift resolver, falling back to 0.
+    pub fn resolve() -> Self {
+        use crate::platform::device_resolver;
+
+        let index = match device_resolver::resolve_avf_indices() {
+            Ok(devices) => match devices.get_camera_index() {
+                Ok(idx) => {
+                    println!("[CameraPreview] Resolved built-in camera index: {}", idx);
+                    idx
+                }
+                Err(e) => {
+                    eprintln!(
+                        "[CameraPreview] Failed to resolve camera index: {}, falling back to 0",
+                        e
+                    );
+                    0
+                }
+            },
+            Err(e) => {
+                eprintln!(
+                    "[CameraPreview] Failed to resolve device indices: {}, falling back to 0",
+                    e
+                );
+                0
+            }
+        };
+        Self { index }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl CameraDevice for AvFoundationCamera {
+    fn label(&self) -> String {
+        format!("AVFoundation camera #{}", self.index)
+    }
+
+    fn id(&self) -> String {
+        format!("avfoundation:video:{}", self.index)
+    }
+
+    fn open_mjpeg(&self, ffmpeg_locator: &FfmpegLocator, fps: u32) -> CoreResult<MjpegStream> {
+        let ffmpeg_path = ffmpeg_locator
+            .resolve()
+            .map_err(|err| CoreError::Camera(err.to_string()))?;
+
+        let mut cmd = Command::new(&ffmpeg_path);
+        cmd.args([
+            "-f",
+            "avfoundation",
+            "-framerate",
+            &fps.to_string(),
+            "-video_size",
+            "640x480",
+            "-i",
+            &format!("{}:", self.index), // Built-in camera, no audio
+            "-vf",
+            &format!("fps={}", fps), // Steady rate for a smooth preview
+            "-f",
+            "image2pipe",
+            "-vcodec",
+            "mjpeg",
+            "-q:v",
+            "3", // Lower quality number = higher quality but faster encoding
+            "-",
+        ]);
+
+        spawn_mjpeg_ffmpeg(cmd, 640, 480)
+    }
+}
+
+/// FFmpeg `testsrc2` pattern at 640x480, encoded to MJPEG in real time.
+pub struct SyntheticCamera;
+
+impl CameraDevice for SyntheticCamera {
+    fn label(&self) -> String {
+        "Synthetic camera (testsrc2)".to_string()
+    }
+
+    fn id(&self) -> String {
+        "synthetic:camera".to_string()
+    }
+
+    fn open_mjpeg(&self, ffmpeg_locator: &FfmpegLocator, fps: u32) -> CoreResult<MjpegStream> {
+        let ffmpeg_path = ffmpeg_locator
+            .resolve()
+            .map_err(|err| CoreError::Camera(err.to_string()))?;
+
+        let mut cmd = Command::new(&ffmpeg_path);
+        cmd.args([
+            "-hide_banner",
+            "-loglevel",
+            "warning",
+            "-re",
+            "-f",
+            "lavfi",
+            "-i",
+            &format!("testsrc2=size=640x480:rate={}", fps),
+            "-vf",
+            "format=yuvj420p",
+            "-f",
+            "image2pipe",
+            "-vcodec",
+            "mjpeg",
+            "-q:v",
+            "3",
+            "-",
+        ]);
+        cmd.stdin(Stdio::null());
+
+        spawn_mjpeg_ffmpeg(cmd, 640, 480)
+    }
+}
+
+/// Replays a recorded MJPEG file in a loop, standing in for a webcam.
+pub struct MjpegFileCamera {
+    path: PathBuf,
+    frames: Arc<Vec<Vec<u8>>>,
+    width: u32,
+    height: u32,
+}
+
+impl MjpegFileCamera {
+    pub fn new(path: PathBuf) -> CoreResult<Self> {
+        let data = std::fs::read(&path)?;
+        let mut splitter = MjpegSplitter::new();
+        let frames = splitter.push(&data);
+        let (width, height) = frames
+            .first()
+            .and_then(|frame| jpeg_dimensions(frame))
+            .ok_or_else(|| {
+                CoreError::Camera(format!("No JPEG frames found in {}", path.display()))
+            })?;
+        Ok(Self {
+            path,
+            frames: Arc::new(frames),
+            width,
+            height,
+        })
+    }
+}
+
+impl CameraDevice for MjpegFileCamera {
+    fn label(&self) -> String {
+        format!("MJPEG fixture ({})", self.path.display())
+    }
+
+    fn id(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn open_mjpeg(&self, _ffmpeg_locator: &FfmpegLocator, fps: u32) -> CoreResult<MjpegStream> {
+        Ok(MjpegStream {
+            reader: Box::new(PacedFrameReader::new(self.frames.clone(), fps)),
+            process: None,
+            width: self.width,
+            height: self.height,
+        })
+    }
+}
+
+/// Hands out one frame per tick, looping forever, like a live camera would.
+struct PacedFrameReader {
+    frames: Arc<Vec<Vec<u8>>>,
+    frame_interval: Duration,
+    next_due: Instant,
+    frame_idx: usize,
+    offset: usize,
+}
+
+impl PacedFrameReader {
+    fn new(frames: Arc<Vec<Vec<u8>>>, fps: u32) -> Self {
+        Self {
+            frames,
+            frame_interval: Duration::from_secs(1) / fps.max(1),
+            next_due: Instant::now(),
+            frame_idx: 0,
+            offset: 0,
+        }
+    }
+}
+
+impl Read for PacedFrameReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.frames.is_empty() || buf.is_empty() {
+            return Ok(0);
+        }
+        if self.offset == 0 {
+            let now = Instant::now();
+            if self.next_due > now {
+                thread::sleep(self.next_due - now);
+            }
+            self.next_due += self.frame_interval;
+        }
+
+        let frame = &self.frames[self.frame_idx];
+        let len = (frame.len() - self.offset).min(buf.len());
+        buf[..len].copy_from_slice(&frame[self.offset..self.offset + len]);
+        self.offset += len;
+        if self.offset == frame.len() {
+            self.offset = 0;
+            self.frame_idx = (self.frame_idx + 1) % self.frames.len();
+        }
+        Ok(len)
+    }
+}
+
+/// Incrementally splits an MJPEG byte stream on SOI (FF D8) / EOI (FF D9)
+/// markers, tolerating markers that straddle read boundaries.
+#[derive(Debug, Default)]
+pub struct MjpegSplitter {
+    current: Vec<u8>,
+    in_frame: bool,
+    previous: Option<u8>,
+}
+
+impl MjpegSplitter {
+    pub fn new() -> Self {
+        Self {
+            current: Vec::with_capacity(50000), // Pre-allocate for typical JPEG size
+            in_frame: false,
+            previous: None,
+        }
+    }
+
+    /// Feeds bytes and returns every JPEG image completed by them.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut complete = Vec::new();
+        for &byte in bytes {
+            if !self.in_frame {
+                if self.previous == Some(0xFF) && byte == 0xD8 {
+                    self.in_frame = true;
+                    self.current.clear();
+                    self.current.extend_from_slice(&[0xFF, 0xD8]);
+                }
+            } else {
+                self.current.push(byte);
+                if self.previous == Some(0xFF) && byte == 0xD9 {
+                    complete.push(std::mem::take(&mut self.current));
+                    self.in_frame = false;
+                    self.previous = None;
+                    continue;
+                }
+            }
+            self.previous = Some(byte);
+        }
+        complete
+    }
+}
+
+/// Reads the image size from the first SOF marker of a JPEG.
+pub fn jpeg_dimensions(jpeg: &[u8]) -> Option<(u32, u32)> {
+    let mut idx = 2;
+    while idx + 9 < jpeg.len() {
+        if jpeg[idx] != 0xFF {
+            return None;
+        }
+        let marker = jpeg[idx + 1];
+        let segment_len = u16::from_be_bytes([jpeg[idx + 2], jpeg[idx + 3]]) as usize;
+        let is_sof = matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+        if is_sof {
+            let height = u16::from_be_bytes([jpeg[idx + 5], jpeg[idx + 6]]) as u32;
+            let width = u16::from_be_bytes([jpeg[idx + 7], jpeg[idx + 8]]) as u32;
+            return Some((width, height));
+        }
+        idx += 2 + segment_len;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{jpeg_dimensions, MjpegSplitter, PacedFrameReader};
+    use std::io::Read;
+    use std::sync::Arc;
+
+    fn fake_jpeg(width: u16, height: u16) -> Vec<u8> {
+        let mut jpeg = vec![0xFF, 0xD8];
+        // APP0 segment with a 2-byte payload
+        jpeg.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00]);
+        // SOF0: length 11, precision 8, height, width, 1 component
+        jpeg.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x0B, 0x08]);
+        jpeg.extend_from_slice(&height.to_be_bytes());
+        jpeg.extend_from_slice(&width.to_be_bytes());
+        jpeg.extend_from_slice(&[0x01, 0x01, 0x11, 0x00]);
+        jpeg.extend_from_slice(&[0xFF, 0xD9]);
+        jpeg
+    }
+
+    #[test]
+    fn splits_frames_across_chunk_boundaries() {
+        let frame = fake_jpeg(640, 480);
+        let mut stream = vec![0x00, 0x42];
+        stream.extend_from_slice(&frame);
+        stream.extend_from_slice(&frame);
+
+        let mut splitter = MjpegSplitter::new();
+        let mut frames = Vec::new();
+        for chunk in stream.chunks(3) {
+            frames.extend(splitter.push(chunk));
+        }
+        assert_eq!(frames, vec![frame.clone(), frame]);
+    }
+
+    #[test]
+    fn reads_jpeg_dimensions_from_sof() {
+        assert_eq!(jpeg_dimensions(&fake_jpeg(1280, 720)), Some((1280, 720)));
+        assert_eq!(jpeg_dimensions(&[0xFF, 0xD8, 0xFF, 0xD9]), None);
+    }
+
+    #[test]
+    fn paced_reader_loops_over_fixture_frames() {
+        let frames = Arc::new(vec![fake_jpeg(2, 2), fake_jpeg(4, 4)]);
+        let mut reader = PacedFrameReader::new(frames.clone(), 1000);
+        let mut splitter = MjpegSplitter::new();
+        let mut replayed = Vec::new();
+        let mut buffer = [0u8; 7];
+        while replayed.len() < 3 {
+            let n = reader.read(&mut buffer).expect("read");
+            replayed.extend(splitter.push(&buffer[..n]));
+        }
+        assert_eq!(replayed, vec![frames[0].clone(), frames[1].clone(), frames[0].clone()]);
+    }
+}
@@ -0,0 +1,255 @@
+//! What a recording captures, parsed from `RecordingOptions.screen_target`:
+//!
+//! - `display:<id>`: a whole display
+//! - `window:<id>`: a single window, following it as it moves
+//! - `region:<display id>:<x>,<y>,<width>x<height>`: a rectangle of a display,
+//!   in pixels from its top-left corner. These are physical pixels on every
+//!   backend; ScreenCaptureKit converts them to points with the display's
+//!   scale factor.
+//!
+//! Ids are the capture backend's own: `CGDirectDisplayID` and `CGWindowID`
+//! with ScreenCaptureKit, the X screen number and window XID on X11. The
+//! backends list them as [`CaptureTargetInfo`] through
+//! `platform::list_capture_targets`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CoreError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureTarget {
+    Display(u32),
+    Window(u32),
+    Region { display: u32, rect: PixelRect },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PixelRect {
+    /// The part of this rectangle on a `width` x `height` display, or `None`
+    /// if it lies entirely outside.
+    pub fn clip_to(&self, width: u32, height: u32) -> Option<PixelRect> {
+        let right = self.x.saturating_add(self.width).min(width);
+        let bottom = self.y.saturating_add(self.height).min(height);
+        (right > self.x && bottom > self.y).then(|| PixelRect {
+            x: self.x,
+            y: self.y,
+            width: right - self.x,
+            height: bottom - self.y,
+        })
+    }
+
+    /// This rectangle as `(x, y, width, height)` in points, on a display with
+    /// `pixels_per_point` pixels per point (2 on Retina displays).
+    pub fn to_points(&self, pixels_per_point: f64) -> (f64, f64, f64, f64) {
+        let scale = pixels_per_point.max(f64::MIN_POSITIVE);
+        (
+            f64::from(self.x) / scale,
+            f64::from(self.y) / scale,
+            f64::from(self.width) / scale,
+            f64::from(self.height) / scale,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CaptureTargetKind {
+    Display,
+    Window,
+}
+
+/// Position and size on the desktop, in the backend's screen coordinates.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetBounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A display or window the user can pick to record.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureTargetInfo {
+    pub kind: CaptureTargetKind,
+    pub id: u32,
+    /// Value for `RecordingOptions.screen_target`.
+    pub target: String,
+    pub title: String,
+    pub owning_app: Option<String>,
+    pub bounds: TargetBounds,
+    /// PNG, base64-encoded; `None` when the target could not be captured.
+    pub thumbnail_base64: Option<String>,
+}
+
+impl CaptureTargetInfo {
+    pub fn new(
+        target: CaptureTarget,
+        title: String,
+        owning_app: Option<String>,
+        bounds: TargetBounds,
+    ) -> Self {
+        let (kind, id) = match target {
+            CaptureTarget::Display(id) | CaptureTarget::Region { display: id, .. } => {
+                (CaptureTargetKind::Display, id)
+            }
+            CaptureTarget::Window(id) => (CaptureTargetKind::Window, id),
+        };
+        Self {
+            kind,
+            id,
+            target: target.to_string(),
+            title,
+            owning_app,
+            bounds,
+            thumbnail_base64: None,
+        }
+    }
+}
+
+/// Width of target thumbnails, in pixels.
+pub const THUMBNAIL_WIDTH: u32 = 320;
+
+/// Thumbnail size for a `width` x `height` target: [`THUMBNAIL_WIDTH`] wide
+/// (or narrower for small windows), keeping the aspect ratio, both even.
+pub fn thumbnail_size(width: u32, height: u32) -> (u32, u32) {
+    let thumb_width = width.clamp(2, THUMBNAIL_WIDTH);
+    let thumb_height =
+        (u64::from(height) * u64::from(thumb_width) / u64::from(width.max(1))) as u32;
+    (thumb_width & !1, (thumb_height & !1).max(2))
+}
+
+impl FromStr for CaptureTarget {
+    type Err = CoreError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || CoreError::Recording(format!("Invalid capture target: {:?}", value));
+        let (kind, rest) = value.split_once(':').ok_or_else(invalid)?;
+        match kind {
+            "display" => rest
+                .parse()
+                .map(CaptureTarget::Display)
+                .map_err(|_| invalid()),
+            "window" => rest
+                .parse()
+                .map(CaptureTarget::Window)
+                .map_err(|_| invalid()),
+            "region" => {
+                let (display, rect) = rest.split_once(':').ok_or_else(invalid)?;
+                let display = display.parse().map_err(|_| invalid())?;
+                let rect = parse_rect(rect).ok_or_else(invalid)?;
+                Ok(CaptureTarget::Region { display, rect })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// `<x>,<y>,<width>x<height>`, with a non-empty size.
+fn parse_rect(value: &str) -> Option<PixelRect> {
+    let (x, rest) = value.split_once(',')?;
+    let (y, size) = rest.split_once(',')?;
+    let (width, height) = size.split_once('x')?;
+    let rect = PixelRect {
+        x: x.trim().parse().ok()?,
+        y: y.trim().parse().ok()?,
+        width: width.trim().parse().ok()?,
+        height: height.trim().parse().ok()?,
+    };
+    (rect.width > 0 && rect.height > 0).then_some(rect)
+}
+
+impl fmt::Display for CaptureTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureTarget::Display(id) => write!(f, "display:{}", id),
+            CaptureTarget::Window(id) => write!(f, "window:{}", id),
+            CaptureTarget::Region { display, rect } => write!(
+                f,
+                "region:{}:{},{},{}x{}",
+                display, rect.x, rect.y, rect.width, rect.height
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{thumbnail_size, CaptureTarget, PixelRect};
+
+    #[test]
+    fn parses_and_formats_every_target() {
+        let region = CaptureTarget::Region {
+            display: 2,
+            rect: PixelRect {
+                x: 100,
+                y: 50,
+                width: 1280,
+                height: 720,
+            },
+        };
+        for (text, target) in [
+            ("display:69733248", CaptureTarget::Display(69733248)),
+            ("window:4242", CaptureTarget::Window(4242)),
+            ("region:2:100,50,1280x720", region),
+        ] {
+            assert_eq!(text.parse::<CaptureTarget>().expect(text), target);
+            assert_eq!(target.to_string(), text);
+        }
+
+        for text in [
+            "",
+            "display",
+            "display:main",
+            "screen:1",
+            "region:2:0,0,0x720",
+        ] {
+            assert!(text.parse::<CaptureTarget>().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn clips_regions_and_converts_them_to_points() {
+        let rect = PixelRect {
+            x: 1600,
+            y: 900,
+            width: 640,
+            height: 360,
+        };
+        assert_eq!(
+            rect.clip_to(1920, 1080),
+            Some(PixelRect {
+                x: 1600,
+                y: 900,
+                width: 320,
+                height: 180,
+            })
+        );
+        assert_eq!(rect.clip_to(1280, 720), None);
+
+        // A 3024x1964 Retina panel is 1512x982 points.
+        assert_eq!(
+            rect.clip_to(3024, 1964).unwrap().to_points(2.0),
+            (800.0, 450.0, 320.0, 180.0)
+        );
+        assert_eq!(rect.to_points(1.0), (1600.0, 900.0, 640.0, 360.0));
+    }
+
+    #[test]
+    fn thumbnails_keep_the_aspect_ratio() {
+        assert_eq!(thumbnail_size(1920, 1080), (320, 180));
+        assert_eq!(thumbnail_size(3024, 1964), (320, 206));
+        assert_eq!(thumbnail_size(201, 75), (200, 74));
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::protocol::{self, EVENT_NOTIFICATION};
+use crate::error::{CoreError, CoreResult};
+
+/// Blocking client for [`super::ControlServer`]. Event notifications that
+/// arrive while waiting for a response are queued for [`Self::next_event`].
+pub struct ControlClient {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+    next_id: u64,
+    pending_events: VecDeque<(String, Value)>,
+}
+
+impl ControlClient {
+    pub fn connect(path: &Path) -> CoreResult<Self> {
+        let writer = UnixStream::connect(path).map_err(|e| {
+            CoreError::Control(format!("Failed to connect to {}: {}", path.display(), e))
+        })?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(Self {
+            reader,
+            writer,
+            next_id: 1,
+            pending_events: VecDeque::new(),
+        })
+    }
+
+    /// Sends one request and waits for its response. JSON-RPC errors come
+    /// back as [`CoreError::Control`] carrying the server's message.
+    pub fn call(&mut self, method: &str, params: Value) -> CoreResult<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let mut line = protocol::request(id, method, params).to_string();
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.flush()?;
+
+        loop {
+            let message = self.read_message()?;
+            if message.get("method").and_then(Value::as_str) == Some(EVENT_NOTIFICATION) {
+                self.pending_events.push_back(split_event(message));
+                continue;
+            }
+            if message.get("id").and_then(Value::as_u64) != Some(id) {
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                let text = error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Unknown error");
+                return Err(CoreError::Control(text.to_string()));
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+
+    /// Blocks until the next event from a `subscribe`d connection.
+    pub fn next_event(&mut self) -> CoreResult<(String, Value)> {
+        if let Some(event) = self.pending_events.pop_front() {
+            return Ok(event);
+        }
+        loop {
+            let message = self.read_message()?;
+            if message.get("method").and_then(Value::as_str) == Some(EVENT_NOTIFICATION) {
+                return Ok(split_event(message));
+            }
+        }
+    }
+
+    fn read_message(&mut self) -> CoreResult<Value> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(CoreError::Control(
+                "Connection closed by server".to_string(),
+            ));
+        }
+        serde_json::from_str(&line)
+            .map_err(|e| CoreError::Control(format!("Invalid message from server: {}", e)))
+    }
+}
+
+fn split_event(mut message: Value) -> (String, Value) {
+    let mut params = message
+        .get_mut("params")
+        .map(Value::take)
+        .unwrap_or_default();
+    let name = params
+        .get("event")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    (
+        name,
+        params
+            .get_mut("payload")
+            .map(Value::take)
+            .unwrap_or_default(),
+    )
+}
@@ -0,0 +1,34 @@
+//! Local control socket: JSON-RPC 2.0, one message per line, over a Unix
+//! domain socket. Lets scripts and launchers drive a running instance and
+//! follow its events.
+
+pub mod client;
+pub mod protocol;
+pub mod server;
+
+use std::path::PathBuf;
+
+use crate::error::{CoreError, CoreResult};
+
+pub use client::ControlClient;
+pub use protocol::RpcError;
+pub use server::{ControlHandler, ControlServer, EventHub};
+
+/// Overrides the socket location, mostly for tests and multiple checkouts.
+pub const CONTROL_SOCKET_ENV: &str = "MOMENTUM_CONTROL_SOCKET";
+
+/// `$MOMENTUM_CONTROL_SOCKET`, else `$XDG_RUNTIME_DIR/momentum.sock`, else
+/// `control.sock` next to the settings file.
+pub fn default_socket_path() -> CoreResult<PathBuf> {
+    if let Some(path) = std::env::var_os(CONTROL_SOCKET_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+    if let Some(runtime_dir) = dirs::runtime_dir() {
+        return Ok(runtime_dir.join("momentum.sock"));
+    }
+    dirs::config_dir()
+        .map(|dir| dir.join("momentum").join("control.sock"))
+        .ok_or_else(|| {
+            CoreError::Control("Could not find a directory for the control socket".to_string())
+        })
+}
@@ -0,0 +1,142 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::error::CoreError;
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// Method name of the notifications pushed to subscribed clients.
+pub const EVENT_NOTIFICATION: &str = "event";
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// Reserved JSON-RPC server error range; used for every failure coming out of
+/// a handler (recording, camera, settings...).
+pub const SERVER_ERROR: i64 = -32000;
+
+/// One line of client input. A request without an `id` is a notification and
+/// gets no response.
+#[derive(Debug, Deserialize)]
+pub struct RpcRequest {
+    #[serde(default)]
+    pub jsonrpc: Option<String>,
+    #[serde(default)]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(METHOD_NOT_FOUND, format!("Unknown method '{}'", method))
+    }
+
+    pub fn invalid_params(message: impl std::fmt::Display) -> Self {
+        Self::new(INVALID_PARAMS, format!("Invalid params: {}", message))
+    }
+
+    pub fn server(message: impl std::fmt::Display) -> Self {
+        Self::new(SERVER_ERROR, message.to_string())
+    }
+}
+
+impl From<CoreError> for RpcError {
+    fn from(err: CoreError) -> Self {
+        Self::server(err)
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+/// Deserializes `params`, treating a missing value as an empty object so
+/// structs whose fields are all optional accept no params at all.
+pub fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = match params {
+        Value::Null => Value::Object(Map::new()),
+        other => other,
+    };
+    serde_json::from_value(params).map_err(RpcError::invalid_params)
+}
+
+pub fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": JSONRPC_VERSION, "id": id, "method": method, "params": params })
+}
+
+pub fn success_response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": JSONRPC_VERSION, "id": id, "result": result })
+}
+
+pub fn error_response(id: Value, error: &RpcError) -> Value {
+    json!({
+        "jsonrpc": JSONRPC_VERSION,
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+pub fn event_notification(event: &str, payload: Value) -> Value {
+    json!({
+        "jsonrpc": JSONRPC_VERSION,
+        "method": EVENT_NOTIFICATION,
+        "params": { "event": event, "payload": payload },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_params, RpcRequest, INVALID_PARAMS};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    #[derive(Debug, Deserialize)]
+    struct MuteParams {
+        muted: bool,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct OptionalParams {
+        #[serde(default)]
+        events: Option<Vec<String>>,
+    }
+
+    #[test]
+    fn parses_requests_and_params() {
+        let request: RpcRequest = serde_json::from_str(
+            r#"{"jsonrpc":"2.0","id":7,"method":"set_mic_muted","params":{"muted":true}}"#,
+        )
+        .expect("request");
+        assert_eq!(request.id, Some(json!(7)));
+        let params: MuteParams = parse_params(request.params).expect("params");
+        assert!(params.muted);
+
+        let notification: RpcRequest =
+            serde_json::from_str(r#"{"method":"stop_recording"}"#).expect("notification");
+        assert_eq!(notification.id, None);
+        let params: OptionalParams = parse_params(notification.params).expect("empty params");
+        assert!(params.events.is_none());
+
+        let err = parse_params::<MuteParams>(Value::Null).expect_err("missing field");
+        assert_eq!(err.code, INVALID_PARAMS);
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::protocol::{
+    error_response, event_notification, parse_params, success_response, RpcError, RpcRequest,
+    INVALID_REQUEST, JSONRPC_VERSION, PARSE_ERROR,
+};
+use crate::error::{CoreError, CoreResult};
+use crate::events::{CoreEvent, EventSink};
+
+/// A subscriber that stops reading must not stall everyone else's events.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Executes the methods a client calls. `subscribe` and `unsubscribe` are
+/// answered by the server itself and never reach the handler.
+pub trait ControlHandler: Send + Sync + 'static {
+    fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError>;
+}
+
+impl<F> ControlHandler for F
+where
+    F: Fn(&str, Value) -> Result<Value, RpcError> + Send + Sync + 'static,
+{
+    fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        self(method, params)
+    }
+}
+
+type SharedStream = Arc<Mutex<UnixStream>>;
+
+struct Subscriber {
+    writer: SharedStream,
+    /// `None` means every event.
+    events: Option<HashSet<String>>,
+}
+
+/// Fan-out of events to every connection that called `subscribe`.
+#[derive(Clone, Default)]
+pub struct EventHub {
+    subscribers: Arc<Mutex<HashMap<u64, Subscriber>>>,
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends `event` to matching subscribers, dropping any whose socket
+    /// fails.
+    pub fn broadcast(&self, event: &str, payload: Value) {
+        let line = event_notification(event, payload);
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|_, subscriber| {
+            let wanted = subscriber
+                .events
+                .as_ref()
+                .map(|events| events.contains(event))
+                .unwrap_or(true);
+            !wanted || write_line(&subscriber.writer, &line).is_ok()
+        });
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().unwrap().len()
+    }
+
+    fn subscribe(&self, connection_id: u64, writer: SharedStream, events: Option<Vec<String>>) {
+        let events = events.map(|events| events.into_iter().collect());
+        self.subscribers
+            .lock()
+            .unwrap()
+            .insert(connection_id, Subscriber { writer, events });
+    }
+
+    fn unsubscribe(&self, connection_id: u64) -> bool {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .remove(&connection_id)
+            .is_some()
+    }
+}
+
+impl EventSink for EventHub {
+    fn emit(&self, event: CoreEvent) {
+        self.broadcast(event.name(), event.payload());
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SubscribeParams {
+    #[serde(default)]
+    events: Option<Vec<String>>,
+}
+
+/// Newline-delimited JSON-RPC 2.0 over a Unix domain socket. Each line a
+/// client writes is one request; responses and event notifications come back
+/// one per line on the same connection.
+pub struct ControlServer {
+    path: PathBuf,
+    hub: EventHub,
+    shutdown: Arc<AtomicBool>,
+    connections: Arc<Mutex<HashMap<u64, UnixStream>>>,
+    accept_thread: Option<JoinHandle<()>>,
+}
+
+impl ControlServer {
+    /// Binds `path` and starts accepting clients. A socket file left behind
+    /// by a crashed instance is replaced; a live one is an error.
+    pub fn bind(path: impl Into<PathBuf>, handler: Arc<dyn ControlHandler>) -> CoreResult<Self> {
+        let path = path.into();
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(CoreError::Control(format!(
+                    "Another instance is already listening on {}",
+                    path.display()
+                )));
+            }
+            std::fs::remove_file(&path)?;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        println!("[Control] Listening on {}", path.display());
+
+        let hub = EventHub::new();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let connections = Arc::new(Mutex::new(HashMap::new()));
+        let accept_thread = {
+            let hub = hub.clone();
+            let shutdown = shutdown.clone();
+            let connections = connections.clone();
+            thread::spawn(move || accept_loop(listener, handler, hub, shutdown, connections))
+        };
+
+        Ok(Self {
+            path,
+            hub,
+            shutdown,
+            connections,
+            accept_thread: Some(accept_thread),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The hub subscribed clients listen on; embedders push their events here.
+    pub fn events(&self) -> EventHub {
+        self.hub.clone()
+    }
+
+    /// Stops accepting, disconnects every client and removes the socket file.
+    pub fn shutdown(&mut self) {
+        let Some(accept_thread) = self.accept_thread.take() else {
+            return;
+        };
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the blocking accept() so the loop sees the flag.
+        let _ = UnixStream::connect(&self.path);
+        let _ = accept_thread.join();
+
+        for (_, stream) in self.connections.lock().unwrap().drain() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        let _ = std::fs::remove_file(&self.path);
+        println!("[Control] Stopped listening on {}", self.path.display());
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn accept_loop(
+    listener: UnixListener,
+    handler: Arc<dyn ControlHandler>,
+    hub: EventHub,
+    shutdown: Arc<AtomicBool>,
+    connections: Arc<Mutex<HashMap<u64, UnixStream>>>,
+) {
+    let next_id = AtomicU64::new(1);
+    for stream in listener.incoming() {
+        if shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("[Control] Failed to accept client: {}", err);
+                continue;
+            }
+        };
+
+        let connection_id = next_id.fetch_add(1, Ordering::SeqCst);
+        if let Ok(clone) = stream.try_clone() {
+            connections.lock().unwrap().insert(connection_id, clone);
+        }
+        let handler = handler.clone();
+        let hub = hub.clone();
+        let connections = connections.clone();
+        thread::spawn(move || {
+            if let Err(err) = serve_connection(stream, connection_id, handler.as_ref(), &hub) {
+                eprintln!("[Control] Client {} disconnected: {}", connection_id, err);
+            }
+            hub.unsubscribe(connection_id);
+            connections.lock().unwrap().remove(&connection_id);
+        });
+    }
+}
+
+fn serve_connection(
+    stream: UnixStream,
+    connection_id: u64,
+    handler: &dyn ControlHandler,
+    hub: &EventHub,
+) -> std::io::Result<()> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let writer: SharedStream = Arc::new(Mutex::new(stream.try_clone()?));
+    let reader = BufReader::new(stream);
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut request: RpcRequest = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(err) => {
+                let error = RpcError::new(PARSE_ERROR, format!("Parse error: {}", err));
+                write_line(&writer, &error_response(Value::Null, &error))?;
+                continue;
+            }
+        };
+
+        let id = request.id.take();
+        let result = dispatch(request, connection_id, &writer, handler, hub);
+        let Some(id) = id else {
+            continue;
+        };
+        let response = match result {
+            Ok(value) => success_response(id, value),
+            Err(error) => error_response(id, &error),
+        };
+        write_line(&writer, &response)?;
+    }
+    Ok(())
+}
+
+fn dispatch(
+    request: RpcRequest,
+    connection_id: u64,
+    writer: &SharedStream,
+    handler: &dyn ControlHandler,
+    hub: &EventHub,
+) -> Result<Value, RpcError> {
+    let version = request.jsonrpc.as_deref();
+    if version.is_some_and(|version| version != JSONRPC_VERSION) {
+        return Err(RpcError::new(
+            INVALID_REQUEST,
+            format!("Unsupported jsonrpc version, expected {}", JSONRPC_VERSION),
+        ));
+    }
+
+    match request.method.as_str() {
+        "subscribe" => {
+            let params: SubscribeParams = parse_params(request.params)?;
+            hub.subscribe(connection_id, writer.clone(), params.events);
+            Ok(json!({ "subscribed": true }))
+        }
+        "unsubscribe" => {
+            Ok(json!({ "subscribed": false, "wasSubscribed": hub.unsubscribe(connection_id) }))
+        }
+        method => handler.handle(method, request.params),
+    }
+}
+
+fn write_line(writer: &SharedStream, value: &Value) -> std::io::Result<()> {
+    let mut line = value.to_string();
+    line.push('\n');
+    let mut stream = writer.lock().unwrap();
+    stream.write_all(line.as_bytes())?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ControlHandler, ControlServer};
+    use crate::control::client::ControlClient;
+    use crate::control::protocol::RpcError;
+    use crate::events::{CoreEvent, EventSink};
+    use serde_json::{json, Value};
+    use std::os::unix::net::UnixListener;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use tempfile::tempdir;
+
+    fn handler() -> Arc<dyn ControlHandler> {
+        Arc::new(|method: &str, params: Value| match method {
+            "echo" => Ok(params),
+            "fail" => Err(RpcError::server("Recording error: not recording")),
+            _ => Err(RpcError::method_not_found(method)),
+        })
+    }
+
+    fn wait_for_subscribers(server: &ControlServer, count: usize) {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while server.events().subscriber_count() != count {
+            assert!(
+                Instant::now() < deadline,
+                "subscriber count never reached {}",
+                count
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn answers_requests_and_reports_errors() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("control.sock");
+        let server = ControlServer::bind(&path, handler()).expect("bind");
+
+        let mut client = ControlClient::connect(&path).expect("connect");
+        assert_eq!(
+            client.call("echo", json!({ "a": 1 })).expect("echo"),
+            json!({ "a": 1 })
+        );
+
+        let err = client.call("fail", Value::Null).expect_err("fail");
+        assert_eq!(
+            err.to_string(),
+            "Control error: Recording error: not recording"
+        );
+        let err = client.call("nope", Value::Null).expect_err("unknown");
+        assert_eq!(err.to_string(), "Control error: Unknown method 'nope'");
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn streams_events_to_matching_subscribers() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("control.sock");
+        let server = ControlServer::bind(&path, handler()).expect("bind");
+
+        let mut everything = ControlClient::connect(&path).expect("connect");
+        everything
+            .call("subscribe", Value::Null)
+            .expect("subscribe");
+        let mut filtered = ControlClient::connect(&path).expect("connect");
+        filtered
+            .call("subscribe", json!({ "events": ["recording-saved"] }))
+            .expect("subscribe");
+        wait_for_subscribers(&server, 2);
+
+        let hub = server.events();
+        hub.emit(CoreEvent::RecordingElapsed { elapsed_ms: 1000 });
+        hub.broadcast("recording-saved", json!({ "path": "/tmp/a.mp4" }));
+
+        let (name, payload) = everything.next_event().expect("elapsed");
+        assert_eq!(name, "recording-elapsed");
+        assert_eq!(payload, json!({ "elapsedMs": 1000 }));
+        assert_eq!(everything.next_event().expect("saved").0, "recording-saved");
+        let (name, payload) = filtered.next_event().expect("saved");
+        assert_eq!(name, "recording-saved");
+        assert_eq!(payload, json!({ "path": "/tmp/a.mp4" }));
+
+        drop(everything);
+        hub.broadcast("recording-saved", Value::Null);
+        wait_for_subscribers(&server, 1);
+    }
+
+    #[test]
+    fn replaces_stale_socket_but_not_a_live_one() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("control.sock");
+        drop(UnixListener::bind(&path).expect("stale"));
+        assert!(path.exists());
+
+        let server = ControlServer::bind(&path, handler()).expect("replace stale socket");
+        assert!(ControlServer::bind(&path, handler()).is_err());
+        drop(server);
+    }
+}
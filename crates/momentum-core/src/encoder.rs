@@ -0,0 +1,354 @@
+//! How recordings are encoded: codec, rate control, speed and keyframes.
+//!
+//! The live video pass and every re-encode at mux time build their FFmpeg
+//! arguments from the same [`EncoderProfile`], so a composited camera or a
+//! camera track comes out in the codec the user picked. The profiles offered
+//! in the settings window ship as data in `encoder_presets.json`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CoreError, CoreResult};
+
+const PRESETS_JSON: &str = include_str!("encoder_presets.json");
+
+/// Longest keyframe interval a profile may ask for.
+pub const MAX_KEYFRAME_INTERVAL_SECS: u32 = 60;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum VideoCodec {
+    #[default]
+    H264,
+    Hevc,
+    Vp9,
+    Av1,
+}
+
+impl VideoCodec {
+    /// FFmpeg encoder used for this codec.
+    pub fn encoder_name(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "libx264",
+            VideoCodec::Hevc => "libx265",
+            VideoCodec::Vp9 => "libvpx-vp9",
+            VideoCodec::Av1 => "libsvtav1",
+        }
+    }
+
+    /// Highest CRF the encoder accepts.
+    fn max_crf(self) -> u8 {
+        match self {
+            VideoCodec::H264 | VideoCodec::Hevc => 51,
+            VideoCodec::Vp9 | VideoCodec::Av1 => 63,
+        }
+    }
+
+    /// Sample entry to write in MP4 when it differs from FFmpeg's default.
+    /// QuickTime only plays HEVC tagged `hvc1`.
+    pub fn mp4_tag(self) -> Option<&'static str> {
+        match self {
+            VideoCodec::Hevc => Some("hvc1"),
+            _ => None,
+        }
+    }
+}
+
+/// Constant quality or average bitrate. Serialized as `{ "crf": 23 }` or
+/// `{ "bitrateKbps": 8000 }`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RateControl {
+    Crf(u8),
+    BitrateKbps(u32),
+}
+
+impl Default for RateControl {
+    fn default() -> Self {
+        RateControl::Crf(23)
+    }
+}
+
+/// Encoder speed, named after x264's `-preset`. VP9 and AV1 map it onto their
+/// own speed settings.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum EncoderSpeed {
+    #[default]
+    Ultrafast,
+    Veryfast,
+    Fast,
+    Medium,
+    Slow,
+}
+
+impl EncoderSpeed {
+    fn x264_preset(self) -> &'static str {
+        match self {
+            EncoderSpeed::Ultrafast => "ultrafast",
+            EncoderSpeed::Veryfast => "veryfast",
+            EncoderSpeed::Fast => "fast",
+            EncoderSpeed::Medium => "medium",
+            EncoderSpeed::Slow => "slow",
+        }
+    }
+
+    /// libvpx `-deadline` and `-cpu-used`.
+    fn vpx_speed(self) -> (&'static str, u8) {
+        match self {
+            EncoderSpeed::Ultrafast => ("realtime", 8),
+            EncoderSpeed::Veryfast => ("realtime", 6),
+            EncoderSpeed::Fast => ("good", 4),
+            EncoderSpeed::Medium => ("good", 2),
+            EncoderSpeed::Slow => ("good", 1),
+        }
+    }
+
+    /// SVT-AV1 `-preset`, 0 (slowest) to 13.
+    fn svt_preset(self) -> u8 {
+        match self {
+            EncoderSpeed::Ultrafast => 12,
+            EncoderSpeed::Veryfast => 10,
+            EncoderSpeed::Fast => 8,
+            EncoderSpeed::Medium => 6,
+            EncoderSpeed::Slow => 4,
+        }
+    }
+}
+
+/// Settings for every video encode of a recording. The video is encoded
+/// live, so slow speeds and the heavier codecs need a machine that keeps up
+/// with the frame rate.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EncoderProfile {
+    #[serde(default)]
+    pub codec: VideoCodec,
+    #[serde(default)]
+    pub rate_control: RateControl,
+    #[serde(default)]
+    pub preset: EncoderSpeed,
+    /// Seconds between forced keyframes; the encoder decides when unset.
+    /// Timed rather than counted in frames since the video is variable
+    /// frame rate. The GOP is capped at the same interval at the nominal
+    /// frame rate, and scene cuts do not add keyframes in between.
+    #[serde(default)]
+    pub keyframe_interval_secs: Option<u32>,
+    /// Archival mode: mathematically lossless, 4:4:4 chroma, and very large.
+    /// Overrides [`Self::rate_control`].
+    #[serde(default)]
+    pub lossless: bool,
+}
+
+impl EncoderProfile {
+    /// Checks values the encoders would reject only once FFmpeg is running.
+    pub fn validate(&self) -> CoreResult<()> {
+        let invalid =
+            |reason: String| CoreError::Recording(format!("Invalid encoder profile: {}", reason));
+        if self.lossless && self.codec == VideoCodec::Av1 {
+            return Err(invalid("AV1 has no lossless mode".to_string()));
+        }
+        match self.rate_control {
+            RateControl::Crf(crf) if crf > self.codec.max_crf() => {
+                return Err(invalid(format!(
+                    "CRF {} is above the {:?} maximum of {}",
+                    crf,
+                    self.codec,
+                    self.codec.max_crf()
+                )));
+            }
+            RateControl::BitrateKbps(0) => return Err(invalid("bitrate is zero".to_string())),
+            _ => {}
+        }
+        match self.keyframe_interval_secs {
+            Some(0) => return Err(invalid("keyframe interval is zero".to_string())),
+            Some(secs) if secs > MAX_KEYFRAME_INTERVAL_SECS => {
+                return Err(invalid(format!(
+                    "keyframe interval of {} s is above the {} s maximum",
+                    secs, MAX_KEYFRAME_INTERVAL_SECS
+                )));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Pixel format the encoders get: full chroma when lossless.
+    pub fn pixel_format(&self) -> &'static str {
+        if self.lossless {
+            "yuv444p"
+        } else {
+            "yuv420p"
+        }
+    }
+
+    /// FFmpeg output options encoding the output stream `stream` (`v`,
+    /// `v:1`, ...) of a `fps` frames per second recording with this profile,
+    /// pixel format included.
+    pub fn video_args(&self, stream: &str, fps: u32) -> Vec<String> {
+        let option = |name: &str| format!("-{}:{}", name, stream);
+        let mut args = vec![option("c"), self.codec.encoder_name().to_string()];
+
+        match self.codec {
+            VideoCodec::H264 | VideoCodec::Hevc => {
+                args.extend([option("preset"), self.preset.x264_preset().to_string()]);
+            }
+            VideoCodec::Vp9 => {
+                let (deadline, cpu_used) = self.preset.vpx_speed();
+                args.extend([
+                    option("deadline"),
+                    deadline.to_string(),
+                    option("cpu-used"),
+                    cpu_used.to_string(),
+                    option("row-mt"),
+                    "1".to_string(),
+                ]);
+            }
+            VideoCodec::Av1 => {
+                args.extend([option("preset"), self.preset.svt_preset().to_string()]);
+            }
+        }
+
+        if self.lossless {
+            match self.codec {
+                VideoCodec::H264 => args.extend([option("qp"), "0".to_string()]),
+                VideoCodec::Hevc => {
+                    args.extend([option("x265-params"), "lossless=1".to_string()]);
+                }
+                VideoCodec::Vp9 => args.extend([option("lossless"), "1".to_string()]),
+                // Rejected by `validate`
+                VideoCodec::Av1 => {}
+            }
+        } else {
+            match self.rate_control {
+                RateControl::Crf(crf) => {
+                    args.extend([option("crf"), crf.to_string()]);
+                    // libvpx only runs constant quality without a target bitrate
+                    if self.codec == VideoCodec::Vp9 {
+                        args.extend([option("b"), "0".to_string()]);
+                    }
+                }
+                RateControl::BitrateKbps(kbps) => {
+                    args.extend([option("b"), format!("{}k", kbps)]);
+                }
+            }
+        }
+
+        if let Some(secs) = self.keyframe_interval_secs {
+            args.extend([
+                option("force_key_frames"),
+                format!("expr:gte(t,n_forced*{})", secs),
+            ]);
+            let gop = (u64::from(secs) * u64::from(fps.max(1))).to_string();
+            args.extend([option("g"), gop.clone()]);
+            match self.codec {
+                VideoCodec::H264 => args.extend([option("sc_threshold"), "0".to_string()]),
+                VideoCodec::Hevc | VideoCodec::Vp9 => args.extend([option("keyint_min"), gop]),
+                // SVT-AV1 only places keyframes on its intra period
+                VideoCodec::Av1 => {}
+            }
+        }
+        args.extend([option("pix_fmt"), self.pixel_format().to_string()]);
+        if let Some(tag) = self.codec.mp4_tag() {
+            args.extend([option("tag"), tag.to_string()]);
+        }
+        args
+    }
+}
+
+/// A named profile offered in the settings window.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EncoderPreset {
+    pub id: String,
+    pub label: String,
+    pub profile: EncoderProfile,
+}
+
+/// The presets from `encoder_presets.json`, in display order.
+pub fn encoder_presets() -> Vec<EncoderPreset> {
+    serde_json::from_str(PRESETS_JSON).expect("encoder_presets.json is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        encoder_presets, EncoderProfile, EncoderSpeed, RateControl, VideoCodec,
+        MAX_KEYFRAME_INTERVAL_SECS,
+    };
+
+    #[test]
+    fn ships_valid_presets() {
+        let presets = encoder_presets();
+        let labels: Vec<&str> = presets.iter().map(|preset| preset.label.as_str()).collect();
+        assert_eq!(labels, ["Small file", "Balanced", "High quality"]);
+        for preset in &presets {
+            preset.profile.validate().expect(&preset.id);
+        }
+        // Existing settings without a profile keep encoding the same way
+        let balanced = presets.iter().find(|preset| preset.id == "balanced");
+        assert_eq!(
+            balanced.map(|preset| preset.profile),
+            Some(EncoderProfile::default())
+        );
+    }
+
+    #[test]
+    fn builds_per_stream_arguments() {
+        let vp9 = EncoderProfile {
+            codec: VideoCodec::Vp9,
+            rate_control: RateControl::Crf(32),
+            preset: EncoderSpeed::Veryfast,
+            keyframe_interval_secs: Some(2),
+            lossless: false,
+        };
+        assert_eq!(
+            vp9.video_args("v:1", 30).join(" "),
+            "-c:v:1 libvpx-vp9 -deadline:v:1 realtime -cpu-used:v:1 6 -row-mt:v:1 1 \
+             -crf:v:1 32 -b:v:1 0 -force_key_frames:v:1 expr:gte(t,n_forced*2) \
+             -g:v:1 60 -keyint_min:v:1 60 -pix_fmt:v:1 yuv420p"
+        );
+        // Every codec caps the GOP at the keyframe interval
+        for (codec, keyframe_args) in [
+            (VideoCodec::H264, "-g:v 240 -sc_threshold:v 0"),
+            (VideoCodec::Hevc, "-g:v 240 -keyint_min:v 240"),
+            (VideoCodec::Vp9, "-g:v 240 -keyint_min:v 240"),
+            (VideoCodec::Av1, "-g:v 240 -pix_fmt:v"),
+        ] {
+            let profile = EncoderProfile {
+                codec,
+                keyframe_interval_secs: Some(4),
+                ..EncoderProfile::default()
+            };
+            let args = profile.video_args("v", 60).join(" ");
+            assert!(args.contains(keyframe_args), "{}", args);
+        }
+
+        let archival = EncoderProfile {
+            codec: VideoCodec::Hevc,
+            lossless: true,
+            ..EncoderProfile::default()
+        };
+        assert_eq!(
+            archival.video_args("v", 30).join(" "),
+            "-c:v libx265 -preset:v ultrafast -x265-params:v lossless=1 -pix_fmt:v yuv444p \
+             -tag:v hvc1"
+        );
+
+        let av1_lossless = EncoderProfile {
+            codec: VideoCodec::Av1,
+            lossless: true,
+            ..EncoderProfile::default()
+        };
+        assert!(av1_lossless.validate().is_err());
+        let crf_too_high = EncoderProfile {
+            rate_control: RateControl::Crf(60),
+            ..EncoderProfile::default()
+        };
+        assert!(crf_too_high.validate().is_err());
+        let keyframes_too_far_apart = EncoderProfile {
+            keyframe_interval_secs: Some(MAX_KEYFRAME_INTERVAL_SECS + 1),
+            ..EncoderProfile::default()
+        };
+        assert!(keyframes_too_far_apart.validate().is_err());
+    }
+}
@@ -0,0 +1,26 @@
+#[derive(Debug, thiserror::Error)]
+pub enum CoreError {
+    #[error("Recording error: {0}")]
+    Recording(String),
+    #[error("Camera error: {0}")]
+    Camera(String),
+    #[error("Settings error: {0}")]
+    Settings(String),
+    #[error("Control error: {0}")]
+    Control(String),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("UTF-8 error: {0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+}
+
+impl serde::Serialize for CoreError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+pub type CoreResult<T> = Result<T, CoreError>;
@@ -0,0 +1,89 @@
+use std::sync::mpsc::{Sender, SyncSender};
+
+use serde_json::{json, Value};
+
+use crate::camera::CameraFramePayload;
+
+/// Notifications produced by the core while it records or previews the
+/// camera. Embedders forward them wherever they need them: the Tauri app
+/// turns each into a window event of the same name.
+#[derive(Debug, Clone)]
+pub enum CoreEvent {
+    RecordingElapsed { elapsed_ms: u64 },
+    CameraFrame(CameraFramePayload),
+    CameraError { message: String },
+}
+
+impl CoreEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoreEvent::RecordingElapsed { .. } => "recording-elapsed",
+            CoreEvent::CameraFrame(_) => "camera-frame",
+            CoreEvent::CameraError { .. } => "camera-error",
+        }
+    }
+
+    /// JSON body matching what the frontend listens for.
+    pub fn payload(&self) -> Value {
+        match self {
+            CoreEvent::RecordingElapsed { elapsed_ms } => json!({ "elapsedMs": elapsed_ms }),
+            CoreEvent::CameraFrame(frame) => serde_json::to_value(frame).unwrap_or(Value::Null),
+            CoreEvent::CameraError { message } => json!({ "message": message }),
+        }
+    }
+}
+
+/// Receiver of [`CoreEvent`]s. Implemented for closures and for `mpsc`
+/// senders, so callers can take events either as callbacks or from a channel.
+pub trait EventSink: Send + Sync + 'static {
+    fn emit(&self, event: CoreEvent);
+}
+
+impl<F> EventSink for F
+where
+    F: Fn(CoreEvent) + Send + Sync + 'static,
+{
+    fn emit(&self, event: CoreEvent) {
+        self(event)
+    }
+}
+
+// A closed channel only means nobody is listening anymore.
+impl EventSink for Sender<CoreEvent> {
+    fn emit(&self, event: CoreEvent) {
+        let _ = self.send(event);
+    }
+}
+
+impl EventSink for SyncSender<CoreEvent> {
+    fn emit(&self, event: CoreEvent) {
+        let _ = self.try_send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoreEvent, EventSink};
+    use serde_json::json;
+    use std::sync::mpsc;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn closures_and_channels_receive_events() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = seen.clone();
+        let callback: Arc<dyn EventSink> =
+            Arc::new(move |event: CoreEvent| seen_clone.lock().unwrap().push(event.name()));
+        callback.emit(CoreEvent::RecordingElapsed { elapsed_ms: 1 });
+        assert_eq!(*seen.lock().unwrap(), vec!["recording-elapsed"]);
+
+        let (tx, rx) = mpsc::channel();
+        let channel: Arc<dyn EventSink> = Arc::new(tx);
+        channel.emit(CoreEvent::CameraError {
+            message: "gone".to_string(),
+        });
+        let event = rx.recv().expect("event");
+        assert_eq!(event.name(), "camera-error");
+        assert_eq!(event.payload(), json!({ "message": "gone" }));
+    }
+}
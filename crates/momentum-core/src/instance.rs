@@ -0,0 +1,191 @@
+//! Single-instance guard. The first process to take an exclusive `flock` on
+//! the lock file owns the devices; later launches forward their requests to
+//! it over the control socket instead of starting a second recorder.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::control::ControlClient;
+use crate::error::{CoreError, CoreResult};
+
+/// How long a second launch waits for the first one to open its control
+/// socket, which happens a moment after the lock is taken.
+const FORWARD_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const FORWARD_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Command-line flags a launch understands, whether it ends up as the
+/// running instance or forwards them to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchAction {
+    Start,
+    Stop,
+    ToggleImmersive,
+    OpenSettings,
+}
+
+impl LaunchAction {
+    pub fn from_flag(flag: &str) -> Option<Self> {
+        match flag {
+            "--start" => Some(LaunchAction::Start),
+            "--stop" => Some(LaunchAction::Stop),
+            "--toggle-immersive" => Some(LaunchAction::ToggleImmersive),
+            "--open-settings" => Some(LaunchAction::OpenSettings),
+            _ => None,
+        }
+    }
+
+    /// Control socket method the running instance executes for this flag.
+    pub fn control_method(self) -> &'static str {
+        match self {
+            LaunchAction::Start => "start_recording",
+            LaunchAction::Stop => "stop_recording",
+            LaunchAction::ToggleImmersive => "toggle_immersive_mode",
+            LaunchAction::OpenSettings => "open_settings",
+        }
+    }
+
+    /// Picks the known flags out of `args`, in order. Anything else (macOS
+    /// adds `-psn_…` when launched from Finder) is ignored.
+    pub fn parse_args<I, S>(args: I) -> Vec<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        args.into_iter()
+            .filter_map(|arg| Self::from_flag(arg.as_ref()))
+            .collect()
+    }
+}
+
+/// Sends `actions` to the instance listening on `socket_path`, retrying the
+/// connection while that instance is still starting up.
+pub fn forward_to_running_instance(socket_path: &Path, actions: &[LaunchAction]) -> CoreResult<()> {
+    let deadline = Instant::now() + FORWARD_CONNECT_TIMEOUT;
+    let mut client = loop {
+        match ControlClient::connect(socket_path) {
+            Ok(client) => break client,
+            Err(err) if Instant::now() >= deadline => return Err(err),
+            Err(_) => std::thread::sleep(FORWARD_RETRY_INTERVAL),
+        }
+    };
+
+    for action in actions {
+        println!("[Instance] Forwarding {:?} to the running instance", action);
+        client.call(action.control_method(), Value::Null)?;
+    }
+    Ok(())
+}
+
+/// Held for the lifetime of the owning process. The kernel drops the lock
+/// when the file is closed, including on a crash, so the file itself is never
+/// deleted.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Returns `Ok(None)` when another process already holds the lock.
+    pub fn acquire(path: &Path) -> CoreResult<Option<Self>> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+
+        let result = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+        if result != 0 {
+            let err = std::io::Error::last_os_error();
+            if err.kind() == std::io::ErrorKind::WouldBlock {
+                return Ok(None);
+            }
+            return Err(CoreError::Io(err));
+        }
+
+        // Only informational: makes `cat instance.lock` show who owns it.
+        file.set_len(0)?;
+        writeln!(file, "{}", std::process::id())?;
+
+        Ok(Some(Self {
+            _file: file,
+            path: path.to_path_buf(),
+        }))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// `instance.lock` next to the settings file.
+pub fn default_lock_path() -> CoreResult<PathBuf> {
+    dirs::config_dir()
+        .map(|dir| dir.join("momentum").join("instance.lock"))
+        .ok_or_else(|| CoreError::Settings("Could not find config directory".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{forward_to_running_instance, InstanceLock, LaunchAction};
+    use crate::control::{ControlServer, RpcError};
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+    use tempfile::tempdir;
+
+    #[test]
+    fn second_acquire_fails_until_first_is_dropped() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("momentum").join("instance.lock");
+
+        let first = InstanceLock::acquire(&path)
+            .expect("acquire")
+            .expect("first owner");
+        assert!(InstanceLock::acquire(&path).expect("acquire").is_none());
+        let pid = std::fs::read_to_string(first.path()).expect("read");
+        assert_eq!(pid.trim(), std::process::id().to_string());
+
+        drop(first);
+        assert!(InstanceLock::acquire(&path).expect("acquire").is_some());
+    }
+
+    #[test]
+    fn forwards_known_flags_as_control_calls() {
+        let actions =
+            LaunchAction::parse_args(["-psn_0_1234", "--toggle-immersive", "--start", "--bogus"]);
+        assert_eq!(
+            actions,
+            vec![LaunchAction::ToggleImmersive, LaunchAction::Start]
+        );
+
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("control.sock");
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let seen = calls.clone();
+        let _server = ControlServer::bind(
+            &path,
+            Arc::new(move |method: &str, _params: Value| {
+                seen.lock().unwrap().push(method.to_string());
+                Ok::<_, RpcError>(json!({ "ok": true }))
+            }),
+        )
+        .expect("bind");
+
+        forward_to_running_instance(&path, &actions).expect("forward");
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "toggle_immersive_mode".to_string(),
+                "start_recording".to_string()
+            ]
+        );
+    }
+}
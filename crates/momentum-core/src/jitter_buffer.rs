@@ -0,0 +1,410 @@
+//! Adaptive jitter buffer that pairs a secondary stream (camera frames) with
+//! the ticks of a reference stream (screen frames). Items wait in pts order;
+//! each tick takes the newest item at or before `tick - offset`.
+//!
+//! After every item served, the offset steers the lag between the tick and
+//! that item toward [`JitterBufferConfig::target_lag_ns`] in 1/8 steps, and
+//! grows further while the newest item pushed is more than half of
+//! [`JitterBufferConfig::max_offset_ns`] behind the tick. That is
+//! [`OffsetController::ServedLag`], the default; [`OffsetController::ArrivalAge`]
+//! instead follows how old items are by the time they are pushed.
+
+use std::collections::VecDeque;
+
+/// Anything stamped on the shared host clock.
+pub trait Timestamped {
+    fn pts_ns(&self) -> u64;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JitterBufferConfig {
+    /// Oldest items are dropped beyond this many.
+    pub capacity: usize,
+    /// Offset used until the first item is served.
+    pub initial_offset_ns: u64,
+    /// Lag between a tick and the item it is served that the offset steers
+    /// toward.
+    pub target_lag_ns: u64,
+    pub max_offset_ns: u64,
+    pub controller: OffsetController,
+}
+
+/// How the offset adapts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OffsetController {
+    /// Steer the lag of each item served toward `target_lag_ns`.
+    #[default]
+    ServedLag,
+    /// Track the age of items when pushed plus `target_lag_ns` of margin,
+    /// rising quickly when they show up late and decaying slowly once they
+    /// are on time again.
+    ArrivalAge,
+}
+
+impl Default for JitterBufferConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 300,
+            initial_offset_ns: 30_000_000,
+            target_lag_ns: 5_000_000,
+            max_offset_ns: 120_000_000,
+            controller: OffsetController::ServedLag,
+        }
+    }
+}
+
+/// Counters since the last [`JitterBuffer::reset`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JitterMetrics {
+    pub pushed: u64,
+    /// Ticks served a new item.
+    pub emitted: u64,
+    /// Ticks served the previous item again because nothing new was due.
+    pub repeated: u64,
+    /// Ticks with nothing to serve at all.
+    pub missed: u64,
+    /// Items never served: skipped for a newer one or evicted at capacity.
+    pub dropped: u64,
+    pub offset_ns: u64,
+    /// Tick pts minus the pts of the last item served.
+    pub last_lag_ns: u64,
+    pub min_queued: usize,
+    pub max_queued: usize,
+}
+
+/// What a tick gets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pull<T> {
+    Fresh(T),
+    Repeated(T),
+    Empty,
+}
+
+pub struct JitterBuffer<T> {
+    config: JitterBufferConfig,
+    items: VecDeque<T>,
+    last_pushed_pts_ns: Option<u64>,
+    /// Newest pts whose arrival age has been fed to the offset.
+    last_observed_pts_ns: Option<u64>,
+    last_served: Option<T>,
+    metrics: JitterMetrics,
+}
+
+impl<T: Timestamped + Clone> JitterBuffer<T> {
+    pub fn new(config: JitterBufferConfig) -> Self {
+        Self {
+            items: VecDeque::with_capacity(config.capacity),
+            last_pushed_pts_ns: None,
+            last_observed_pts_ns: None,
+            last_served: None,
+            metrics: fresh_metrics(&config),
+            config,
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.last_pushed_pts_ns = Some(item.pts_ns());
+        self.items.push_back(item);
+        self.metrics.pushed += 1;
+        while self.items.len() > self.config.capacity.max(1) {
+            self.items.pop_front();
+            self.metrics.dropped += 1;
+        }
+        self.update_queue_stats();
+    }
+
+    /// Serves the tick at `tick_pts_ns`; a fresh item also updates the offset.
+    /// With [`OffsetController::ArrivalAge`], the offset is updated instead
+    /// with the age of anything pushed since the previous tick.
+    pub fn pull(&mut self, tick_pts_ns: u64) -> Pull<T> {
+        if self.config.controller == OffsetController::ArrivalAge {
+            self.observe_arrival(tick_pts_ns);
+        }
+        let due_ns = tick_pts_ns.saturating_sub(self.metrics.offset_ns);
+        let Some(item) = self.take_newest_due(due_ns) else {
+            return match &self.last_served {
+                Some(item) => {
+                    self.metrics.repeated += 1;
+                    Pull::Repeated(item.clone())
+                }
+                None => {
+                    self.metrics.missed += 1;
+                    Pull::Empty
+                }
+            };
+        };
+
+        let lag_ns = tick_pts_ns.saturating_sub(item.pts_ns());
+        let leading_delay_ns = self
+            .last_pushed_pts_ns
+            .map_or(0, |pts_ns| tick_pts_ns.saturating_sub(pts_ns));
+        self.metrics.emitted += 1;
+        self.metrics.last_lag_ns = lag_ns;
+        if self.config.controller == OffsetController::ServedLag {
+            self.adjust_offset(lag_ns, leading_delay_ns);
+        }
+        self.last_served = Some(item.clone());
+        Pull::Fresh(item)
+    }
+
+    /// Drops queued items and the item a repeat would serve.
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.last_pushed_pts_ns = None;
+        self.last_observed_pts_ns = None;
+        self.last_served = None;
+    }
+
+    /// [`Self::clear`], plus metrics and offset back to their initial values.
+    pub fn reset(&mut self) {
+        self.clear();
+        self.metrics = fresh_metrics(&self.config);
+    }
+
+    pub fn metrics(&self) -> JitterMetrics {
+        self.metrics
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    fn take_newest_due(&mut self, due_ns: u64) -> Option<T> {
+        let due = self
+            .items
+            .iter()
+            .take_while(|item| item.pts_ns() <= due_ns)
+            .count();
+        if due == 0 {
+            return None;
+        }
+        self.metrics.dropped += (due - 1) as u64;
+        self.items.drain(..due - 1);
+        let item = self.items.pop_front();
+        self.update_queue_stats();
+        item
+    }
+
+    fn adjust_offset(&mut self, lag_ns: u64, leading_delay_ns: u64) {
+        let target = self.config.target_lag_ns;
+        let max = self.config.max_offset_ns;
+        let mut offset = self.metrics.offset_ns;
+        if lag_ns > target {
+            offset = (offset + ((lag_ns - target) / 8).max(1_000)).min(max);
+        } else {
+            offset = offset.saturating_sub(((target - lag_ns) / 8).max(1_000));
+        }
+        // Falling far behind the newest item: catch up faster.
+        if leading_delay_ns > max / 2 {
+            offset = (offset + leading_delay_ns / 16).min(max);
+        }
+        self.metrics.offset_ns = offset;
+    }
+
+    fn observe_arrival(&mut self, tick_pts_ns: u64) {
+        let Some(pts_ns) = self.last_pushed_pts_ns else {
+            return;
+        };
+        if self.last_observed_pts_ns == Some(pts_ns) {
+            return;
+        }
+        self.last_observed_pts_ns = Some(pts_ns);
+        let arrival_age_ns = tick_pts_ns.saturating_sub(pts_ns);
+        let desired = (arrival_age_ns + self.config.target_lag_ns).min(self.config.max_offset_ns);
+        let offset = self.metrics.offset_ns;
+        // Close a quarter of the gap per arrival going up, a thirty-second
+        // going down, at least 1 µs either way.
+        self.metrics.offset_ns = if desired > offset {
+            (offset + ((desired - offset) / 4).max(1_000)).min(desired)
+        } else {
+            offset
+                .saturating_sub(((offset - desired) / 32).max(1_000))
+                .max(desired)
+        };
+    }
+
+    fn update_queue_stats(&mut self) {
+        let len = self.items.len();
+        self.metrics.min_queued = self.metrics.min_queued.min(len);
+        self.metrics.max_queued = self.metrics.max_queued.max(len);
+    }
+}
+
+impl<T: Timestamped + Clone> Default for JitterBuffer<T> {
+    fn default() -> Self {
+        Self::new(JitterBufferConfig::default())
+    }
+}
+
+fn fresh_metrics(config: &JitterBufferConfig) -> JitterMetrics {
+    JitterMetrics {
+        offset_ns: config.initial_offset_ns,
+        min_queued: usize::MAX,
+        ..JitterMetrics::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JitterBuffer, JitterBufferConfig, OffsetController, Pull, Timestamped};
+    use crate::time::{MediaClock, MockClock};
+    use std::time::Duration;
+
+    const MS: u64 = 1_000_000;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Item {
+        id: u64,
+        pts_ns: u64,
+    }
+
+    impl Timestamped for Item {
+        fn pts_ns(&self) -> u64 {
+            self.pts_ns
+        }
+    }
+
+    fn id(pull: Pull<Item>) -> Option<u64> {
+        match pull {
+            Pull::Fresh(item) => Some(item.id),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn serves_the_newest_due_item_and_repeats_when_starved() {
+        let clock = MockClock::new(1_000 * MS);
+        let mut buffer = JitterBuffer::new(JitterBufferConfig::default());
+        assert_eq!(buffer.pull(clock.now_ns()), Pull::Empty);
+
+        for id in 0..4 {
+            buffer.push(Item {
+                id,
+                pts_ns: clock.now_ns(),
+            });
+            clock.advance(Duration::from_millis(10));
+        }
+        // Tick at 1.040 s, due at 1.010 s with the initial 30 ms offset: #1
+        // is served and #0 is dropped.
+        assert_eq!(id(buffer.pull(clock.now_ns())), Some(1));
+        // Nothing new is due 1 ms later, so #1 repeats.
+        clock.advance(Duration::from_millis(1));
+        assert!(matches!(
+            buffer.pull(clock.now_ns()),
+            Pull::Repeated(Item { id: 1, .. })
+        ));
+
+        let metrics = buffer.metrics();
+        assert_eq!(metrics.pushed, 4);
+        assert_eq!(metrics.emitted, 1);
+        assert_eq!(metrics.repeated, 1);
+        assert_eq!(metrics.missed, 1);
+        assert_eq!(metrics.dropped, 1);
+        assert_eq!(metrics.last_lag_ns, 30 * MS);
+        assert_eq!(metrics.max_queued, 4);
+        assert_eq!(buffer.len(), 2);
+
+        buffer.reset();
+        assert_eq!(buffer.metrics().pushed, 0);
+        assert_eq!(buffer.metrics().offset_ns, 30 * MS);
+        assert_eq!(buffer.pull(clock.now_ns()), Pull::Empty);
+    }
+
+    #[test]
+    fn offset_steers_the_served_lag_toward_the_target() {
+        let config = JitterBufferConfig::default();
+        let mut buffer = JitterBuffer::new(config);
+
+        // Served 45 ms after its pts: 40 ms over the 5 ms target, so the
+        // offset grows by an eighth of that.
+        buffer.push(Item {
+            id: 0,
+            pts_ns: 1_000 * MS,
+        });
+        assert_eq!(id(buffer.pull(1_045 * MS)), Some(0));
+        assert_eq!(buffer.metrics().offset_ns, 35 * MS);
+
+        // The newest item is 70 ms behind the tick, more than half the
+        // 120 ms maximum: on top of the step, jump by a sixteenth of it.
+        buffer.push(Item {
+            id: 1,
+            pts_ns: 1_100 * MS,
+        });
+        assert_eq!(id(buffer.pull(1_170 * MS)), Some(1));
+        assert_eq!(
+            buffer.metrics().offset_ns,
+            35 * MS + 65 * MS / 8 + 70 * MS / 16
+        );
+
+        for id in 2..200 {
+            buffer.push(Item {
+                id,
+                pts_ns: id * 100 * MS,
+            });
+            buffer.pull(id * 100 * MS + 200 * MS);
+        }
+        assert_eq!(buffer.metrics().offset_ns, config.max_offset_ns);
+    }
+
+    #[test]
+    fn arrival_age_controller_follows_how_late_items_arrive() {
+        // A 30 fps camera against 60 fps screen ticks. Frames reach the buffer
+        // 0-12 ms after their pts, then 50 ms more after a USB hiccup.
+        let clock = MockClock::new(1_000 * MS);
+        let mut buffer = JitterBuffer::new(JitterBufferConfig {
+            controller: OffsetController::ArrivalAge,
+            ..JitterBufferConfig::default()
+        });
+        let mut next_pts_ns = clock.now_ns();
+        let mut in_flight = Vec::new();
+        let mut next_id = 0u64;
+        for tick in 0..1_200u64 {
+            clock.advance(Duration::from_nanos(16_666_667));
+            let now_ns = clock.now_ns();
+            while next_pts_ns <= now_ns {
+                let hiccup_ns = if tick < 600 { 0 } else { 50 * MS };
+                let delay_ns = (next_id * 7_919 % 13) * MS + hiccup_ns;
+                in_flight.push((next_pts_ns + delay_ns, next_id, next_pts_ns));
+                next_id += 1;
+                next_pts_ns += 33_333_333;
+            }
+            in_flight.sort();
+            while in_flight
+                .first()
+                .is_some_and(|(arrival_ns, _, _)| *arrival_ns <= now_ns)
+            {
+                let (_, id, pts_ns) = in_flight.remove(0);
+                buffer.push(Item { id, pts_ns });
+            }
+            buffer.pull(now_ns);
+
+            if tick == 599 {
+                // Settled below the initial 30 ms but above the jitter, so
+                // every frame was still waiting when its tick came.
+                let metrics = buffer.metrics();
+                assert!(
+                    (12 * MS..30 * MS).contains(&metrics.offset_ns),
+                    "{:?}",
+                    metrics
+                );
+                assert_eq!(metrics.dropped, 0, "{:?}", metrics);
+                assert_eq!(metrics.emitted, metrics.pushed, "{:?}", metrics);
+            }
+        }
+
+        let metrics = buffer.metrics();
+        assert!(
+            (62 * MS..80 * MS).contains(&metrics.offset_ns),
+            "{:?}",
+            metrics
+        );
+        // Only the frames caught by the jump itself were skipped.
+        assert!(metrics.dropped <= 3, "{:?}", metrics);
+        assert_eq!(metrics.missed, 1);
+        assert!(metrics.max_queued <= 4, "{:?}", metrics);
+    }
+}
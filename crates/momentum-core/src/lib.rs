@@ -0,0 +1,34 @@
+//! Recording engine behind Momentum, free of any UI framework: capture
+//! backends, the mux graph, camera sync and the settings store. Progress is
+//! reported through [`events::EventSink`].
+
+/// Global multiplier applied to microphone samples before they are encoded into
+/// the final recording. Increase to make mic audio louder, decrease to quiet
+/// it down. Keep within a reasonable range (e.g. `0.8..=2.0`) to avoid harsh
+/// dynamics.
+pub const MIC_VOLUME_GAIN: f32 = 1.8;
+
+pub mod calibration;
+pub mod camera;
+pub mod camera_device;
+pub mod capture_target;
+#[cfg(unix)]
+pub mod control;
+pub mod encoder;
+pub mod error;
+pub mod events;
+#[cfg(unix)]
+pub mod instance;
+pub mod jitter_buffer;
+pub mod models;
+pub mod output;
+pub mod platform;
+pub mod recording;
+pub mod settings;
+pub mod time;
+
+pub use camera::{CameraPreview, CameraSyncHandle};
+pub use error::{CoreError, CoreResult};
+pub use events::{CoreEvent, EventSink};
+pub use recording::Recorder;
+pub use settings::SettingsStore;
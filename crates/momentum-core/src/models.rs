@@ -0,0 +1,332 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::capture_target::CaptureTarget;
+use crate::encoder::EncoderProfile;
+use crate::error::CoreResult;
+
+fn default_immersive_shortcut() -> String {
+    "Option+I".to_string()
+}
+
+/// Where captured media comes from. `Synthetic` replaces every device with
+/// FFmpeg test generators so a full session can run headless.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CaptureMode {
+    #[default]
+    Native,
+    Synthetic,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingOptions {
+    pub include_microphone: bool,
+    pub include_camera: bool,
+    /// See [`CaptureTarget`]; the first display when unset.
+    pub screen_target: Option<String>,
+    /// Overrides `MOMENTUM_CAPTURE_MODE` when set.
+    #[serde(default)]
+    pub capture_mode: Option<CaptureMode>,
+    /// Copied from [`AppSettings::device_offsets`] when a session starts.
+    #[serde(default)]
+    pub device_offsets: DeviceOffsets,
+    /// Copied from [`AppSettings::camera_overlay`] when a session starts.
+    #[serde(default)]
+    pub camera_overlay: CameraOverlay,
+    /// Copied from [`AppSettings::frame_rate`] when a session starts.
+    #[serde(default)]
+    pub frame_rate: FrameRate,
+    /// Copied from [`AppSettings::output_scale`] when a session starts.
+    #[serde(default)]
+    pub output_scale: OutputScale,
+    /// Copied from [`AppSettings::encoder`] when a session starts.
+    #[serde(default)]
+    pub encoder: EncoderProfile,
+}
+
+impl RecordingOptions {
+    /// Parses [`Self::screen_target`]; `None` when unset or empty.
+    pub fn capture_target(&self) -> CoreResult<Option<CaptureTarget>> {
+        match self.screen_target.as_deref().map(str::trim) {
+            None | Some("") => Ok(None),
+            Some(target) => target.parse().map(Some),
+        }
+    }
+
+    /// Latency to compensate for audio from `device_id`, in nanoseconds.
+    pub fn audio_latency_ns(&self, device_id: &str) -> i64 {
+        self.device_offsets
+            .get(device_id)
+            .map_or(0, |offset| ms_to_ns(offset.audio_ms))
+    }
+
+    /// Latency to compensate for frames from `device_id`, in nanoseconds.
+    pub fn video_latency_ns(&self, device_id: &str) -> i64 {
+        self.device_offsets
+            .get(device_id)
+            .map_or(0, |offset| ms_to_ns(offset.video_ms))
+    }
+}
+
+fn ms_to_ns(ms: f64) -> i64 {
+    (ms * 1_000_000.0).round() as i64
+}
+
+/// Fixed latency a capture device adds (Bluetooth headsets, USB interfaces),
+/// in milliseconds, compensated on top of the measured alignment. Positive
+/// means the device delivers late, so its media is moved earlier.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceOffset {
+    #[serde(default)]
+    pub audio_ms: f64,
+    #[serde(default)]
+    pub video_ms: f64,
+}
+
+/// Offsets keyed by device id: the Pulse source name or V4L2 path on Linux,
+/// `avfoundation:audio:<index>`, `avfoundation:video:<index>` and
+/// `screencapturekit:system-audio` on macOS, `synthetic:*` for the test
+/// generators. Backends log the ids they use.
+pub type DeviceOffsets = BTreeMap<String, DeviceOffset>;
+
+/// Corner of the screen the camera sits in.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CameraCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CameraShape {
+    Rectangle,
+    Rounded,
+    #[default]
+    Circle,
+}
+
+/// Frames per second requested from the screen, the camera and every
+/// encoder. Serialized as the number.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "u32", into = "u32")]
+pub enum FrameRate {
+    Fps15,
+    Fps24,
+    #[default]
+    Fps30,
+    Fps60,
+}
+
+impl FrameRate {
+    pub fn fps(self) -> u32 {
+        match self {
+            FrameRate::Fps15 => 15,
+            FrameRate::Fps24 => 24,
+            FrameRate::Fps30 => 30,
+            FrameRate::Fps60 => 60,
+        }
+    }
+}
+
+impl TryFrom<u32> for FrameRate {
+    type Error = String;
+
+    fn try_from(fps: u32) -> Result<Self, Self::Error> {
+        match fps {
+            15 => Ok(FrameRate::Fps15),
+            24 => Ok(FrameRate::Fps24),
+            30 => Ok(FrameRate::Fps30),
+            60 => Ok(FrameRate::Fps60),
+            _ => Err(format!(
+                "unsupported frame rate {} (expected 15, 24, 30 or 60)",
+                fps
+            )),
+        }
+    }
+}
+
+impl From<FrameRate> for u32 {
+    fn from(rate: FrameRate) -> Self {
+        rate.fps()
+    }
+}
+
+/// Size of the recorded video relative to the captured screen. Presets cap
+/// the height, a fixed width keeps the aspect ratio; neither scales up.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputScale {
+    #[default]
+    Native,
+    #[serde(rename = "1440p")]
+    P1440,
+    #[serde(rename = "1080p")]
+    P1080,
+    #[serde(rename = "720p")]
+    P720,
+    /// Width in pixels.
+    Width(u32),
+}
+
+impl OutputScale {
+    /// Output size for a `width` x `height` capture, even so it can be
+    /// encoded as 4:2:0.
+    pub fn output_size(self, width: u32, height: u32) -> (u32, u32) {
+        let scale_to = |numerator: u32, denominator: u32| {
+            let scale = |side: u32| {
+                (u64::from(side) * u64::from(numerator) + u64::from(denominator) / 2)
+                    / u64::from(denominator.max(1))
+            };
+            (scale(width) as u32, scale(height) as u32)
+        };
+        let (scaled_width, scaled_height) = match self {
+            OutputScale::P1440 if height > 1440 => scale_to(1440, height),
+            OutputScale::P1080 if height > 1080 => scale_to(1080, height),
+            OutputScale::P720 if height > 720 => scale_to(720, height),
+            OutputScale::Width(target) if target > 0 && target < width => scale_to(target, width),
+            _ => (width, height),
+        };
+        ((scaled_width & !1).max(2), (scaled_height & !1).max(2))
+    }
+}
+
+/// Where the recorded camera ends up.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CameraOutput {
+    /// Composited over the screen as a picture-in-picture.
+    #[default]
+    Composite,
+    /// Second video stream in the recording, for placing it in an editor.
+    Track,
+    /// Separate `<name>.camera.mp4` next to the recording.
+    Sidecar,
+}
+
+/// Placement of the camera picture-in-picture, shared by the on-screen
+/// `camera-overlay` window and the camera composited into the recording.
+/// The camera is cropped to a square in every shape.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraOverlay {
+    #[serde(default)]
+    pub corner: CameraCorner,
+    /// Width as a fraction of the screen width.
+    #[serde(default = "default_camera_overlay_size")]
+    pub size: f64,
+    #[serde(default)]
+    pub shape: CameraShape,
+    #[serde(default)]
+    pub output: CameraOutput,
+}
+
+fn default_camera_overlay_size() -> f64 {
+    0.15
+}
+
+/// Gap between the camera and the screen edges, as a fraction of the screen
+/// width.
+const CAMERA_OVERLAY_MARGIN: f64 = 0.015;
+/// Corner radius of [`CameraShape::Rounded`], as a fraction of its side.
+pub const CAMERA_ROUNDED_RADIUS: f64 = 0.12;
+
+impl CameraOverlay {
+    /// Side of the camera on a `screen_width` wide screen, even so it can be
+    /// encoded as 4:2:0.
+    pub fn side_px(&self, screen_width: u32) -> u32 {
+        let side = (f64::from(screen_width) * self.size.clamp(0.05, 0.5)).round() as u32;
+        (side & !1).max(2)
+    }
+
+    /// Top-left corner of the camera on a `screen_width` x `screen_height`
+    /// screen.
+    pub fn origin_px(&self, screen_width: u32, screen_height: u32) -> (u32, u32) {
+        let side = self.side_px(screen_width);
+        let margin = (f64::from(screen_width) * CAMERA_OVERLAY_MARGIN).round() as u32;
+        let right = screen_width.saturating_sub(side + margin);
+        let bottom = screen_height.saturating_sub(side + margin);
+        match self.corner {
+            CameraCorner::TopLeft => (margin, margin),
+            CameraCorner::TopRight => (right, margin),
+            CameraCorner::BottomLeft => (margin, bottom),
+            CameraCorner::BottomRight => (right, bottom),
+        }
+    }
+}
+
+impl Default for CameraOverlay {
+    fn default() -> Self {
+        Self {
+            corner: CameraCorner::default(),
+            size: default_camera_overlay_size(),
+            shape: CameraShape::default(),
+            output: CameraOutput::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSettings {
+    pub mic_enabled: bool,
+    pub camera_enabled: bool,
+    #[serde(default = "default_immersive_shortcut")]
+    pub immersive_shortcut: String,
+    pub save_location: Option<String>,
+    #[serde(default)]
+    pub device_offsets: DeviceOffsets,
+    #[serde(default)]
+    pub camera_overlay: CameraOverlay,
+    #[serde(default)]
+    pub frame_rate: FrameRate,
+    #[serde(default)]
+    pub output_scale: OutputScale,
+    #[serde(default)]
+    pub encoder: EncoderProfile,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            mic_enabled: false,
+            camera_enabled: false,
+            immersive_shortcut: default_immersive_shortcut(),
+            save_location: None,
+            device_offsets: DeviceOffsets::new(),
+            camera_overlay: CameraOverlay::default(),
+            frame_rate: FrameRate::default(),
+            output_scale: OutputScale::default(),
+            encoder: EncoderProfile::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OutputScale;
+
+    #[test]
+    fn output_scale_only_scales_down() {
+        // Retina MacBook Pro panel
+        assert_eq!(OutputScale::Native.output_size(3024, 1964), (3024, 1964));
+        assert_eq!(OutputScale::P1440.output_size(3024, 1964), (2216, 1440));
+        assert_eq!(OutputScale::P1080.output_size(3024, 1964), (1662, 1080));
+        assert_eq!(OutputScale::P720.output_size(3024, 1964), (1108, 720));
+        assert_eq!(
+            OutputScale::Width(1600).output_size(3024, 1964),
+            (1600, 1038)
+        );
+
+        assert_eq!(OutputScale::P1080.output_size(1280, 720), (1280, 720));
+        assert_eq!(OutputScale::Width(1920).output_size(1280, 720), (1280, 720));
+        assert_eq!(OutputScale::Native.output_size(1281, 721), (1280, 720));
+    }
+}
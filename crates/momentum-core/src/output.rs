@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::error::{CoreError, CoreResult};
+use crate::models::AppSettings;
+
+/// The configured save location, or the user's Downloads folder.
+pub fn resolve_output_dir(settings: &AppSettings) -> CoreResult<PathBuf> {
+    if let Some(path) = &settings.save_location {
+        return Ok(PathBuf::from(path));
+    }
+
+    dirs::download_dir().ok_or_else(|| {
+        CoreError::Recording("Failed to resolve downloads directory".to_string())
+    })
+}
+
+/// `<output dir>/momentum-recording-<unix seconds>.mp4`
+pub fn default_output_path(settings: &AppSettings) -> CoreResult<PathBuf> {
+    let target_dir = resolve_output_dir(settings)?;
+    Ok(target_dir.join(format!(
+        "momentum-recording-{}.mp4",
+        current_time_seconds()
+    )))
+}
+
+/// `<dir>/<stem>.camera.mp4`, where the camera goes when it is recorded as a
+/// sidecar (see [`CameraOutput::Sidecar`]).
+///
+/// [`CameraOutput::Sidecar`]: crate::models::CameraOutput::Sidecar
+pub fn camera_sidecar_path(video_path: &Path) -> PathBuf {
+    let stem = video_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    video_path.with_file_name(format!("{}.camera.mp4", stem))
+}
+
+/// Moves a finished recording from its temp location to `final_path`,
+/// creating parent directories as needed. A camera sidecar next to the
+/// recording is moved along with it.
+pub fn save_recording_file(temp_path: &Path, final_path: &Path) -> CoreResult<()> {
+    if let Some(parent) = final_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    if !temp_path.exists() {
+        return Err(CoreError::Recording(format!(
+            "Recording file not found: {:?}",
+            temp_path
+        )));
+    }
+
+    std::fs::copy(temp_path, final_path)?;
+    if !final_path.exists() {
+        return Err(CoreError::Recording(format!(
+            "Output file was not created: {:?}",
+            final_path
+        )));
+    }
+
+    let _ = std::fs::remove_file(temp_path);
+
+    let temp_sidecar = camera_sidecar_path(temp_path);
+    if temp_sidecar.exists() {
+        std::fs::copy(&temp_sidecar, camera_sidecar_path(final_path))?;
+        let _ = std::fs::remove_file(&temp_sidecar);
+    }
+    Ok(())
+}
+
+fn current_time_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_else(|_| Duration::from_secs(0))
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{camera_sidecar_path, default_output_path, save_recording_file};
+    use crate::models::AppSettings;
+    use tempfile::tempdir;
+
+    #[test]
+    fn moves_recording_into_nested_directory() {
+        let dir = tempdir().expect("tempdir");
+        let temp_path = dir.path().join("momentum_screen.mp4");
+        std::fs::write(&temp_path, b"mp4").expect("write");
+        let final_path = dir.path().join("exports").join("demo.mp4");
+
+        save_recording_file(&temp_path, &final_path).expect("save");
+        assert_eq!(std::fs::read(&final_path).expect("read"), b"mp4");
+        assert!(!temp_path.exists());
+        assert!(save_recording_file(&temp_path, &final_path).is_err());
+    }
+
+    #[test]
+    fn moves_camera_sidecar_with_the_recording() {
+        let dir = tempdir().expect("tempdir");
+        let temp_path = dir.path().join("momentum_screen.mp4");
+        std::fs::write(&temp_path, b"mp4").expect("write");
+        std::fs::write(camera_sidecar_path(&temp_path), b"camera").expect("write");
+        let final_path = dir.path().join("demo.mp4");
+
+        save_recording_file(&temp_path, &final_path).expect("save");
+        let sidecar = dir.path().join("demo.camera.mp4");
+        assert_eq!(camera_sidecar_path(&final_path), sidecar);
+        assert_eq!(std::fs::read(&sidecar).expect("read"), b"camera");
+        assert!(!camera_sidecar_path(&temp_path).exists());
+    }
+
+    #[test]
+    fn default_path_uses_save_location() {
+        let settings = AppSettings {
+            save_location: Some("/tmp/momentum".to_string()),
+            ..AppSettings::default()
+        };
+        let path = default_output_path(&settings).expect("path");
+        assert!(path.starts_with("/tmp/momentum"));
+        assert!(path.to_string_lossy().ends_with(".mp4"));
+    }
+}
@@ -0,0 +1,437 @@
+//! Measures how far the mic lags the system audio when it picks up the
+//! speakers, by cross-correlating their mixer stems. Chunk timestamps place
+//! the mic by when reads completed, which still includes device start-up and
+//! pipe latency; the echo of the system audio does not lie about that. When the
+//! match is clear the mix is rebuilt from the stems with the mic shifted.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::platform::audio_mixer::{MixStem, MixSummary, MIX_CHANNELS, MIX_SAMPLE_RATE};
+use crate::time::duration_to_ns;
+
+/// Stems are averaged down to this rate before correlating.
+const ANALYSIS_RATE: u32 = 8_000;
+const DECIMATION: usize = (MIX_SAMPLE_RATE / ANALYSIS_RATE) as usize;
+/// The coarse search runs on a 1 kHz loudness envelope.
+const ENVELOPE_DECIMATION: usize = 8;
+/// Correlated from the first audible system audio on.
+const ANALYSIS_WINDOW: Duration = Duration::from_secs(120);
+const MAX_LAG: Duration = Duration::from_millis(500);
+const SILENCE_THRESHOLD: f32 = 0.01;
+/// Pearson correlation of the waveforms at the best lag.
+const MIN_CONFIDENCE: f64 = 0.4;
+/// Smaller corrections are not worth mixing again for.
+const MIN_SHIFT: Duration = Duration::from_millis(5);
+const BLOCK_FRAMES: usize = 4_096;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OffsetEstimate {
+    /// Positive when the delayed signal lags the reference.
+    pub offset_seconds: f64,
+    /// Correlation at that offset, 0 to 1.
+    pub confidence: f64,
+}
+
+/// Outcome of [`align_to_reference`] for one source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alignment {
+    pub label: &'static str,
+    pub estimate: OffsetEstimate,
+    /// Whether the mix was rebuilt with the source shifted by the estimate.
+    pub applied: bool,
+}
+
+/// Finds the lag of `delayed` behind `reference` (both mono at
+/// `sample_rate`) within `max_lag`, or `None` when either is silent.
+pub fn estimate_offset(
+    reference: &[f32],
+    delayed: &[f32],
+    sample_rate: u32,
+    max_lag: Duration,
+) -> Option<OffsetEstimate> {
+    let max_lag = (duration_to_ns(max_lag) as f64 * f64::from(sample_rate) / 1e9) as i64;
+    let reference_envelope = envelope(reference);
+    let delayed_envelope = envelope(delayed);
+    let coarse_range = max_lag / ENVELOPE_DECIMATION as i64;
+    let (coarse_lag, _) = best_lag(
+        &reference_envelope,
+        &delayed_envelope,
+        -coarse_range..=coarse_range,
+    )?;
+
+    let step = ENVELOPE_DECIMATION as i64;
+    let center = coarse_lag * step;
+    let fine_range = (center - step).max(-max_lag)..=(center + step).min(max_lag);
+    let (lag, confidence) = best_lag(reference, delayed, fine_range)?;
+    Some(OffsetEstimate {
+        offset_seconds: lag as f64 / f64::from(sample_rate),
+        confidence: confidence.max(0.0),
+    })
+}
+
+/// Estimates the offset of the `target` stem against the `reference` stem
+/// and, when it is confident and large enough, rewrites `mix_path` with
+/// `target` moved by it. `Ok(None)` when a stem is missing or silent.
+pub fn align_to_reference(
+    summary: &MixSummary,
+    mix_path: &Path,
+    reference: &str,
+    target: &str,
+) -> io::Result<Option<Alignment>> {
+    let stem = |label: &str| summary.stems.iter().find(|stem| stem.label == label);
+    let (Some(reference_stem), Some(target_stem)) = (stem(reference), stem(target)) else {
+        return Ok(None);
+    };
+
+    let max_lag_frames = ns_to_frames(duration_to_ns(MAX_LAG));
+    let Some(active) = first_active_frame(&reference_stem.path)? else {
+        return Ok(None);
+    };
+    let start = active.saturating_sub(max_lag_frames);
+    let frames = ns_to_frames(duration_to_ns(ANALYSIS_WINDOW));
+    let reference_samples = read_analysis(&reference_stem.path, start, frames)?;
+    let target_samples = read_analysis(&target_stem.path, start, frames)?;
+    let Some(estimate) =
+        estimate_offset(&reference_samples, &target_samples, ANALYSIS_RATE, MAX_LAG)
+    else {
+        return Ok(None);
+    };
+
+    let applied = estimate.confidence >= MIN_CONFIDENCE
+        && estimate.offset_seconds.abs() >= MIN_SHIFT.as_secs_f64();
+    if applied {
+        let shift = -(estimate.offset_seconds * f64::from(MIX_SAMPLE_RATE)).round() as i64;
+        let shifts: Vec<i64> = summary
+            .stems
+            .iter()
+            .map(|stem| if stem.label == target { shift } else { 0 })
+            .collect();
+        let realigned = mix_path.with_extension("realigned");
+        remix(&summary.stems, &shifts, summary.frames, &realigned)?;
+        std::fs::rename(&realigned, mix_path)?;
+    }
+    Ok(Some(Alignment {
+        label: target_stem.label,
+        estimate,
+        applied,
+    }))
+}
+
+/// Finalize step shared by the recorders: aligns the mic to the system audio
+/// in the mix at `mix_path`, logs the outcome and returns the tags to write.
+pub fn align_mic_to_system(
+    summary: &MixSummary,
+    mix_path: &Path,
+    log_tag: &str,
+) -> Vec<(String, String)> {
+    let alignment = match align_to_reference(summary, mix_path, "system", "mic") {
+        Ok(alignment) => alignment,
+        Err(err) => {
+            println!("[{}] ⚠ Mic alignment failed: {}", log_tag, err);
+            None
+        }
+    };
+    match &alignment {
+        Some(alignment) => println!(
+            "[{}] Mic offset by cross-correlation: {:.1}ms (confidence {:.2}, {})",
+            log_tag,
+            alignment.estimate.offset_seconds * 1_000.0,
+            alignment.estimate.confidence,
+            if alignment.applied {
+                "applied"
+            } else {
+                "kept timestamps"
+            }
+        ),
+        None => println!(
+            "[{}] Mic offset: no common audio with the system track, kept timestamps",
+            log_tag
+        ),
+    }
+    alignment_metadata("mic", alignment.as_ref())
+}
+
+/// Tags for the output file describing how `label` was aligned.
+pub fn alignment_metadata(label: &str, alignment: Option<&Alignment>) -> Vec<(String, String)> {
+    let key = |name: &str| format!("momentum_{}_offset_{}", label, name);
+    match alignment {
+        Some(alignment) => {
+            let applied_ms = if alignment.applied {
+                alignment.estimate.offset_seconds * 1_000.0
+            } else {
+                0.0
+            };
+            let source = if alignment.applied {
+                "cross_correlation"
+            } else {
+                "timestamps"
+            };
+            vec![
+                (key("ms"), format!("{:.1}", applied_ms)),
+                (key("source"), source.to_string()),
+                (
+                    key("estimate_ms"),
+                    format!("{:.1}", alignment.estimate.offset_seconds * 1_000.0),
+                ),
+                (
+                    key("confidence"),
+                    format!("{:.2}", alignment.estimate.confidence),
+                ),
+            ]
+        }
+        None => vec![
+            (key("ms"), "0.0".to_string()),
+            (key("source"), "timestamps".to_string()),
+        ],
+    }
+}
+
+fn ns_to_frames(ns: u64) -> u64 {
+    (u128::from(ns) * u128::from(MIX_SAMPLE_RATE) / 1_000_000_000) as u64
+}
+
+/// Mean absolute level per block, with the mean removed.
+fn envelope(samples: &[f32]) -> Vec<f32> {
+    let blocks: Vec<f32> = samples
+        .chunks(ENVELOPE_DECIMATION)
+        .map(|block| block.iter().map(|s| s.abs()).sum::<f32>() / block.len() as f32)
+        .collect();
+    let mean = blocks.iter().sum::<f32>() / blocks.len().max(1) as f32;
+    blocks.into_iter().map(|level| level - mean).collect()
+}
+
+fn best_lag(a: &[f32], b: &[f32], lags: std::ops::RangeInclusive<i64>) -> Option<(i64, f64)> {
+    lags.filter_map(|lag| correlation(a, b, lag).map(|score| (lag, score)))
+        .max_by(|x, y| x.1.total_cmp(&y.1))
+}
+
+/// Pearson correlation of `a[i]` with `b[i + lag]` over their overlap.
+fn correlation(a: &[f32], b: &[f32], lag: i64) -> Option<f64> {
+    let (a, b) = if lag >= 0 {
+        (a, b.get(lag as usize..)?)
+    } else {
+        (a.get((-lag) as usize..)?, b)
+    };
+    let n = a.len().min(b.len());
+    if n < 2 {
+        return None;
+    }
+    let (mut sa, mut sb, mut saa, mut sbb, mut sab) = (0.0f64, 0.0, 0.0, 0.0, 0.0);
+    for (&x, &y) in a[..n].iter().zip(&b[..n]) {
+        let (x, y) = (f64::from(x), f64::from(y));
+        sa += x;
+        sb += y;
+        saa += x * x;
+        sbb += y * y;
+        sab += x * y;
+    }
+    let n = n as f64;
+    let variance = (saa - sa * sa / n) * (sbb - sb * sb / n);
+    if variance <= f64::EPSILON {
+        return None;
+    }
+    Some((sab - sa * sb / n) / variance.sqrt())
+}
+
+fn read_frames(reader: &mut impl Read, frames: usize) -> io::Result<Vec<f32>> {
+    let mut bytes = vec![0u8; frames * 2 * MIX_CHANNELS as usize];
+    let mut filled = 0;
+    while filled < bytes.len() {
+        match reader.read(&mut bytes[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    bytes.truncate(filled - filled % 4);
+    Ok(bytes
+        .chunks_exact(2)
+        .map(|b| f32::from(i16::from_le_bytes([b[0], b[1]])) / 32768.0)
+        .collect())
+}
+
+fn first_active_frame(path: &Path) -> io::Result<Option<u64>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut frame = 0u64;
+    loop {
+        let samples = read_frames(&mut reader, BLOCK_FRAMES)?;
+        if samples.is_empty() {
+            return Ok(None);
+        }
+        if let Some(index) = samples.iter().position(|s| s.abs() > SILENCE_THRESHOLD) {
+            return Ok(Some(frame + (index / 2) as u64));
+        }
+        frame += (samples.len() / 2) as u64;
+    }
+}
+
+/// Mono at [`ANALYSIS_RATE`], from `start` for up to `frames` stem frames.
+fn read_analysis(path: &Path, start: u64, frames: u64) -> io::Result<Vec<f32>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    io::copy(
+        &mut reader.by_ref().take(start * 2 * u64::from(MIX_CHANNELS)),
+        &mut io::sink(),
+    )?;
+    let mut analysis = Vec::new();
+    let mut remaining = frames as usize;
+    while remaining > 0 {
+        let samples = read_frames(&mut reader, BLOCK_FRAMES.min(remaining))?;
+        if samples.is_empty() {
+            break;
+        }
+        remaining = remaining.saturating_sub(samples.len() / 2);
+        analysis.extend(
+            samples
+                .chunks(2 * DECIMATION)
+                .map(|block| block.iter().sum::<f32>() / block.len() as f32),
+        );
+    }
+    Ok(analysis)
+}
+
+/// Writes `frames` of f32le stereo mixed from the stems, each moved later by
+/// its shift in frames (earlier when negative).
+fn remix(stems: &[MixStem], shifts: &[i64], frames: u64, out_path: &Path) -> io::Result<()> {
+    let mut readers = Vec::with_capacity(stems.len());
+    for (stem, &shift) in stems.iter().zip(shifts) {
+        let mut reader = BufReader::new(File::open(&stem.path)?);
+        if shift < 0 {
+            let skip = shift.unsigned_abs() * 2 * u64::from(MIX_CHANNELS);
+            io::copy(&mut reader.by_ref().take(skip), &mut io::sink())?;
+        }
+        readers.push((reader, shift.max(0) as u64, stem.gain));
+    }
+
+    let mut out = BufWriter::new(File::create(out_path)?);
+    let mut written = 0u64;
+    while written < frames {
+        let block = BLOCK_FRAMES.min((frames - written) as usize);
+        let mut mix = vec![0.0f32; block * 2];
+        for (reader, lead, gain) in &mut readers {
+            let silent = (*lead).min(block as u64) as usize;
+            *lead -= silent as u64;
+            let samples = read_frames(reader, block - silent)?;
+            for (slot, sample) in mix[silent * 2..].iter_mut().zip(samples) {
+                *slot += sample * *gain;
+            }
+        }
+        let bytes: Vec<u8> = mix.iter().flat_map(|s| s.to_le_bytes()).collect();
+        out.write_all(&bytes)?;
+        written += block as u64;
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{align_to_reference, estimate_offset, MixStem, MixSummary};
+    use std::io::Write;
+    use std::time::Duration;
+
+    fn noise(len: usize, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_a_delayed_echo_and_rejects_unrelated_audio() {
+        // 3 s at 8 kHz; the "mic" hears the speakers 37.5 ms late, quieter,
+        // over its own noise.
+        let speakers = noise(24_000, 1);
+        let room = noise(24_300, 2);
+        let mic: Vec<f32> = room
+            .iter()
+            .enumerate()
+            .map(|(i, r)| 0.2 * r + i.checked_sub(300).map_or(0.0, |j| 0.6 * speakers[j]))
+            .collect();
+
+        let estimate =
+            estimate_offset(&speakers, &mic, 8_000, Duration::from_millis(500)).expect("estimate");
+        assert!(
+            (estimate.offset_seconds - 0.0375).abs() < 1e-9,
+            "{:?}",
+            estimate
+        );
+        assert!(estimate.confidence > 0.8, "{:?}", estimate);
+
+        let estimate =
+            estimate_offset(&speakers, &room, 8_000, Duration::from_millis(500)).expect("estimate");
+        assert!(estimate.confidence < 0.1, "{:?}", estimate);
+        assert_eq!(
+            estimate_offset(&speakers, &[0.0; 100], 8_000, Duration::from_millis(500)),
+            None
+        );
+    }
+
+    #[test]
+    fn remixes_with_the_late_source_pulled_forward() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let write_stem = |name: &str, mono: &[f32]| {
+            let path = dir.path().join(name);
+            let mut file = std::fs::File::create(&path).expect("stem");
+            for &sample in mono {
+                let value = (sample * 32767.0) as i16;
+                file.write_all(&value.to_le_bytes()).unwrap();
+                file.write_all(&value.to_le_bytes()).unwrap();
+            }
+            path
+        };
+        // 2 s at 48 kHz; the mic stem hears the system 20 ms (960 frames) late.
+        let system = noise(96_000, 3);
+        let mic: Vec<f32> = (0..96_000)
+            .map(|i: usize| i.checked_sub(960).map_or(0.0, |j| 0.5 * system[j]))
+            .collect();
+        let summary = MixSummary {
+            frames: 96_000,
+            duration: Duration::from_secs(2),
+            sources: Vec::new(),
+            stems: vec![
+                MixStem {
+                    label: "system",
+                    gain: 1.0,
+                    path: write_stem("system.stem", &system),
+                },
+                MixStem {
+                    label: "mic",
+                    gain: 2.0,
+                    path: write_stem("mic.stem", &mic),
+                },
+            ],
+        };
+        let mix_path = dir.path().join("mix.raw");
+        std::fs::write(&mix_path, b"live mix").unwrap();
+
+        let alignment = align_to_reference(&summary, &mix_path, "system", "mic")
+            .expect("align")
+            .expect("estimate");
+        assert!(alignment.applied);
+        assert!((alignment.estimate.offset_seconds - 0.020).abs() < 0.000_5);
+
+        let mix: Vec<f32> = std::fs::read(&mix_path)
+            .unwrap()
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(mix.len(), 96_000 * 2);
+        // Mic at 2x gain now lands on the system audio it echoed.
+        let frame = 10_000;
+        let expected = 2.0 * system[frame];
+        assert!(
+            (mix[frame * 2] - expected).abs() < 1e-3,
+            "{} vs {}",
+            mix[frame * 2],
+            expected
+        );
+        assert_eq!(
+            mix[(96_000 - 1) * 2],
+            (system[95_999] * 32767.0) as i16 as f32 / 32768.0
+        );
+    }
+}
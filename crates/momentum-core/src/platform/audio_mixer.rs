@@ -0,0 +1,696 @@
+//! Live mix of every audio source into one stream on the video timeline.
+//! Sources push timestamped PCM as it arrives. Each chunk is placed by its
+//! host-time timestamp (t=0 at the first video frame, paused spans cut out),
+//! scaled by the source gain and summed. A flush thread writes everything older
+//! than [`MIX_LATENCY`] to the output, so stop only has to encode it.
+//!
+//! Consecutive chunks of a source are laid end to end while their timestamps
+//! agree. A chunk stamped later than that leaves a gap of silence (a dropout);
+//! one stamped earlier overlaps audio already placed and loses its head. Either
+//! way the source keeps wall-clock length.
+//!
+//! A file-backed mixer also keeps each source's placed audio as an s16le stem
+//! next to the mix, before gain, so finalize can shift one source and mix again
+//! (see [`crate::platform::audio_alignment`]).
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::error::{CoreError, CoreResult};
+use crate::platform::video_timeline::VideoTimeline;
+use crate::time::{duration_to_ns, SharedClock};
+
+pub const MIX_SAMPLE_RATE: u32 = 48_000;
+pub const MIX_CHANNELS: u32 = 2;
+
+/// How late a chunk may arrive after its timestamp and still make the mix.
+const MIX_LATENCY: Duration = Duration::from_millis(500);
+const FLUSH_INTERVAL: Duration = Duration::from_millis(20);
+/// How far beyond [`MIX_LATENCY`] past the flushed mix a chunk may be
+/// stamped. Anything later is a bogus timestamp, not a dropout, and is
+/// dropped rather than padded up to with silence.
+const MAX_AHEAD: Duration = Duration::from_secs(2);
+/// Audio kept per source while no video frame has fixed t=0 yet.
+const MAX_WAITING: Duration = Duration::from_secs(5);
+const DEVICE_STAMP_TOLERANCE: Duration = Duration::from_millis(20);
+/// Above scheduling and pipe-buffering jitter, below an audible dropout.
+const ARRIVAL_STAMP_TOLERANCE: Duration = Duration::from_millis(150);
+
+/// Where a source's timestamps come from, which decides how far they may
+/// stray from back-to-back placement before a gap or overlap is counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timestamps {
+    /// Presentation timestamps from the capture API, sample accurate.
+    Device,
+    /// Taken when a read from a pipe completed.
+    Arrival,
+}
+
+impl Timestamps {
+    fn tolerance_frames(self) -> i64 {
+        let tolerance = match self {
+            Timestamps::Device => DEVICE_STAMP_TOLERANCE,
+            Timestamps::Arrival => ARRIVAL_STAMP_TOLERANCE,
+        };
+        ns_to_frames(duration_to_ns(tolerance) as i64)
+    }
+}
+
+/// Owns the output stream and the flush thread. Sources get a [`MixerInput`].
+pub struct AudioMixer {
+    bus: Arc<Mutex<MixBus>>,
+    stop: Arc<AtomicBool>,
+    flusher: Option<JoinHandle<()>>,
+    /// Mix file the stems are named after; `None` mixes without stems.
+    stem_base: Option<PathBuf>,
+}
+
+/// One source feeding the mix. Cheap to clone; pushes after
+/// [`AudioMixer::finish`] are ignored.
+#[derive(Clone)]
+pub struct MixerInput {
+    bus: Arc<Mutex<MixBus>>,
+    source: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixSummary {
+    /// Frames written, always matching the video duration.
+    pub frames: u64,
+    pub duration: Duration,
+    pub sources: Vec<SourceStats>,
+    pub stems: Vec<MixStem>,
+}
+
+/// One source's audio on the mix timeline: s16le stereo at
+/// [`MIX_SAMPLE_RATE`], `frames` long, not yet multiplied by `gain`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixStem {
+    pub label: &'static str,
+    pub gain: f32,
+    pub path: PathBuf,
+}
+
+/// Timing anomalies of one source over a recording.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceStats {
+    pub label: &'static str,
+    /// Dropouts, and the frames of silence that filled them.
+    pub gaps: u64,
+    pub gap_frames: u64,
+    /// Chunks that started inside audio already placed, and frames dropped.
+    pub overlaps: u64,
+    pub overlap_frames: u64,
+    /// Frames that arrived after their slot was written.
+    pub late_frames: u64,
+    /// Frames dropped because they were stamped too far ahead of the mix.
+    pub stray_frames: u64,
+}
+
+impl AudioMixer {
+    /// Mixes into `path` as raw interleaved f32le at [`MIX_SAMPLE_RATE`].
+    pub fn create(
+        path: &Path,
+        clock: SharedClock,
+        timeline: Arc<Mutex<VideoTimeline>>,
+    ) -> CoreResult<Self> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| CoreError::Recording(format!("Failed to create mix file: {}", e)))?;
+        let mut mixer = Self::start(Box::new(io::BufWriter::new(file)), clock, timeline);
+        mixer.stem_base = Some(path.to_path_buf());
+        Ok(mixer)
+    }
+
+    pub fn start(
+        out: Box<dyn Write + Send>,
+        clock: SharedClock,
+        timeline: Arc<Mutex<VideoTimeline>>,
+    ) -> Self {
+        let bus = Arc::new(Mutex::new(MixBus {
+            out,
+            timeline,
+            pending: VecDeque::new(),
+            flushed_frames: 0,
+            sources: Vec::new(),
+            closed: false,
+            write_error: None,
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let flusher = {
+            let bus = bus.clone();
+            let stop = stop.clone();
+            let latency_ns = duration_to_ns(MIX_LATENCY);
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    thread::sleep(FLUSH_INTERVAL);
+                    let watermark_ns = clock.now_ns().saturating_sub(latency_ns);
+                    bus.lock().unwrap().flush_to_instant(watermark_ns);
+                }
+            })
+        };
+        Self {
+            bus,
+            stop,
+            flusher: Some(flusher),
+            stem_base: None,
+        }
+    }
+
+    /// Registers a source; its samples are multiplied by `gain` before mixing.
+    /// `latency_ns` is the device's fixed delay: its audio is placed that much
+    /// earlier than stamped (later if negative). Compensation beyond
+    /// [`MIX_LATENCY`] lands in already-written audio and is lost.
+    pub fn add_input(
+        &self,
+        label: &'static str,
+        gain: f32,
+        timestamps: Timestamps,
+        latency_ns: i64,
+    ) -> MixerInput {
+        let stem = self.stem_base.as_ref().and_then(|base| {
+            let path = stem_path(base, label);
+            match std::fs::File::create(&path) {
+                Ok(file) => Some(Stem {
+                    path,
+                    out: io::BufWriter::new(file),
+                    pending: VecDeque::new(),
+                    failed: false,
+                }),
+                Err(err) => {
+                    eprintln!("[Mixer] Failed to create {} stem: {}", label, err);
+                    None
+                }
+            }
+        });
+        let mut bus = self.bus.lock().unwrap();
+        bus.sources.push(MixSource {
+            gain,
+            tolerance_frames: timestamps.tolerance_frames(),
+            latency_ns,
+            cursor: None,
+            waiting: VecDeque::new(),
+            stats: SourceStats {
+                label,
+                ..SourceStats::default()
+            },
+            stem,
+        });
+        MixerInput {
+            bus: self.bus.clone(),
+            source: bus.sources.len() - 1,
+        }
+    }
+
+    /// Writes the rest of the mix up to the video duration at `end_ns`,
+    /// padding with silence, and closes the output.
+    pub fn finish(&mut self, end_ns: u64) -> io::Result<MixSummary> {
+        self.stop_flusher();
+        let mut bus = self.bus.lock().unwrap();
+        if bus.closed {
+            return Err(io::Error::other("mixer already finished"));
+        }
+        let duration_ns = bus.timeline.lock().unwrap().duration_ns(end_ns);
+        let total_frames = ns_to_frames(duration_ns as i64).max(0) as u64;
+        bus.drain_waiting();
+        bus.flush_to_frame(total_frames);
+        bus.closed = true;
+        bus.pending.clear();
+        if let Some(err) = bus.write_error.take() {
+            return Err(err);
+        }
+        bus.out.flush()?;
+        let mut stems = Vec::new();
+        for source in &mut bus.sources {
+            let label = source.stats.label;
+            if let Some(stem) = source.stem.as_mut().filter(|stem| !stem.failed) {
+                match stem.out.flush() {
+                    Ok(()) => stems.push(MixStem {
+                        label,
+                        gain: source.gain,
+                        path: stem.path.clone(),
+                    }),
+                    Err(err) => eprintln!("[Mixer] Failed to write {} stem: {}", label, err),
+                }
+            }
+        }
+        Ok(MixSummary {
+            frames: bus.flushed_frames,
+            duration: Duration::from_nanos(duration_ns),
+            sources: bus
+                .sources
+                .iter()
+                .map(|source| source.stats.clone())
+                .collect(),
+            stems,
+        })
+    }
+
+    /// Stem files created so far, for cleanup once finalize is done.
+    pub fn stem_paths(&self) -> Vec<PathBuf> {
+        self.bus
+            .lock()
+            .unwrap()
+            .sources
+            .iter()
+            .filter_map(|source| source.stem.as_ref().map(|stem| stem.path.clone()))
+            .collect()
+    }
+
+    fn stop_flusher(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(flusher) = self.flusher.take() {
+            let _ = flusher.join();
+        }
+    }
+}
+
+impl Drop for AudioMixer {
+    fn drop(&mut self) {
+        self.stop_flusher();
+    }
+}
+
+impl MixerInput {
+    /// Interleaved s16le with `channels` channels at [`MIX_SAMPLE_RATE`],
+    /// whose first frame was captured at `pts_ns`.
+    pub fn push_s16le(&self, pts_ns: u64, data: &[u8], channels: u32) {
+        self.push_f32(pts_ns, &s16le_to_f32(data), channels);
+    }
+
+    /// Interleaved f32 with `channels` channels at [`MIX_SAMPLE_RATE`].
+    pub fn push_f32(&self, pts_ns: u64, samples: &[f32], channels: u32) {
+        let stereo = to_stereo(samples, channels);
+        if stereo.is_empty() {
+            return;
+        }
+        self.bus.lock().unwrap().push(self.source, pts_ns, stereo);
+    }
+}
+
+struct MixSource {
+    gain: f32,
+    tolerance_frames: i64,
+    latency_ns: i64,
+    /// Output frame the next chunk continues from. `None` until the first
+    /// chunk and again after a paused one, so the source re-anchors on its
+    /// timestamp instead of carrying sample-count error forward.
+    cursor: Option<i64>,
+    /// Chunks received before the timeline had an origin.
+    waiting: VecDeque<(u64, Vec<f32>)>,
+    stats: SourceStats,
+    stem: Option<Stem>,
+}
+
+struct Stem {
+    path: PathBuf,
+    out: io::BufWriter<std::fs::File>,
+    /// Like [`MixBus::pending`], for this source alone and before gain.
+    pending: VecDeque<f32>,
+    /// A write failed; the stem is incomplete and only kept for cleanup.
+    failed: bool,
+}
+
+struct MixBus {
+    out: Box<dyn Write + Send>,
+    timeline: Arc<Mutex<VideoTimeline>>,
+    /// Interleaved stereo sums from `flushed_frames` on.
+    pending: VecDeque<f32>,
+    flushed_frames: u64,
+    sources: Vec<MixSource>,
+    closed: bool,
+    write_error: Option<io::Error>,
+}
+
+impl MixBus {
+    fn push(&mut self, source: usize, pts_ns: u64, stereo: Vec<f32>) {
+        if self.closed {
+            return;
+        }
+        if !self.timeline.lock().unwrap().has_origin() {
+            let waiting = &mut self.sources[source].waiting;
+            waiting.push_back((pts_ns, stereo));
+            let max_waiting_ns = duration_to_ns(MAX_WAITING);
+            while waiting
+                .front()
+                .is_some_and(|(first_ns, _)| pts_ns.saturating_sub(*first_ns) > max_waiting_ns)
+            {
+                waiting.pop_front();
+            }
+            return;
+        }
+        self.drain_waiting();
+        self.place(source, pts_ns, &stereo);
+    }
+
+    fn drain_waiting(&mut self) {
+        if !self.timeline.lock().unwrap().has_origin() {
+            return;
+        }
+        for source in 0..self.sources.len() {
+            while let Some((pts_ns, stereo)) = self.sources[source].waiting.pop_front() {
+                self.place(source, pts_ns, &stereo);
+            }
+        }
+    }
+
+    fn place(&mut self, source: usize, pts_ns: u64, stereo: &[f32]) {
+        let offset_ns = self.timeline.lock().unwrap().offset_ns(pts_ns);
+        let source = &mut self.sources[source];
+        let Some(offset_ns) = offset_ns else {
+            // Captured while paused: the span is not on the timeline.
+            source.cursor = None;
+            return;
+        };
+        let stamped = ns_to_frames(offset_ns - source.latency_ns);
+        let frames = (stereo.len() / 2) as i64;
+        let max_ahead_ns = duration_to_ns(MIX_LATENCY + MAX_AHEAD) as i64;
+        if stamped > self.flushed_frames as i64 + ns_to_frames(max_ahead_ns) {
+            // Re-anchor on the next chunk that lands within reach.
+            source.stats.stray_frames += frames as u64;
+            source.cursor = None;
+            return;
+        }
+        let (start, skip) = match source.cursor {
+            None => (stamped, 0),
+            Some(cursor) if stamped - cursor > source.tolerance_frames => {
+                // Dropout: the frames in between stay silent.
+                source.stats.gaps += 1;
+                source.stats.gap_frames += (stamped - cursor) as u64;
+                (stamped, 0)
+            }
+            Some(cursor) if cursor - stamped > source.tolerance_frames => {
+                let skip = (cursor - stamped).min(frames);
+                source.stats.overlaps += 1;
+                source.stats.overlap_frames += skip as u64;
+                (cursor, skip)
+            }
+            Some(cursor) => (cursor, 0),
+        };
+        source.cursor = Some(start + frames - skip);
+
+        let flushed = self.flushed_frames as i64;
+        for (i, frame) in stereo.chunks_exact(2).skip(skip as usize).enumerate() {
+            let at = start + i as i64;
+            if at < 0 {
+                continue;
+            }
+            if at < flushed {
+                source.stats.late_frames += 1;
+                continue;
+            }
+            let index = ((at - flushed) * 2) as usize;
+            if self.pending.len() < index + 2 {
+                self.pending.resize(index + 2, 0.0);
+            }
+            self.pending[index] += frame[0] * source.gain;
+            self.pending[index + 1] += frame[1] * source.gain;
+            if let Some(stem) = source.stem.as_mut().filter(|stem| !stem.failed) {
+                if stem.pending.len() < index + 2 {
+                    stem.pending.resize(index + 2, 0.0);
+                }
+                stem.pending[index] += frame[0];
+                stem.pending[index + 1] += frame[1];
+            }
+        }
+    }
+
+    fn flush_to_instant(&mut self, capture_ns: u64) {
+        let offset_ns = self.timeline.lock().unwrap().offset_ns(capture_ns);
+        if let Some(offset_ns) = offset_ns {
+            self.flush_to_frame(ns_to_frames(offset_ns).max(0) as u64);
+        }
+    }
+
+    fn flush_to_frame(&mut self, target: u64) {
+        if self.closed || target <= self.flushed_frames {
+            return;
+        }
+        let frames = (target - self.flushed_frames) as usize;
+        let mut bytes = Vec::with_capacity(frames * 8);
+        for _ in 0..frames * 2 {
+            let sample = self.pending.pop_front().unwrap_or(0.0);
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        self.flushed_frames = target;
+        for source in &mut self.sources {
+            if let Some(stem) = source.stem.as_mut().filter(|stem| !stem.failed) {
+                let mut stem_bytes = Vec::with_capacity(frames * 4);
+                for _ in 0..frames * 2 {
+                    let sample = stem.pending.pop_front().unwrap_or(0.0);
+                    stem_bytes.extend_from_slice(&f32_to_s16(sample).to_le_bytes());
+                }
+                if let Err(err) = stem.out.write_all(&stem_bytes) {
+                    eprintln!(
+                        "[Mixer] Failed to write {} stem: {}",
+                        source.stats.label, err
+                    );
+                    stem.failed = true;
+                }
+            }
+        }
+        if let Err(err) = self.out.write_all(&bytes) {
+            eprintln!("[Mixer] Failed to write mix: {}", err);
+            self.write_error = Some(err);
+            self.closed = true;
+        }
+    }
+}
+
+/// Interleaved s16le bytes as f32 samples in [-1, 1).
+pub fn s16le_to_f32(data: &[u8]) -> Vec<f32> {
+    data.chunks_exact(2)
+        .map(|bytes| f32::from(i16::from_le_bytes([bytes[0], bytes[1]])) / 32768.0)
+        .collect()
+}
+
+pub fn f32_to_s16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * 32767.0).round() as i16
+}
+
+/// `<mix>.raw` -> `<mix>.<label>.stem`
+fn stem_path(mix_path: &Path, label: &str) -> PathBuf {
+    mix_path.with_extension(format!("{}.stem", label))
+}
+
+fn ns_to_frames(ns: i64) -> i64 {
+    let frames = i128::from(ns) * i128::from(MIX_SAMPLE_RATE) / 1_000_000_000;
+    frames as i64
+}
+
+/// Mono is duplicated; extra channels beyond the first two are dropped.
+fn to_stereo(samples: &[f32], channels: u32) -> Vec<f32> {
+    match channels {
+        0 => Vec::new(),
+        1 => samples.iter().flat_map(|&s| [s, s]).collect(),
+        2 => samples.to_vec(),
+        n => samples
+            .chunks_exact(n as usize)
+            .flat_map(|frame| [frame[0], frame[1]])
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AudioMixer, MixSummary, SourceStats, Timestamps};
+    use crate::platform::video_timeline::VideoTimeline;
+    use crate::time::MockClock;
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    const MS: u64 = 1_000_000;
+    const T0: u64 = 10_000 * MS;
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn left_channel(buf: &SharedBuf) -> Vec<f32> {
+        buf.0
+            .lock()
+            .unwrap()
+            .chunks_exact(8)
+            .map(|frame| f32::from_le_bytes([frame[0], frame[1], frame[2], frame[3]]))
+            .collect()
+    }
+
+    fn mixer_at_origin() -> (AudioMixer, Arc<Mutex<VideoTimeline>>, SharedBuf) {
+        let timeline = Arc::new(Mutex::new(VideoTimeline::new()));
+        // The first video frame fixes t=0.
+        timeline.lock().unwrap().position_ns(T0);
+        let out = SharedBuf::default();
+        let mixer = AudioMixer::start(
+            Box::new(out.clone()),
+            Arc::new(MockClock::new(T0)),
+            timeline.clone(),
+        );
+        (mixer, timeline, out)
+    }
+
+    #[test]
+    fn places_sources_by_timestamp_with_gain() {
+        let (mut mixer, _timeline, out) = mixer_at_origin();
+        let system = mixer.add_input("system", 1.0, Timestamps::Device, 0);
+        let mic = mixer.add_input("mic", 2.0, Timestamps::Arrival, 0);
+
+        // 1 ms = 48 frames. System from t=0, mic from t=1 ms, mono.
+        system.push_f32(T0, &[0.25; 96 * 2], 2);
+        mic.push_f32(T0 + MS, &[0.125; 48], 1);
+        mic.push_f32(T0 + 5 * MS, &[0.125; 48], 1);
+
+        let summary = mixer.finish(T0 + 3 * MS).expect("finish");
+        assert_eq!(summary.frames, 144);
+        assert_eq!(summary.duration, Duration::from_millis(3));
+
+        let left = left_channel(&out);
+        assert_eq!(left.len(), 144);
+        assert_eq!(left[47], 0.25);
+        // The second mic chunk continues from the first: its 5 ms stamp is
+        // within arrival jitter.
+        assert_eq!(left[48], 0.5);
+        assert_eq!(left[96], 0.25);
+        assert_eq!(left[143], 0.25);
+    }
+
+    #[test]
+    fn drops_paused_audio_and_reanchors_after_resume() {
+        let (mut mixer, timeline, out) = mixer_at_origin();
+        let mic = mixer.add_input("mic", 1.0, Timestamps::Arrival, 0);
+
+        mic.push_f32(T0, &[0.5; 48], 1);
+        timeline.lock().unwrap().set_paused(true, T0 + MS);
+        mic.push_f32(T0 + MS, &[0.9; 48], 1);
+        timeline.lock().unwrap().set_paused(false, T0 + 11 * MS);
+        mic.push_f32(T0 + 12 * MS, &[0.75; 48], 1);
+
+        let summary = mixer.finish(T0 + 13 * MS).expect("finish");
+        assert_eq!(
+            summary,
+            MixSummary {
+                frames: 144,
+                duration: Duration::from_millis(3),
+                sources: vec![SourceStats {
+                    label: "mic",
+                    ..SourceStats::default()
+                }],
+                stems: Vec::new(),
+            }
+        );
+        let left = left_channel(&out);
+        assert_eq!(left[0], 0.5);
+        assert_eq!(left[48], 0.0);
+        assert_eq!(left[96], 0.75);
+    }
+
+    #[test]
+    fn holds_audio_until_the_first_video_frame() {
+        let timeline = Arc::new(Mutex::new(VideoTimeline::new()));
+        let out = SharedBuf::default();
+        let mut mixer = AudioMixer::start(
+            Box::new(out.clone()),
+            Arc::new(MockClock::new(T0)),
+            timeline.clone(),
+        );
+        let system = mixer.add_input("system", 1.0, Timestamps::Device, 0);
+
+        // 2 ms of audio, half of it before the first frame.
+        system.push_f32(T0 - MS, &[0.5; 96], 1);
+        timeline.lock().unwrap().position_ns(T0);
+
+        mixer.finish(T0 + 2 * MS).expect("finish");
+        let left = left_channel(&out);
+        assert_eq!(left.len(), 96);
+        assert_eq!(left[47], 0.5);
+        assert_eq!(left[48], 0.0);
+    }
+
+    #[test]
+    fn fills_dropouts_with_silence_and_trims_overlaps() {
+        let (mut mixer, _timeline, out) = mixer_at_origin();
+        let system = mixer.add_input("system", 1.0, Timestamps::Device, 0);
+
+        system.push_f32(T0, &[0.25; 480], 1);
+        // 30 ms dropout after the first 10 ms.
+        system.push_f32(T0 + 40 * MS, &[0.5; 480], 1);
+        // Stamped at 25 ms: all but its last 5 ms overlaps audio already placed.
+        system.push_f32(T0 + 25 * MS, &[0.75; 1_440], 1);
+
+        let summary = mixer.finish(T0 + 60 * MS).expect("finish");
+        assert_eq!(
+            summary.sources,
+            vec![SourceStats {
+                label: "system",
+                gaps: 1,
+                gap_frames: 1_440,
+                overlaps: 1,
+                overlap_frames: 1_200,
+                late_frames: 0,
+                stray_frames: 0,
+            }]
+        );
+        let left = left_channel(&out);
+        assert_eq!(left.len(), 2_880);
+        assert_eq!(left[479], 0.25);
+        assert_eq!(left[480], 0.0);
+        assert_eq!(left[1_919], 0.0);
+        assert_eq!(left[1_920], 0.5);
+        assert_eq!(left[2_400], 0.75);
+        assert_eq!(left[2_639], 0.75);
+        assert_eq!(left[2_640], 0.0);
+    }
+
+    #[test]
+    fn drops_chunks_stamped_far_ahead_of_the_mix() {
+        let (mut mixer, _timeline, out) = mixer_at_origin();
+        let mic = mixer.add_input("mic", 1.0, Timestamps::Arrival, 0);
+
+        mic.push_f32(T0, &[0.25; 48], 1);
+        // A garbage stamp an hour out must not pad the mix up to it.
+        mic.push_f32(T0 + 3_600_000 * MS, &[0.9; 48], 1);
+        mic.push_f32(T0 + 2 * MS, &[0.5; 48], 1);
+
+        let summary = mixer.finish(T0 + 3 * MS).expect("finish");
+        assert_eq!(summary.sources[0].stray_frames, 48);
+        assert_eq!(summary.sources[0].gaps, 0);
+        let left = left_channel(&out);
+        assert_eq!(left.len(), 144);
+        assert_eq!(left[47], 0.25);
+        assert_eq!(left[48], 0.0);
+        // Re-anchored on its own stamp rather than the dropped chunk.
+        assert_eq!(left[96], 0.5);
+    }
+
+    #[test]
+    fn moves_a_late_device_earlier_by_its_latency() {
+        let (mut mixer, _timeline, out) = mixer_at_origin();
+        let system = mixer.add_input("system", 1.0, Timestamps::Device, 0);
+        // A headset that delivers 2 ms late.
+        let mic = mixer.add_input("mic", 1.0, Timestamps::Arrival, 2 * MS as i64);
+
+        system.push_f32(T0 + MS, &[0.25; 48], 1);
+        mic.push_f32(T0 + 3 * MS, &[0.5; 48], 1);
+
+        mixer.finish(T0 + 3 * MS).expect("finish");
+        let left = left_channel(&out);
+        assert_eq!(left[0], 0.0);
+        assert_eq!(left[48], 0.75);
+        assert_eq!(left[95], 0.75);
+        assert_eq!(left[96], 0.0);
+    }
+}
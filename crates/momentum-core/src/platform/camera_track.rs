@@ -0,0 +1,239 @@
+//! Camera frames recorded next to the screen video. Each frame the camera
+//! sync pairs with a screen tick is stored as a JPEG at that tick's position
+//! on the video timeline, together with the spans during which the
+//! `camera-overlay` window was showing. The mux composites the camera from
+//! this file, or writes it out as a track or sidecar of its own.
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use base64::{engine::general_purpose, Engine as _};
+
+use crate::camera::{CameraFramePayload, CameraSyncHandle};
+use crate::models::CameraOverlay;
+use crate::platform::matroska::{RawVideoMkvWriter, TIMESTAMP_SCALE_NS};
+use crate::platform::video_timeline::VideoTimeline;
+
+pub struct CameraTrackWriter {
+    path: PathBuf,
+    overlay: CameraOverlay,
+    screen_width: u32,
+    screen_height: u32,
+    timeline: Arc<Mutex<VideoTimeline>>,
+    /// Created with the first frame, which gives the track its size.
+    mkv: Option<RawVideoMkvWriter<BufWriter<File>>>,
+    first_ms: Option<u64>,
+    last_ms: Option<u64>,
+    frames: u64,
+    /// `(start, end)` timeline positions; the last span is open while visible.
+    visible_spans: Vec<(u64, Option<u64>)>,
+}
+
+/// A finished camera recording, ready for the mux.
+#[derive(Debug, Clone)]
+pub struct CameraTrack {
+    pub path: PathBuf,
+    pub overlay: CameraOverlay,
+    pub screen_width: u32,
+    pub screen_height: u32,
+    /// Timeline position of the first frame.
+    pub start_ns: u64,
+    pub frames: u64,
+    /// `(start, end)` timeline positions during which the camera is shown.
+    pub visible_spans: Vec<(u64, u64)>,
+}
+
+impl CameraTrackWriter {
+    pub fn new(
+        path: PathBuf,
+        overlay: CameraOverlay,
+        screen_width: u32,
+        screen_height: u32,
+        timeline: Arc<Mutex<VideoTimeline>>,
+    ) -> Self {
+        Self {
+            path,
+            overlay,
+            screen_width,
+            screen_height,
+            timeline,
+            mkv: None,
+            first_ms: None,
+            last_ms: None,
+            frames: 0,
+            visible_spans: Vec::new(),
+        }
+    }
+
+    /// Called after the screen frame captured at `capture_ns` was written,
+    /// with the overlay visibility at that moment and the camera frame paired
+    /// with it, if there is a new one.
+    pub fn write_tick(
+        &mut self,
+        capture_ns: u64,
+        visible: bool,
+        frame: Option<&CameraFramePayload>,
+    ) -> io::Result<()> {
+        let Some(position_ns) = self.timeline.lock().unwrap().offset_ns(capture_ns) else {
+            return Ok(());
+        };
+        let position_ns = position_ns.max(0) as u64;
+        self.set_visible(visible, position_ns);
+
+        let Some(frame) = frame else {
+            return Ok(());
+        };
+        let timestamp_ms = position_ns / TIMESTAMP_SCALE_NS;
+        if self.last_ms.is_some_and(|last| timestamp_ms <= last) {
+            return Ok(());
+        }
+        let jpeg = general_purpose::STANDARD
+            .decode(&frame.data_base64)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mkv = match &mut self.mkv {
+            Some(mkv) => mkv,
+            slot => {
+                let file = BufWriter::new(File::create(&self.path)?);
+                slot.insert(RawVideoMkvWriter::mjpeg(file, frame.width, frame.height)?)
+            }
+        };
+        mkv.write_frame(timestamp_ms, &jpeg)?;
+        self.first_ms.get_or_insert(timestamp_ms);
+        self.last_ms = Some(timestamp_ms);
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Flushes the file and closes the visible span still open at `end_ns`.
+    /// `None` when no camera frame was written.
+    pub fn finish(mut self, end_ns: u64) -> io::Result<Option<CameraTrack>> {
+        let Some(mut mkv) = self.mkv.take() else {
+            return Ok(None);
+        };
+        mkv.flush()?;
+
+        let end_position_ns = self.timeline.lock().unwrap().duration_ns(end_ns);
+        let visible_spans = self
+            .visible_spans
+            .iter()
+            .map(|&(start, end)| (start, end.unwrap_or(end_position_ns)))
+            .filter(|(start, end)| end > start)
+            .collect();
+        Ok(Some(CameraTrack {
+            path: self.path,
+            overlay: self.overlay,
+            screen_width: self.screen_width,
+            screen_height: self.screen_height,
+            start_ns: self.first_ms.unwrap_or(0) * TIMESTAMP_SCALE_NS,
+            frames: self.frames,
+            visible_spans,
+        }))
+    }
+
+    fn set_visible(&mut self, visible: bool, position_ns: u64) {
+        match self.visible_spans.last_mut() {
+            Some((_, None)) if visible => {}
+            Some((_, end @ None)) => *end = Some(position_ns),
+            _ if visible => self.visible_spans.push((position_ns, None)),
+            _ => {}
+        }
+    }
+}
+
+/// Records the screen tick at `capture_ns` in `track`, with the camera frame
+/// the sync paired with it. A track that fails to write is deleted and the
+/// recording continues without it.
+pub fn record_tick(
+    track: &Mutex<Option<CameraTrackWriter>>,
+    log_tag: &str,
+    capture_ns: u64,
+    sync: &CameraSyncHandle,
+    frame: Option<&CameraFramePayload>,
+) {
+    let mut track = track.lock().unwrap();
+    let Some(writer) = track.as_mut() else {
+        return;
+    };
+    if let Err(err) = writer.write_tick(capture_ns, sync.overlay_visible(), frame) {
+        eprintln!(
+            "[{}] ⚠ Failed to record camera frame, continuing without camera: {}",
+            log_tag, err
+        );
+        if let Some(writer) = track.take() {
+            let _ = std::fs::remove_file(&writer.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CameraTrackWriter;
+    use crate::camera::CameraFramePayload;
+    use crate::models::CameraOverlay;
+    use crate::platform::video_timeline::VideoTimeline;
+    use std::sync::{Arc, Mutex};
+
+    const MS: u64 = 1_000_000;
+
+    fn frame(id: u64) -> CameraFramePayload {
+        CameraFramePayload {
+            id,
+            width: 2,
+            height: 2,
+            format: "jpeg",
+            // Not a real JPEG; the track stores the bytes as they are.
+            data_base64: "/9j/2Q==".to_string(),
+            pts_ns: 0,
+        }
+    }
+
+    #[test]
+    fn records_frames_and_visible_spans_on_the_timeline() {
+        let path = std::env::temp_dir().join(format!(
+            "momentum_camera_track_test_{}.mkv",
+            uuid::Uuid::new_v4()
+        ));
+        let timeline = Arc::new(Mutex::new(VideoTimeline::new()));
+        let mut writer = CameraTrackWriter::new(
+            path.clone(),
+            CameraOverlay::default(),
+            1280,
+            720,
+            timeline.clone(),
+        );
+
+        // Screen frames every 100 ms from 1 s; the camera shows up on the
+        // second tick, is hidden at 1.3 s and shown again during a pause
+        // from 1.4 s to 1.6 s.
+        for tick in 0..8u64 {
+            let capture_ns = 1_000 * MS + tick * 100 * MS;
+            if tick == 4 {
+                timeline.lock().unwrap().set_paused(true, capture_ns);
+                continue;
+            }
+            if tick == 6 {
+                timeline.lock().unwrap().set_paused(false, capture_ns);
+            }
+            timeline.lock().unwrap().position_ns(capture_ns);
+            let visible = !(3..5).contains(&tick);
+            let camera = (tick >= 1).then(|| frame(tick));
+            writer
+                .write_tick(capture_ns, visible, camera.as_ref())
+                .expect("tick");
+        }
+
+        let track = writer.finish(1_800 * MS).expect("finish").expect("track");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(track.frames, 5);
+        assert_eq!(track.start_ns, 100 * MS);
+        // The pause is cut out: hidden at 0.3 s, shown again from the resume
+        // at 0.4 s until the end at 0.6 s.
+        assert_eq!(
+            track.visible_spans,
+            vec![(0, 300 * MS), (400 * MS, 600 * MS)]
+        );
+    }
+}
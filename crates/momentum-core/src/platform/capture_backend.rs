@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::error::CoreResult;
+use crate::models::RecordingOptions;
+use crate::camera::CameraSyncHandle;
+
+/// Everything a backend needs to begin a capture session.
+pub struct CaptureRequest<'a> {
+    pub output_path: &'a Path,
+    pub options: &'a RecordingOptions,
+    pub camera_sync: Option<Arc<CameraSyncHandle>>,
+}
+
+/// [`DeviceOffsets`](crate::models::DeviceOffsets) keys of the audio sources
+/// a session records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioDeviceIds {
+    pub system_audio: String,
+    /// `None` when the session records no microphone.
+    pub mic: Option<String>,
+}
+
+/// A platform capture pipeline (screen, system audio and microphone) driven by
+/// [`Recorder`](crate::Recorder). Implementations own their devices
+/// and temp files; the recorder only tracks session state and timing.
+pub trait CaptureBackend: Send + Sync + 'static {
+    fn start(&self, request: CaptureRequest<'_>) -> CoreResult<()>;
+
+    /// Stops capture, finalizes the output and returns the path of the muxed file.
+    fn stop(&self) -> CoreResult<PathBuf>;
+
+    fn set_recording_paused(&self, paused: bool);
+
+    fn set_mic_muted(&self, muted: bool);
+
+    fn set_system_audio_muted(&self, muted: bool);
+
+    fn is_active(&self) -> bool;
+
+    /// The audio devices a session started with `options` would record.
+    fn audio_device_ids(&self, options: &RecordingOptions) -> CoreResult<AudioDeviceIds>;
+
+    /// Fails when the FFmpeg this backend runs lacks something a session
+    /// started with `options` would need.
+    fn check_ffmpeg(&self, options: &RecordingOptions) -> CoreResult<()>;
+}
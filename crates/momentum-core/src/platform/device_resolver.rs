@@ -1,4 +1,4 @@
-use crate::error::{AppError, AppResult};
+use crate::error::{CoreError, CoreResult};
 use serde::Deserialize;
 use std::path::PathBuf;
 use std::process::Command;
@@ -14,19 +14,19 @@ pub struct AvfResolved {
 }
 
 impl AvfResolved {
-    pub fn get_mic_index(&self) -> AppResult<i32> {
+    pub fn get_mic_index(&self) -> CoreResult<i32> {
         self.audio_index_builtin_mic
-            .ok_or_else(|| AppError::Recording("Built-in microphone not found".to_string()))
+            .ok_or_else(|| CoreError::Recording("Built-in microphone not found".to_string()))
     }
 
-    pub fn get_camera_index(&self) -> AppResult<i32> {
+    pub fn get_camera_index(&self) -> CoreResult<i32> {
         self.video_index_builtin_cam
-            .ok_or_else(|| AppError::Recording("Built-in camera not found".to_string()))
+            .ok_or_else(|| CoreError::Recording("Built-in camera not found".to_string()))
     }
 
-    pub fn get_screen_index(&self) -> AppResult<i32> {
+    pub fn get_screen_index(&self) -> CoreResult<i32> {
         self.video_index_main_screen
-            .ok_or_else(|| AppError::Recording("Main screen not found".to_string()))
+            .ok_or_else(|| CoreError::Recording("Main screen not found".to_string()))
     }
 
     pub fn get_system_audio_index(&self) -> Option<i32> {
@@ -34,7 +34,7 @@ impl AvfResolved {
     }
 }
 
-pub fn resolve_avf_indices() -> AppResult<AvfResolved> {
+pub fn resolve_avf_indices() -> CoreResult<AvfResolved> {
     // Get the path to the Swift resolver script
     // In Tauri, resources are bundled, but during development we need to find it
     let resolver_path = get_resolver_path()?;
@@ -48,7 +48,7 @@ pub fn resolve_avf_indices() -> AppResult<AvfResolved> {
         .arg(&resolver_path)
         .output()
         .map_err(|e| {
-            AppError::Recording(format!(
+            CoreError::Recording(format!(
                 "Failed to run Swift resolver: {}. Make sure Xcode Command Line Tools are installed (xcode-select --install)",
                 e
             ))
@@ -56,7 +56,7 @@ pub fn resolve_avf_indices() -> AppResult<AvfResolved> {
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::Recording(format!(
+        return Err(CoreError::Recording(format!(
             "Swift resolver failed: {}",
             stderr
         )));
@@ -66,7 +66,7 @@ pub fn resolve_avf_indices() -> AppResult<AvfResolved> {
     println!("[DeviceResolver] Resolver output: {}", stdout.trim());
 
     let parsed: AvfResolved = serde_json::from_str(stdout.trim()).map_err(|e| {
-        AppError::Recording(format!(
+        CoreError::Recording(format!(
             "Failed to parse resolver output: {}. Output was: {}",
             e, stdout
         ))
@@ -93,7 +93,7 @@ pub fn resolve_avf_indices() -> AppResult<AvfResolved> {
     Ok(parsed)
 }
 
-fn get_resolver_path() -> AppResult<PathBuf> {
+fn get_resolver_path() -> CoreResult<PathBuf> {
     // Try multiple locations in order of preference
 
     // 1. Try relative to the executable (for bundled app)
@@ -171,8 +171,10 @@ fn get_resolver_path() -> AppResult<PathBuf> {
         }
     }
 
-    // 4. Try absolute path from project root
+    // 4. Try absolute path from project root (the script ships with the app
+    // bundle, so it lives in src-tauri rather than next to this crate)
     let absolute_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../src-tauri")
         .join("resources")
         .join("resolve_avf.swift");
     if absolute_path.exists() {
@@ -183,7 +185,7 @@ fn get_resolver_path() -> AppResult<PathBuf> {
         return Ok(absolute_path);
     }
 
-    Err(AppError::Recording(format!(
+    Err(CoreError::Recording(format!(
         "Could not find resolve_avf.swift script. Searched in:\n\
             - App bundle Resources directory\n\
             - CARGO_MANIFEST_DIR/resources/\n\
@@ -0,0 +1,213 @@
+//! Keeps a device with its own sample clock (the mic) locked to the host
+//! clock. Every chunk's timestamp says how many frames the device should have
+//! delivered by then; the difference from what was actually produced drives a
+//! PI loop that nudges the resampling ratio. Corrections stay within
+//! [`MAX_CORRECTION`] and follow drift that changes over a session, instead of
+//! one tempo change applied to the whole file afterwards.
+
+use crate::time::NANOS_PER_SECOND;
+
+/// Largest deviation from 1:1, far above real device drift (≈0.01–0.1%).
+const MAX_CORRECTION: f64 = 0.01;
+/// Arrival jitter is averaged over about this long before it moves the ratio.
+const ERROR_SMOOTHING_SECONDS: f64 = 1.0;
+/// Proportional (1/s) and integral (1/s²) gains; the loop settles in ~20 s.
+const KP: f64 = 0.5;
+const KI: f64 = 0.05;
+
+/// Resamples interleaved f32 chunks so the output runs at `sample_rate` on the
+/// host clock.
+pub struct DriftResampler {
+    channels: usize,
+    sample_rate: f64,
+    anchor_ns: Option<u64>,
+    frames_out: u64,
+    ratio: f64,
+    smoothed_error: f64,
+    integral: f64,
+    /// Input frames not fully consumed yet, one frame of history first.
+    pending: Vec<f32>,
+    /// Read position in `pending`, in frames.
+    position: f64,
+}
+
+impl DriftResampler {
+    pub fn new(sample_rate: u32, channels: u32) -> Self {
+        Self {
+            channels: channels.max(1) as usize,
+            sample_rate: f64::from(sample_rate.max(1)),
+            anchor_ns: None,
+            frames_out: 0,
+            ratio: 1.0,
+            smoothed_error: 0.0,
+            integral: 0.0,
+            pending: Vec::new(),
+            position: 1.0,
+        }
+    }
+
+    /// Resamples a chunk whose first frame was captured at `pts_ns` and
+    /// returns the corrected frames. The ratio used is the one learned from
+    /// earlier chunks; this chunk's timing only affects the next one.
+    pub fn process(&mut self, pts_ns: u64, input: &[f32]) -> Vec<f32> {
+        let channels = self.channels;
+        let input_frames = input.len() / channels;
+        if input_frames == 0 {
+            return Vec::new();
+        }
+        let anchor_ns = *self.anchor_ns.get_or_insert(pts_ns);
+        if self.pending.is_empty() {
+            // Stand-in for the frame before the first one.
+            self.pending.extend_from_slice(&input[..channels]);
+        }
+        self.pending
+            .extend_from_slice(&input[..input_frames * channels]);
+
+        let output = self.resample();
+        self.frames_out += (output.len() / channels) as u64;
+
+        // Frames still queued count as produced: they come out next chunk.
+        let queued = (self.pending.len() / channels) as f64 - self.position;
+        let expected = pts_ns.saturating_sub(anchor_ns) as f64 * self.sample_rate
+            / NANOS_PER_SECOND as f64
+            + input_frames as f64;
+        let error_seconds = (self.frames_out as f64 + queued - expected) / self.sample_rate;
+        self.adjust(error_seconds, input_frames as f64 / self.sample_rate);
+        output
+    }
+
+    /// Forgets the timing anchor, e.g. for chunks dropped while paused. The
+    /// learned drift is kept, so the ratio carries over to the resumed span.
+    pub fn reset(&mut self) {
+        self.anchor_ns = None;
+        self.frames_out = 0;
+        self.smoothed_error = 0.0;
+        self.pending.clear();
+        self.position = 1.0;
+    }
+
+    /// Output frames per input frame currently applied.
+    pub fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    /// Correction in parts per million, for logs. Negative means the device
+    /// runs fast and frames are being dropped.
+    pub fn correction_ppm(&self) -> f64 {
+        (self.ratio - 1.0) * 1_000_000.0
+    }
+
+    fn adjust(&mut self, error_seconds: f64, chunk_seconds: f64) {
+        let alpha = (chunk_seconds / ERROR_SMOOTHING_SECONDS).min(1.0);
+        self.smoothed_error += (error_seconds - self.smoothed_error) * alpha;
+        // The integral term alone may never exceed the allowed correction.
+        let integral_limit = MAX_CORRECTION / KI;
+        self.integral = (self.integral + self.smoothed_error * chunk_seconds)
+            .clamp(-integral_limit, integral_limit);
+        let correction = KP * self.smoothed_error + KI * self.integral;
+        self.ratio = (1.0 - correction).clamp(1.0 - MAX_CORRECTION, 1.0 + MAX_CORRECTION);
+    }
+
+    /// Catmull-Rom interpolation over `pending`, advancing `1 / ratio` input
+    /// frames per output frame.
+    fn resample(&mut self) -> Vec<f32> {
+        let channels = self.channels;
+        let frames = self.pending.len() / channels;
+        let step = 1.0 / self.ratio;
+        let mut output = Vec::with_capacity(((frames as f64 * self.ratio) as usize + 1) * channels);
+        while (self.position as usize) + 2 < frames {
+            let index = self.position as usize;
+            let t = (self.position - index as f64) as f32;
+            for channel in 0..channels {
+                let at = |frame: usize| self.pending[frame * channels + channel];
+                output.push(catmull_rom(
+                    at(index - 1),
+                    at(index),
+                    at(index + 1),
+                    at(index + 2),
+                    t,
+                ));
+            }
+            self.position += step;
+        }
+        let consumed = (self.position as usize).saturating_sub(1).min(frames);
+        self.pending.drain(..consumed * channels);
+        self.position -= consumed as f64;
+        output
+    }
+}
+
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DriftResampler;
+    use crate::time::samples_to_ns;
+
+    const RATE: u32 = 48_000;
+    const CHUNK: usize = 480;
+
+    #[test]
+    fn passes_a_clock_accurate_device_through_untouched() {
+        let mut resampler = DriftResampler::new(RATE, 2);
+        let mut output = Vec::new();
+        for chunk in 0..100u64 {
+            let input: Vec<f32> = (0..CHUNK * 2)
+                .map(|i| ((chunk as usize * CHUNK * 2 + i) % 1000) as f32 / 1000.0)
+                .collect();
+            output.extend(resampler.process(samples_to_ns(chunk * CHUNK as u64, RATE), &input));
+        }
+        assert_eq!(resampler.ratio(), 1.0);
+        // Two frames stay queued for interpolation.
+        assert_eq!(output.len(), (100 * CHUNK - 2) * 2);
+        assert_eq!(output[0], 0.0);
+        assert_eq!(output[999], 0.999);
+        assert_eq!(output[1000], 0.0);
+    }
+
+    #[test]
+    fn locks_a_fast_device_to_the_clock_despite_jitter() {
+        // 0.5% fast: 480 frames every 9.95 ms, read up to 8 ms late.
+        let device_rate = 48_240;
+        let mut resampler = DriftResampler::new(RATE, 1);
+        let input = vec![0.25f32; CHUNK];
+        let mut produced = 0u64;
+        let mut worst_late_error_ms = 0.0f64;
+        for chunk in 0..12_000u64 {
+            let captured_ns = samples_to_ns(chunk * CHUNK as u64, device_rate);
+            let jitter_ns = (chunk * 7_919 % 9) * 1_000_000;
+            produced += resampler.process(captured_ns + jitter_ns, &input).len() as u64;
+            if chunk >= 9_000 {
+                let end_ns = samples_to_ns((chunk + 1) * CHUNK as u64, device_rate);
+                let expected = end_ns as f64 * f64::from(RATE) / 1e9;
+                let error_ms = (produced as f64 - expected) * 1_000.0 / f64::from(RATE);
+                worst_late_error_ms = worst_late_error_ms.max(error_ms.abs());
+            }
+        }
+        let expected_ppm = (48_000.0 / 48_240.0 - 1.0) * 1_000_000.0;
+        assert!(
+            (resampler.correction_ppm() - expected_ppm).abs() < 300.0,
+            "correction {} ppm, expected {} ppm",
+            resampler.correction_ppm(),
+            expected_ppm
+        );
+        // Within the average read delay plus a little residual.
+        assert!(
+            worst_late_error_ms < 10.0,
+            "error {} ms",
+            worst_late_error_ms
+        );
+
+        // A pause re-anchors but keeps the learned ratio.
+        let ratio = resampler.ratio();
+        resampler.reset();
+        assert_eq!(resampler.ratio(), ratio);
+    }
+}
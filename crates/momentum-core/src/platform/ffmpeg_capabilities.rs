@@ -0,0 +1,427 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use crate::encoder::VideoCodec;
+use crate::error::{CoreError, CoreResult};
+use crate::models::RecordingOptions;
+use crate::platform::{ffmpeg_process, mux};
+
+/// What the resolved FFmpeg build can do. Users bring their own FFmpeg, so
+/// components the pipeline relies on may be missing; probed once per resolved
+/// path by [`FfmpegLocator`](super::macos::ffmpeg::FfmpegLocator).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegCapabilities {
+    pub ffmpeg_path: PathBuf,
+    /// First line of `ffmpeg -version`.
+    pub version: Option<String>,
+    pub encoders: BTreeSet<String>,
+    pub decoders: BTreeSet<String>,
+    /// Input formats, devices included.
+    pub demuxers: BTreeSet<String>,
+    pub filters: BTreeSet<String>,
+    pub hwaccels: BTreeSet<String>,
+}
+
+impl FfmpegCapabilities {
+    /// Runs `ffmpeg -encoders`, `-decoders`, `-demuxers`, `-filters` and
+    /// `-hwaccels`.
+    pub fn probe(ffmpeg_path: &Path) -> CoreResult<Self> {
+        let version = run_listing(ffmpeg_path, "-version")?
+            .lines()
+            .next()
+            .map(|line| line.trim().to_string());
+        let capabilities = Self {
+            ffmpeg_path: ffmpeg_path.to_path_buf(),
+            version,
+            encoders: parse_codecs(&run_listing(ffmpeg_path, "-encoders")?),
+            decoders: parse_codecs(&run_listing(ffmpeg_path, "-decoders")?),
+            demuxers: parse_formats(&run_listing(ffmpeg_path, "-demuxers")?),
+            filters: parse_filters(&run_listing(ffmpeg_path, "-filters")?),
+            hwaccels: parse_hwaccels(&run_listing(ffmpeg_path, "-hwaccels")?),
+        };
+        println!(
+            "[FFmpeg] Probed {}: {} encoders, {} decoders, {} demuxers, {} filters, hwaccels: {}",
+            ffmpeg_path.display(),
+            capabilities.encoders.len(),
+            capabilities.decoders.len(),
+            capabilities.demuxers.len(),
+            capabilities.filters.len(),
+            capabilities
+                .hwaccels
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        Ok(capabilities)
+    }
+
+    pub fn has_encoder(&self, name: &str) -> bool {
+        self.encoders.contains(name)
+    }
+
+    pub fn has_filter(&self, name: &str) -> bool {
+        self.filters.contains(name)
+    }
+
+    /// Fails when this build lacks anything in `requirements`, naming the
+    /// first component missing.
+    pub fn check(&self, requirements: &FfmpegRequirements) -> CoreResult<()> {
+        let codec = requirements.video_codec;
+        if !self.has_encoder(codec.encoder_name()) {
+            return Err(CoreError::Recording(format!(
+                "FFmpeg at {} has no {} encoder, which the {:?} encoder profile needs. \
+                 Pick another codec or use an FFmpeg build with {}.",
+                self.ffmpeg_path.display(),
+                codec.encoder_name(),
+                codec,
+                codec.encoder_name()
+            )));
+        }
+        for (kind, required, available) in [
+            ("encoder", &requirements.encoders, &self.encoders),
+            ("decoder", &requirements.decoders, &self.decoders),
+            ("demuxer", &requirements.demuxers, &self.demuxers),
+            ("filter", &requirements.filters, &self.filters),
+        ] {
+            if let Some(name) = required.difference(available).next() {
+                return Err(self.missing(kind, name));
+            }
+        }
+        Ok(())
+    }
+
+    fn missing(&self, kind: &str, name: &str) -> CoreError {
+        CoreError::Recording(format!(
+            "FFmpeg at {} has no {} {}, which recordings need",
+            self.ffmpeg_path.display(),
+            name,
+            kind
+        ))
+    }
+}
+
+/// FFmpeg components a recording runs. Collected from the same input
+/// arguments and filtergraphs the passes are started with, so the check
+/// before a recording cannot drift from what it runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FfmpegRequirements {
+    /// Codec of the encoder profile, reported with a hint when missing.
+    pub video_codec: VideoCodec,
+    pub encoders: BTreeSet<String>,
+    pub decoders: BTreeSet<String>,
+    pub demuxers: BTreeSet<String>,
+    pub filters: BTreeSet<String>,
+}
+
+impl FfmpegRequirements {
+    /// The encoder pass and the mux every backend finishes with; backends
+    /// add their own sources.
+    pub fn for_options(options: &RecordingOptions) -> Self {
+        let mut requirements = Self {
+            video_codec: options.encoder.codec,
+            ..Self::default()
+        };
+        ffmpeg_process::add_encoder_requirements(&mut requirements, &options.encoder);
+        mux::add_mux_requirements(&mut requirements, options);
+        requirements
+    }
+
+    pub fn add_encoder(&mut self, name: &str) {
+        self.encoders.insert(name.to_string());
+    }
+
+    pub fn add_decoder(&mut self, name: &str) {
+        self.decoders.insert(name.to_string());
+    }
+
+    pub fn add_filter(&mut self, name: &str) {
+        self.filters.insert(name.to_string());
+    }
+
+    /// Every filter of `graph`, as passed to `-vf`, `-af`,
+    /// `-filter_complex` or a lavfi input.
+    pub fn add_graph(&mut self, graph: &str) {
+        self.filters.extend(graph_filters(graph));
+    }
+
+    /// The format of every `-f` in input options `args`, plus the filters of
+    /// lavfi graphs.
+    pub fn add_input<S: AsRef<str>>(&mut self, args: &[S]) {
+        let mut format = None;
+        for pair in args.windows(2) {
+            match pair[0].as_ref() {
+                "-f" => {
+                    format = Some(pair[1].as_ref());
+                    self.demuxers.insert(pair[1].as_ref().to_string());
+                }
+                "-i" => {
+                    if format == Some("lavfi") {
+                        self.add_graph(pair[1].as_ref());
+                    }
+                    format = None;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Filter names in a filtergraph: every `,`/`;`-separated segment, without
+/// its link labels and arguments. Separators inside quotes or escaped with a
+/// backslash belong to the arguments.
+fn graph_filters(graph: &str) -> Vec<String> {
+    let mut segments = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = graph.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '\'' => quoted = !quoted,
+            ',' | ';' if !quoted => segments.push(String::new()),
+            _ => segments.last_mut().unwrap().push(c),
+        }
+    }
+    segments
+        .iter()
+        .filter_map(|segment| {
+            let mut rest = segment.trim();
+            while let Some(label_end) = rest.strip_prefix('[').and_then(|r| r.find(']')) {
+                rest = rest[label_end + 2..].trim_start();
+            }
+            let name = rest.split(['=', '@', '[']).next().unwrap_or("").trim();
+            (!name.is_empty()).then(|| name.to_string())
+        })
+        .collect()
+}
+
+fn run_listing(ffmpeg_path: &Path, flag: &str) -> CoreResult<String> {
+    let output = Command::new(ffmpeg_path)
+        .args(["-hide_banner", flag])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| CoreError::Recording(format!("Failed to run ffmpeg {}: {}", flag, e)))?;
+    if !output.status.success() {
+        return Err(CoreError::Recording(format!(
+            "ffmpeg {} failed: {}",
+            flag,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Names from `ffmpeg -encoders` or `-decoders`: the second column of every
+/// line after the `------` separator.
+fn parse_codecs(listing: &str) -> BTreeSet<String> {
+    listing
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("---"))
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Names from `ffmpeg -filters`, whose rows read `TSC name  A->A  description`.
+/// The legend above them has no `->` column.
+fn parse_filters(listing: &str) -> BTreeSet<String> {
+    listing
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let _flags = columns.next()?;
+            let name = columns.next()?;
+            columns
+                .next()
+                .filter(|io| io.contains("->"))
+                .map(|_| name.to_string())
+        })
+        .collect()
+}
+
+/// Names from `ffmpeg -demuxers`, whose rows after the `--` separator read
+/// ` D  matroska,webm  description`. The flag column is two or three
+/// characters wide depending on the version (a `d` marks devices), and one
+/// row can list several comma-separated names.
+fn parse_formats(listing: &str) -> BTreeSet<String> {
+    listing
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("--"))
+        .skip(1)
+        .filter_map(|line| {
+            line.split_whitespace()
+                .find(|column| !column.chars().all(|c| matches!(c, 'D' | 'E' | 'd' | '.')))
+        })
+        .flat_map(|names| names.split(','))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Method names from `ffmpeg -hwaccels`, one per line after the header.
+fn parse_hwaccels(listing: &str) -> BTreeSet<String> {
+    listing
+        .lines()
+        .skip_while(|line| !line.ends_with(':'))
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        graph_filters, parse_codecs, parse_filters, parse_formats, parse_hwaccels,
+        FfmpegCapabilities, FfmpegRequirements,
+    };
+    use crate::encoder::{EncoderProfile, VideoCodec};
+    use crate::models::{CameraOverlay, CameraShape, RecordingOptions};
+    use crate::platform::synthetic_recorder::{SyntheticPattern, SyntheticTiming};
+
+    #[test]
+    fn parses_ffmpeg_listings() {
+        let encoders = "Encoders:
+ V..... = Video
+ A..... = Audio
+ ------
+ V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC (codec h264)
+ V....D h264_videotoolbox    VideoToolbox H.264 Encoder (codec h264)
+ A....D aac                  AAC (Advanced Audio Coding)
+";
+        assert_eq!(
+            parse_codecs(encoders).into_iter().collect::<Vec<_>>(),
+            ["aac", "h264_videotoolbox", "libx264"]
+        );
+
+        let demuxers = "File formats:
+ D.. = Demuxing supported
+ .E. = Muxing supported
+ ..d = Is a device
+ ---
+ D   f32le           PCM 32-bit floating-point little-endian
+ D d lavfi           Libavfilter virtual input device
+ D   matroska,webm   Matroska / WebM
+";
+        assert_eq!(
+            parse_formats(demuxers).into_iter().collect::<Vec<_>>(),
+            ["f32le", "lavfi", "matroska", "webm"]
+        );
+
+        let filters = "Filters:
+  T.. = Timeline support
+  .S. = Slice threading
+  A = Audio input/output
+  | = Source or sink filter
+ TSC alimiter          A->A       Audio lookahead limiter.
+ ... atempo            A->A       Adjust audio tempo.
+ ..C overlay           VV->V      Overlay a video source on top of the input.
+ ... testsrc2          |->V       Generate another test pattern.
+";
+        assert_eq!(
+            parse_filters(filters).into_iter().collect::<Vec<_>>(),
+            ["alimiter", "atempo", "overlay", "testsrc2"]
+        );
+
+        let hwaccels = "Hardware acceleration methods:\nvideotoolbox\ncuda\n\n";
+        assert_eq!(
+            parse_hwaccels(hwaccels).into_iter().collect::<Vec<_>>(),
+            ["cuda", "videotoolbox"]
+        );
+    }
+
+    #[test]
+    fn collects_requirements_from_the_graphs_a_session_runs() {
+        assert_eq!(
+            graph_filters("[1:v]crop=1:1,geq=a='if(lt(X,2),255,0)'[cam];[0:v][cam]overlay[v]"),
+            ["crop", "geq", "overlay"]
+        );
+
+        let options = RecordingOptions {
+            include_microphone: true,
+            include_camera: true,
+            camera_overlay: CameraOverlay {
+                shape: CameraShape::Circle,
+                ..CameraOverlay::default()
+            },
+            ..RecordingOptions::default()
+        };
+        let requirements = SyntheticTiming::default().requirements(&options);
+        assert_eq!(
+            requirements.filters.iter().collect::<Vec<_>>(),
+            [
+                "alimiter",
+                "anoisesrc",
+                "asetrate",
+                "crop",
+                "format",
+                "geq",
+                "overlay",
+                "scale",
+                "sine",
+                "testsrc2"
+            ]
+        );
+        assert_eq!(
+            requirements.demuxers.iter().collect::<Vec<_>>(),
+            ["f32le", "lavfi", "matroska"]
+        );
+        assert_eq!(
+            requirements.decoders.iter().collect::<Vec<_>>(),
+            ["mjpeg", "pcm_f32le", "rawvideo"]
+        );
+        assert_eq!(
+            requirements.encoders.iter().collect::<Vec<_>>(),
+            ["aac", "libx264"]
+        );
+
+        let flash_beep = SyntheticTiming {
+            pattern: SyntheticPattern::FlashBeep,
+            ..SyntheticTiming::default()
+        };
+        let filters = flash_beep.requirements(&options).filters;
+        for filter in ["color", "drawbox", "aevalsrc", "asetrate"] {
+            assert!(filters.contains(filter), "{}", filter);
+        }
+    }
+
+    #[test]
+    fn rejects_options_the_build_cannot_run() {
+        let options = RecordingOptions {
+            include_microphone: true,
+            ..RecordingOptions::default()
+        };
+        let requirements = FfmpegRequirements::for_options(&options);
+        let mut capabilities = FfmpegCapabilities {
+            encoders: requirements.encoders.clone(),
+            decoders: requirements.decoders.clone(),
+            demuxers: requirements.demuxers.clone(),
+            filters: requirements.filters.clone(),
+            ..FfmpegCapabilities::default()
+        };
+        assert!(capabilities.check(&requirements).is_ok());
+
+        let hevc = FfmpegRequirements::for_options(&RecordingOptions {
+            encoder: EncoderProfile {
+                codec: VideoCodec::Hevc,
+                ..EncoderProfile::default()
+            },
+            ..options
+        });
+        let err = capabilities.check(&hevc).expect_err("no libx265");
+        assert!(err.to_string().contains("libx265"), "{}", err);
+
+        capabilities.demuxers.remove("matroska");
+        let err = capabilities
+            .check(&requirements)
+            .expect_err("no matroska demuxer");
+        assert!(err.to_string().contains("matroska demuxer"), "{}", err);
+    }
+}
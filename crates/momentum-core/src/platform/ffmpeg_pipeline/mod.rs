@@ -0,0 +1,130 @@
+mod start;
+mod state;
+mod stop;
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::error::{CoreError, CoreResult};
+use crate::platform::capture_backend::CaptureRequest;
+use crate::platform::pcm_capture::AudioSource;
+use crate::platform::video_timeline::VideoTimeline;
+use crate::time::{host_clock, SharedClock};
+
+use state::RecordingState;
+
+/// An FFmpeg video input whose frames are converted to raw BGRA, stamped on
+/// arrival and fed to the shared encoder pass.
+pub struct VideoSource {
+    /// Input arguments, e.g. `["-f", "x11grab", "-i", ":0"]`.
+    pub input_args: Vec<String>,
+    pub width: u32,
+    pub height: u32,
+    /// Nominal rate of the source; the encoded timeline follows arrival times.
+    pub fps: u32,
+}
+
+pub struct PipelineSources {
+    pub video: VideoSource,
+    pub system_audio: AudioSource,
+    pub mic: Option<AudioSource>,
+}
+
+/// Recording engine for backends whose every source is an FFmpeg input. It
+/// produces the same temp files and timeline markers as the ScreenCaptureKit
+/// backend and finishes through `mux_final_video`.
+pub struct FfmpegPipeline {
+    log_tag: &'static str,
+    state: Mutex<Option<RecordingState>>,
+    mic_muted: Arc<AtomicBool>,
+    system_audio_muted: Arc<AtomicBool>,
+    recording_paused: Arc<AtomicBool>,
+    video_timeline: Arc<Mutex<VideoTimeline>>,
+    clock: SharedClock,
+}
+
+impl FfmpegPipeline {
+    /// `log_tag` prefixes log lines and temp file names (e.g. `X11`).
+    pub fn new(log_tag: &'static str) -> Self {
+        Self {
+            log_tag,
+            state: Mutex::new(None),
+            mic_muted: Arc::new(AtomicBool::new(false)),
+            system_audio_muted: Arc::new(AtomicBool::new(false)),
+            recording_paused: Arc::new(AtomicBool::new(false)),
+            video_timeline: Arc::new(Mutex::new(VideoTimeline::new())),
+            clock: host_clock(),
+        }
+    }
+
+    /// Stamps frames, pauses and arrival markers against `clock` instead of
+    /// the host clock. Camera frames are stamped by the sync handle's clock,
+    /// so pass the same one to [`CameraSyncHandle::with_clock`].
+    ///
+    /// [`CameraSyncHandle::with_clock`]: crate::camera::CameraSyncHandle::with_clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn start(
+        &self,
+        ffmpeg_path: &Path,
+        sources: PipelineSources,
+        request: CaptureRequest<'_>,
+    ) -> CoreResult<()> {
+        if self.is_active() {
+            return Err(CoreError::Recording("Already recording".to_string()));
+        }
+
+        self.set_recording_paused(false);
+        *self.video_timeline.lock().unwrap() = VideoTimeline::new();
+        start::start_recording(
+            self.log_tag,
+            &self.state,
+            &self.mic_muted,
+            &self.system_audio_muted,
+            &self.recording_paused,
+            &self.video_timeline,
+            &self.clock,
+            ffmpeg_path,
+            sources,
+            request,
+        )
+    }
+
+    pub fn stop(&self) -> CoreResult<PathBuf> {
+        self.set_recording_paused(false);
+        stop::stop_recording(self.log_tag, &self.state, &self.recording_paused)
+    }
+
+    pub fn set_recording_paused(&self, paused: bool) {
+        self.video_timeline
+            .lock()
+            .unwrap()
+            .set_paused(paused, self.clock.now_ns());
+        let old = self.recording_paused.swap(paused, Ordering::Relaxed);
+        if old != paused {
+            println!("[{}] Recording pause state -> {}", self.log_tag, paused);
+        }
+    }
+
+    pub fn set_mic_muted(&self, muted: bool) {
+        let old = self.mic_muted.swap(muted, Ordering::Relaxed);
+        if old != muted {
+            println!("[{}] Microphone mute state updated -> {}", self.log_tag, muted);
+        }
+    }
+
+    pub fn set_system_audio_muted(&self, muted: bool) {
+        let old = self.system_audio_muted.swap(muted, Ordering::Relaxed);
+        if old != muted {
+            println!("[{}] System audio mute state updated -> {}", self.log_tag, muted);
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state.lock().unwrap().is_some()
+    }
+}
@@ -0,0 +1,318 @@
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::error::{CoreError, CoreResult};
+use crate::platform::audio_mixer::{AudioMixer, Timestamps};
+use crate::platform::camera_track::{self, CameraTrackWriter};
+use crate::platform::capture_backend::CaptureRequest;
+use crate::platform::ffmpeg_process;
+use crate::platform::pcm_capture::{spawn_pcm_capture, AudioSource, PcmCaptureSpec};
+use crate::platform::video_timeline::{TimelineVideoWriter, VideoTimeline};
+use crate::time::{ArrivalMarker, SharedClock};
+use crate::MIC_VOLUME_GAIN;
+
+use super::state::RecordingState;
+use super::PipelineSources;
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn start_recording(
+    log_tag: &'static str,
+    state: &Mutex<Option<RecordingState>>,
+    mic_muted: &Arc<AtomicBool>,
+    system_audio_muted: &Arc<AtomicBool>,
+    recording_paused: &Arc<AtomicBool>,
+    video_timeline: &Arc<Mutex<VideoTimeline>>,
+    clock: &SharedClock,
+    ffmpeg_path: &Path,
+    sources: PipelineSources,
+    request: CaptureRequest<'_>,
+) -> CoreResult<()> {
+    // Same two-pass layout as the ScreenCaptureKit backend:
+    // 1. video source -> raw BGRA pipe -> pump thread (stamps each frame)
+    //    -> BGRA Matroska pipe -> encoder temp file; camera frames paired
+    //    with each screen frame -> MJPEG Matroska temp file
+    // 2. system audio + mic (if enabled) -> timestamped chunks -> live mixer
+    //    -> raw f32le temp file
+    // 3. On stop: mux_final_video composites the camera and encodes the mix
+    //    next to the video
+    let CaptureRequest {
+        output_path,
+        options,
+        camera_sync,
+    } = request;
+    if state.lock().unwrap().is_some() {
+        return Err(CoreError::Recording("Already recording".to_string()));
+    }
+    let capture_started_ns = clock.now_ns();
+    recording_paused.store(false, Ordering::Relaxed);
+
+    let PipelineSources {
+        video,
+        system_audio: system_audio_source,
+        mic: mic_source,
+    } = sources;
+    let mic_enabled = mic_source.is_some();
+    let (output_width, output_height) = options.output_scale.output_size(video.width, video.height);
+    println!(
+        "[{}] Starting recording ({}x{} @ {} fps, output {}x{})",
+        log_tag, video.width, video.height, video.fps, output_width, output_height
+    );
+    println!("[{}]   Final output: {:?}", log_tag, output_path);
+    println!("[{}]   Mic: {}", log_tag, mic_enabled);
+
+    let temp_dir = std::env::temp_dir();
+    let session_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
+    let prefix = log_tag.to_lowercase();
+    let temp_video_path = temp_dir.join(format!("{}_video_{}.mp4", prefix, session_id));
+    let mixed_audio_path = temp_dir.join(format!("{}_mix_{}.raw", prefix, session_id));
+    let camera_path = camera_sync
+        .as_ref()
+        .map(|_| temp_dir.join(format!("{}_camera_{}.mkv", prefix, session_id)));
+    // Removed by `abort_start` if anything after the encoder spawn fails
+    let mut temp_files = vec![temp_video_path.clone(), mixed_audio_path.clone()];
+    temp_files.extend(camera_path.clone());
+    let camera_track = Arc::new(Mutex::new(camera_path.map(|camera_path| {
+        CameraTrackWriter::new(
+            camera_path,
+            options.camera_overlay,
+            output_width,
+            output_height,
+            video_timeline.clone(),
+        )
+    })));
+
+    // === PASS 1: VIDEO ONLY FFmpeg ===
+    let mut encoder = ffmpeg_process::spawn_bgra_encoder(
+        ffmpeg_path,
+        output_width,
+        output_height,
+        video.fps,
+        &options.encoder,
+        &temp_video_path,
+    )?;
+    println!("[{}] Video encoder started (PID: {})", log_tag, encoder.id());
+    ffmpeg_process::forward_stderr(&mut encoder, "FFmpeg-Video");
+    let encoder_stdin = encoder
+        .stdin
+        .take()
+        .ok_or_else(|| CoreError::Recording("Failed to get FFmpeg stdin".to_string()))
+        .map_err(|err| ffmpeg_process::abort_start(&mut [&mut encoder], &temp_files, err))?;
+    let video_writer = TimelineVideoWriter::new(
+        encoder_stdin,
+        video.width,
+        video.height,
+        video.fps,
+        video_timeline.clone(),
+    )
+    .map_err(|e| {
+        let err = CoreError::Recording(format!("Failed to write video stream header: {}", e));
+        ffmpeg_process::abort_start(&mut [&mut encoder], &temp_files, err)
+    })?;
+    let video_writer = Arc::new(Mutex::new(Some(video_writer)));
+
+    let mut source_cmd = Command::new(ffmpeg_path);
+    source_cmd
+        .args(["-hide_banner", "-loglevel", "warning"])
+        .args(&video.input_args)
+        .args(["-f", "rawvideo", "-pix_fmt", "bgra", "-"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    ffmpeg_process::isolate_from_terminal(&mut source_cmd);
+    let mut source = source_cmd.spawn().map_err(|e| {
+        let err = CoreError::Recording(format!("Failed to start video source: {}", e));
+        ffmpeg_process::abort_start(&mut [&mut encoder], &temp_files, err)
+    })?;
+    println!("[{}] Video source started (PID: {})", log_tag, source.id());
+    ffmpeg_process::forward_stderr(&mut source, "FFmpeg-VideoSource");
+    let source_stdout = source
+        .stdout
+        .take()
+        .ok_or_else(|| CoreError::Recording("Failed to capture video source stdout".to_string()))
+        .map_err(|err| {
+            ffmpeg_process::abort_start(&mut [&mut source, &mut encoder], &temp_files, err)
+        })?;
+
+    let video_frame_count = Arc::new(AtomicU64::new(0));
+    let first_screen_frame_arrival = ArrivalMarker::new();
+    let video_pump = {
+        let video_frame_count = video_frame_count.clone();
+        let first_screen_frame_arrival = first_screen_frame_arrival.clone();
+        let clock = clock.clone();
+        let video_writer = video_writer.clone();
+        let camera_track = camera_track.clone();
+        let frame_bytes = video.width as usize * video.height as usize * 4;
+        let log_every = video.fps.max(1) as u64;
+        thread::spawn(move || {
+            let mut reader = BufReader::with_capacity(frame_bytes, source_stdout);
+            let mut frame = vec![0u8; frame_bytes];
+            while reader.read_exact(&mut frame).is_ok() {
+                let capture_ns = clock.now_ns();
+                first_screen_frame_arrival.mark(capture_ns);
+                let camera_frame = camera_sync
+                    .as_ref()
+                    .and_then(|sync| sync.emit_for_screen_pts(capture_ns));
+                let written = match video_writer.lock().unwrap().as_mut() {
+                    Some(writer) => writer.write_frame(capture_ns, &frame),
+                    None => break,
+                };
+                match written {
+                    Ok(true) => {}
+                    Ok(false) => continue,
+                    Err(_) => {
+                        eprintln!("[{}] Video encoder closed its input", log_tag);
+                        break;
+                    }
+                }
+                if let Some(sync) = &camera_sync {
+                    camera_track::record_tick(
+                        &camera_track,
+                        log_tag,
+                        capture_ns,
+                        sync,
+                        camera_frame.as_ref(),
+                    );
+                }
+                let count = video_frame_count.fetch_add(1, Ordering::Relaxed);
+                if count == 0 {
+                    println!("[{}] First video frame written ({} bytes)", log_tag, frame.len());
+                } else if count.is_multiple_of(log_every) {
+                    println!("[{}] Video frames: {}", log_tag, count + 1);
+                }
+            }
+            // The writer stays in the state; stop finishes it with the end time.
+        })
+    };
+
+    // === AUDIO MIX ===
+    let mixer = match AudioMixer::create(&mixed_audio_path, clock.clone(), video_timeline.clone()) {
+        Ok(mixer) => mixer,
+        Err(err) => {
+            let mut processes = [&mut source, &mut encoder];
+            return Err(ffmpeg_process::abort_start(
+                &mut processes,
+                &temp_files,
+                err,
+            ));
+        }
+    };
+
+    // === SYSTEM AUDIO ===
+    log_device(log_tag, "System audio", &system_audio_source);
+    let audio_samples_written = Arc::new(AtomicU64::new(0));
+    let first_system_audio_arrival = ArrivalMarker::new();
+    let mut system_audio = match spawn_pcm_capture(
+        ffmpeg_path,
+        &system_audio_source,
+        PcmCaptureSpec {
+            mixer_input: mixer.add_input(
+                "system",
+                1.0,
+                Timestamps::Arrival,
+                system_audio_source.latency_ns,
+            ),
+            log_label: "FFmpeg-SystemAudio",
+            muted: system_audio_muted.clone(),
+            recording_paused: recording_paused.clone(),
+            samples_written: audio_samples_written.clone(),
+            first_arrival: first_system_audio_arrival.clone(),
+            correct_drift: false,
+            clock: clock.clone(),
+            capture_started_ns,
+        },
+    ) {
+        Ok(capture) => capture,
+        Err(err) => {
+            temp_files.extend(mixer.stem_paths());
+            let mut processes = [&mut source, &mut encoder];
+            return Err(ffmpeg_process::abort_start(
+                &mut processes,
+                &temp_files,
+                err,
+            ));
+        }
+    };
+
+    // === MIC ===
+    let mic_samples_written = Arc::new(AtomicU64::new(0));
+    let first_mic_audio_arrival = ArrivalMarker::new();
+    if let Some(mic_source) = &mic_source {
+        log_device(log_tag, "Mic", mic_source);
+    }
+    let mic_audio = match &mic_source {
+        Some(mic_source) => match spawn_pcm_capture(
+            ffmpeg_path,
+            mic_source,
+            PcmCaptureSpec {
+                mixer_input: mixer.add_input(
+                    "mic",
+                    MIC_VOLUME_GAIN,
+                    Timestamps::Arrival,
+                    mic_source.latency_ns,
+                ),
+                log_label: "FFmpeg-Mic",
+                muted: mic_muted.clone(),
+                recording_paused: recording_paused.clone(),
+                samples_written: mic_samples_written.clone(),
+                first_arrival: first_mic_audio_arrival.clone(),
+                correct_drift: true,
+                clock: clock.clone(),
+                capture_started_ns,
+            },
+        ) {
+            Ok(capture) => Some(capture),
+            Err(err) => {
+                temp_files.extend(mixer.stem_paths());
+                let mut processes = [&mut source, &mut encoder, &mut system_audio.process];
+                return Err(ffmpeg_process::abort_start(
+                    &mut processes,
+                    &temp_files,
+                    err,
+                ));
+            }
+        },
+        None => None,
+    };
+
+    *state.lock().unwrap() = Some(RecordingState {
+        source_process: source,
+        encoder_process: encoder,
+        video_writer,
+        video_pump: Some(video_pump),
+        camera_track,
+        system_audio: Some(system_audio),
+        mic_audio,
+        mixer,
+        temp_video_path,
+        mixed_audio_path,
+        output_path: output_path.to_path_buf(),
+        video_frame_count,
+        audio_samples_written,
+        mic_samples_written,
+        clock: clock.clone(),
+        capture_started_ns,
+        first_screen_frame_arrival,
+        first_system_audio_arrival,
+        first_mic_audio_arrival,
+        requested_fps: video.fps,
+        ffmpeg_path: ffmpeg_path.to_path_buf(),
+        encoder: options.encoder,
+    });
+
+    println!("[{}] ✓ Recording started successfully", log_tag);
+    Ok(())
+}
+
+fn log_device(log_tag: &str, name: &str, source: &AudioSource) {
+    println!(
+        "[{}] {} device: {} (latency offset {:.1} ms)",
+        log_tag,
+        name,
+        source.device_id,
+        source.latency_ns as f64 / 1_000_000.0
+    );
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin};
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::encoder::EncoderProfile;
+use crate::platform::audio_mixer::AudioMixer;
+use crate::platform::camera_track::CameraTrackWriter;
+use crate::platform::pcm_capture::PcmCapture;
+use crate::platform::video_timeline::TimelineVideoWriter;
+use crate::time::{ArrivalMarker, SharedClock};
+
+pub(super) struct RecordingState {
+    // FFmpeg process producing raw BGRA frames on stdout
+    pub source_process: Child,
+    // Encoder pass fed by the video pump thread
+    pub encoder_process: Child,
+    // Taken and finished on stop, which closes the encoder's stdin
+    pub video_writer: Arc<Mutex<Option<TimelineVideoWriter<ChildStdin>>>>,
+    pub video_pump: Option<JoinHandle<()>>,
+    // Camera frames for compositing, when the camera is recorded
+    pub camera_track: Arc<Mutex<Option<CameraTrackWriter>>>,
+    pub system_audio: Option<PcmCapture>,
+    pub mic_audio: Option<PcmCapture>,
+    // Live mix of system audio and mic, finished on stop
+    pub mixer: AudioMixer,
+    // Paths
+    pub temp_video_path: PathBuf,
+    pub mixed_audio_path: PathBuf,
+    pub output_path: PathBuf,
+    pub video_frame_count: Arc<AtomicU64>,
+    pub audio_samples_written: Arc<AtomicU64>,
+    pub mic_samples_written: Arc<AtomicU64>,
+    pub clock: SharedClock,
+    pub capture_started_ns: u64,
+    pub first_screen_frame_arrival: ArrivalMarker,
+    pub first_system_audio_arrival: ArrivalMarker,
+    pub first_mic_audio_arrival: ArrivalMarker,
+    pub requested_fps: u32,
+    pub ffmpeg_path: PathBuf,
+    // Re-encodes at mux time match the live pass
+    pub encoder: EncoderProfile,
+}
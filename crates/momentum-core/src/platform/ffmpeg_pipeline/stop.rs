@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::error::{CoreError, CoreResult};
+use crate::platform::audio_alignment::align_mic_to_system;
+use crate::platform::camera_track::CameraTrack;
+use crate::platform::ffmpeg_process::{interrupt_and_wait, wait_with_timeout};
+use crate::platform::mux::mux_final_video;
+
+use super::state::RecordingState;
+
+pub(super) fn stop_recording(
+    log_tag: &str,
+    state: &Mutex<Option<RecordingState>>,
+    recording_paused: &Arc<AtomicBool>,
+) -> CoreResult<PathBuf> {
+    println!("[{}] === STOP RECORDING START ===", log_tag);
+    let stop_start = std::time::Instant::now();
+
+    let mut state = state
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| CoreError::Recording("No active recording".to_string()))?;
+    let timeline_end_ns = state.clock.now_ns();
+    recording_paused.store(false, Ordering::Relaxed);
+
+    // STEP 1: Stop the video source and let the pump thread drain it
+    interrupt_and_wait(&mut state.source_process, Duration::from_secs(3), "Video source FFmpeg");
+    if let Some(pump) = state.video_pump.take() {
+        let _ = pump.join();
+    }
+
+    // Close the timeline at the moment stop was requested; dropping the
+    // writer closes the encoder stdin
+    let video_duration = state
+        .video_writer
+        .lock()
+        .unwrap()
+        .take()
+        .and_then(|mut writer| writer.finish(timeline_end_ns).ok())
+        .unwrap_or_default();
+    let camera_track = finish_camera_track(log_tag, &state, timeline_end_ns);
+
+    // STEP 2: Let the encoder finalize the temp video
+    wait_with_timeout(
+        &mut state.encoder_process,
+        Duration::from_secs(5),
+        "Video FFmpeg",
+    );
+
+    // STEP 3: Stop audio captures and flush their files
+    if let Some(capture) = state.system_audio.take() {
+        capture.stop("System audio FFmpeg");
+    }
+    if let Some(capture) = state.mic_audio.take() {
+        capture.stop("Mic FFmpeg");
+    }
+
+    // STEP 4: Write the rest of the mix, padded to the video length
+    let mix = state.mixer.finish(timeline_end_ns);
+
+    let video_frames = state.video_frame_count.load(Ordering::Relaxed);
+    let system_audio_samples = state.audio_samples_written.load(Ordering::Relaxed);
+    let mic_audio_samples = state.mic_samples_written.load(Ordering::Relaxed);
+
+    let video_seconds = video_duration.as_secs_f64();
+    println!(
+        "[{}] Frame stats: video={} ({:.3}s, nominal {} fps), system_samples={} mic_samples={}",
+        log_tag,
+        video_frames, video_seconds, state.requested_fps, system_audio_samples, mic_audio_samples
+    );
+    println!(
+        "[{}] Timeline markers (from recorder start): screen={}ms system={}ms mic={}ms total={}ms",
+        log_tag,
+        state.first_screen_frame_arrival.millis_since(state.capture_started_ns),
+        state.first_system_audio_arrival.millis_since(state.capture_started_ns),
+        state.first_mic_audio_arrival.millis_since(state.capture_started_ns),
+        timeline_end_ns.saturating_sub(state.capture_started_ns) / 1_000_000
+    );
+    let mut metadata = Vec::new();
+    let mixed_audio_path = match &mix {
+        Ok(summary) => {
+            println!(
+                "[{}] Audio mix: {} frames ({:.3}s)",
+                log_tag,
+                summary.frames,
+                summary.duration.as_secs_f64()
+            );
+            for source in &summary.sources {
+                println!(
+                    "[{}]   {}: {} gaps ({} frames of silence), {} overlaps ({} frames dropped), {} late frames, {} stray frames",
+                    log_tag,
+                    source.label,
+                    source.gaps,
+                    source.gap_frames,
+                    source.overlaps,
+                    source.overlap_frames,
+                    source.late_frames,
+                    source.stray_frames
+                );
+            }
+            metadata = align_mic_to_system(summary, &state.mixed_audio_path, log_tag);
+            Some(state.mixed_audio_path.as_path())
+        }
+        Err(err) => {
+            println!("[{}] ⚠ Audio mix failed: {}, muxing video only", log_tag, err);
+            None
+        }
+    };
+
+    // STEP 5: Encode the mix and mux it with the video
+    println!("[{}] Muxing video + audio...", log_tag);
+    let mux_result = mux_final_video(
+        &state.temp_video_path,
+        mixed_audio_path,
+        camera_track.as_ref(),
+        &state.output_path,
+        &state.ffmpeg_path,
+        &state.encoder,
+        state.requested_fps,
+        &metadata,
+    );
+
+    if let Err(e) = mux_result {
+        println!("[{}] ⚠ Mux failed: {}, returning video-only", log_tag, e);
+        if state.temp_video_path.exists() {
+            let _ = std::fs::copy(&state.temp_video_path, &state.output_path);
+        }
+    }
+
+    let _ = std::fs::remove_file(&state.temp_video_path);
+    let _ = std::fs::remove_file(&state.mixed_audio_path);
+    if let Some(track) = &camera_track {
+        let _ = std::fs::remove_file(&track.path);
+    }
+    for stem in state.mixer.stem_paths() {
+        let _ = std::fs::remove_file(stem);
+    }
+
+    println!(
+        "[{}] === STOP RECORDING COMPLETE in {:?} ===",
+        log_tag,
+        stop_start.elapsed()
+    );
+
+    if state.output_path.exists() {
+        Ok(state.output_path.clone())
+    } else {
+        Err(CoreError::Recording(format!(
+            "Output file not created: {:?}",
+            state.output_path
+        )))
+    }
+}
+
+fn finish_camera_track(
+    log_tag: &str,
+    state: &RecordingState,
+    timeline_end_ns: u64,
+) -> Option<CameraTrack> {
+    let writer = state.camera_track.lock().unwrap().take()?;
+    match writer.finish(timeline_end_ns) {
+        Ok(Some(track)) => {
+            println!(
+                "[{}] Camera track: {} frames, visible {:?}",
+                log_tag, track.frames, track.visible_spans
+            );
+            Some(track)
+        }
+        Ok(None) => {
+            println!("[{}] ⚠ No camera frames recorded", log_tag);
+            None
+        }
+        Err(err) => {
+            println!("[{}] ⚠ Failed to finish camera track: {}", log_tag, err);
+            None
+        }
+    }
+}
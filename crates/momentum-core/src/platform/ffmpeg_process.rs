@@ -0,0 +1,153 @@
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::encoder::EncoderProfile;
+use crate::error::{CoreError, CoreResult};
+use crate::platform::ffmpeg_capabilities::FfmpegRequirements;
+use crate::platform::matroska;
+
+/// The BGRA Matroska stream on stdin.
+const BGRA_PIPE_INPUT: [&str; 4] = ["-f", "matroska", "-i", "pipe:0"];
+
+/// Spawns the video-only FFmpeg pass that encodes the BGRA Matroska stream
+/// written to its stdin (see `video_timeline::TimelineVideoWriter`) into
+/// `output_path`, scaled to `width` x `height` and encoded with `encoder` at
+/// up to `fps`.
+/// Frame timestamps pass straight through, so the result is variable frame
+/// rate. Both capture backends feed this encoder so the mux step always
+/// receives the same kind of intermediate file.
+pub(crate) fn spawn_bgra_encoder(
+    ffmpeg_path: &Path,
+    width: u32,
+    height: u32,
+    fps: u32,
+    encoder: &EncoderProfile,
+    output_path: &Path,
+) -> CoreResult<Child> {
+    let mut cmd = Command::new(ffmpeg_path);
+    cmd.args(["-y", "-hide_banner", "-loglevel", "warning"]);
+    cmd.args(BGRA_PIPE_INPUT);
+    cmd.args([
+        "-fps_mode",
+        "passthrough",
+        "-vf",
+        &scale_graph(width, height),
+    ]);
+    cmd.args(encoder.video_args("v", fps));
+    cmd.args([
+        "-an", // No audio in this pass
+        "-movflags",
+        "+faststart",
+    ]);
+    cmd.arg(output_path.to_str().unwrap());
+
+    cmd.stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    isolate_from_terminal(&mut cmd);
+
+    cmd.spawn()
+        .map_err(|e| CoreError::Recording(format!("Failed to start FFmpeg: {}", e)))
+}
+
+/// Rounds the size down to even, which the 4:2:0 encoders need.
+fn scale_graph(width: u32, height: u32) -> String {
+    format!("scale={}:{}", width - (width % 2), height - (height % 2))
+}
+
+/// What [`spawn_bgra_encoder`] runs with `encoder`.
+pub(crate) fn add_encoder_requirements(
+    requirements: &mut FfmpegRequirements,
+    encoder: &EncoderProfile,
+) {
+    requirements.add_input(&BGRA_PIPE_INPUT);
+    requirements.add_decoder(matroska::BGRA_DECODER);
+    requirements.add_graph(&scale_graph(2, 2));
+    requirements.add_encoder(encoder.codec.encoder_name());
+}
+
+/// Puts the child in its own process group so a Ctrl-C in the terminal only
+/// reaches our process, which then stops FFmpeg in order through
+/// [`interrupt_and_wait`] instead of every stage dying at once.
+pub(crate) fn isolate_from_terminal(cmd: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+}
+
+/// Forwards every non-empty stderr line of `child` to stdout with `label`.
+pub(crate) fn forward_stderr(child: &mut Child, label: &'static str) {
+    if let Some(stderr) = child.stderr.take() {
+        thread::spawn(move || {
+            use std::io::{BufRead, BufReader};
+            let reader = BufReader::new(stderr);
+            for line in reader.lines().map_while(Result::ok) {
+                if !line.is_empty() {
+                    println!("[{}] {}", label, line);
+                }
+            }
+        });
+    }
+}
+
+/// Tears down a session whose start failed once its encoder was running:
+/// kills and reaps `processes`, deletes whichever `temp_files` were written
+/// and hands `err` back for the caller to return.
+pub(crate) fn abort_start(
+    processes: &mut [&mut Child],
+    temp_files: &[PathBuf],
+    err: CoreError,
+) -> CoreError {
+    for process in processes.iter_mut() {
+        let _ = process.kill();
+        let _ = process.wait();
+    }
+    for path in temp_files {
+        let _ = std::fs::remove_file(path);
+    }
+    err
+}
+
+/// Asks an FFmpeg process to finish its output cleanly (SIGINT), then waits
+/// up to `timeout` before killing it.
+pub(crate) fn interrupt_and_wait(child: &mut Child, timeout: Duration, label: &str) {
+    let _ = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status();
+    wait_with_timeout(child, timeout, label);
+}
+
+/// Waits for `child` to exit, killing it once `timeout` has elapsed.
+pub(crate) fn wait_with_timeout(child: &mut Child, timeout: Duration, label: &str) {
+    let wait_start = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                println!(
+                    "[FFmpeg] ✓ {} exited: {:?} ({:?})",
+                    label,
+                    status,
+                    wait_start.elapsed()
+                );
+                break;
+            }
+            Ok(None) => {
+                if wait_start.elapsed() > timeout {
+                    println!("[FFmpeg] ⚠ {} timeout, killing...", label);
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break;
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+            Err(_) => {
+                let _ = child.kill();
+                break;
+            }
+        }
+    }
+}
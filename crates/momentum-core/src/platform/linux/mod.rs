@@ -0,0 +1,2 @@
+pub mod pulse;
+pub mod v4l2;
@@ -0,0 +1,175 @@
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::error::{CoreError, CoreResult};
+use crate::platform::pcm_capture::AudioSource;
+
+/// PulseAudio source names that always follow the server defaults. They also
+/// work against PipeWire through `pipewire-pulse`.
+pub const DEFAULT_MONITOR_SOURCE: &str = "@DEFAULT_MONITOR@";
+pub const DEFAULT_INPUT_SOURCE: &str = "@DEFAULT_SOURCE@";
+
+const MONITOR_SUFFIX: &str = ".monitor";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PulseSource {
+    pub index: u32,
+    pub name: String,
+    pub driver: String,
+    pub sample_spec: String,
+    pub state: String,
+    /// `true` for `<sink>.monitor` sources, which carry what a sink is playing.
+    pub is_monitor: bool,
+}
+
+/// Lists every capture source known to the Pulse server, monitors included.
+pub fn list_sources() -> CoreResult<Vec<PulseSource>> {
+    let stdout = pactl(&["list", "short", "sources"])?;
+    Ok(parse_short_sources(&stdout))
+}
+
+/// Picks the monitor of the default sink, falling back to any monitor source.
+pub fn resolve_system_audio_source(sources: &[PulseSource]) -> String {
+    let default_sink_monitor = pactl(&["get-default-sink"])
+        .ok()
+        .map(|sink| format!("{}{}", sink.trim(), MONITOR_SUFFIX));
+    pick_system_audio_source(sources, default_sink_monitor.as_deref())
+}
+
+/// Picks the default source unless it is a monitor, in which case the first
+/// real input wins so the mic track never duplicates system audio.
+pub fn resolve_mic_source(sources: &[PulseSource]) -> String {
+    let default_source = pactl(&["get-default-source"])
+        .ok()
+        .map(|source| source.trim().to_string());
+    pick_mic_source(sources, default_source.as_deref())
+}
+
+fn pick_system_audio_source(sources: &[PulseSource], default_sink_monitor: Option<&str>) -> String {
+    if let Some(name) = default_sink_monitor {
+        if sources.iter().any(|source| source.name == name) {
+            return name.to_string();
+        }
+    }
+    sources
+        .iter()
+        .find(|source| source.is_monitor)
+        .map(|source| source.name.clone())
+        .unwrap_or_else(|| DEFAULT_MONITOR_SOURCE.to_string())
+}
+
+fn pick_mic_source(sources: &[PulseSource], default_source: Option<&str>) -> String {
+    if let Some(name) = default_source {
+        if !name.is_empty() && !name.ends_with(MONITOR_SUFFIX) {
+            return name.to_string();
+        }
+    }
+    sources
+        .iter()
+        .find(|source| !source.is_monitor)
+        .map(|source| source.name.clone())
+        .unwrap_or_else(|| DEFAULT_INPUT_SOURCE.to_string())
+}
+
+fn pactl(args: &[&str]) -> CoreResult<String> {
+    let output = Command::new("pactl")
+        .args(args)
+        .output()
+        .map_err(|e| CoreError::Recording(format!("Failed to run pactl: {}", e)))?;
+    if !output.status.success() {
+        return Err(CoreError::Recording(format!(
+            "pactl {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Parses `pactl list short sources`:
+/// `<index>\t<name>\t<driver>\t<sample spec>\t<state>`.
+fn parse_short_sources(stdout: &str) -> Vec<PulseSource> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split('\t');
+            let index = columns.next()?.trim().parse().ok()?;
+            let name = columns.next()?.trim().to_string();
+            if name.is_empty() {
+                return None;
+            }
+            let driver = columns.next().unwrap_or_default().trim().to_string();
+            let sample_spec = columns.next().unwrap_or_default().trim().to_string();
+            let state = columns.next().unwrap_or_default().trim().to_string();
+            Some(PulseSource {
+                index,
+                is_monitor: name.ends_with(MONITOR_SUFFIX),
+                name,
+                driver,
+                sample_spec,
+                state,
+            })
+        })
+        .collect()
+}
+
+/// FFmpeg input for a Pulse source, recorded by the shared PCM capture. The
+/// source name doubles as its device id.
+pub fn audio_source(name: &str) -> AudioSource {
+    AudioSource::new(name, vec![
+        "-f".to_string(),
+        "pulse".to_string(),
+        "-i".to_string(),
+        name.to_string(),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_short_sources, pick_mic_source, pick_system_audio_source};
+
+    const SOURCES: &str = "\
+0\talsa_output.pci-0000_00_1f.3.analog-stereo.monitor\tmodule-alsa-card.c\ts16le 2ch 48000Hz\tSUSPENDED
+1\talsa_input.pci-0000_00_1f.3.analog-stereo\tmodule-alsa-card.c\ts16le 2ch 48000Hz\tRUNNING
+7\tmomentum_null.monitor\tmodule-null-sink.c\tfloat32le 2ch 44100Hz\tIDLE
+";
+
+    #[test]
+    fn parses_short_source_listing() {
+        let sources = parse_short_sources(SOURCES);
+        assert_eq!(sources.len(), 3);
+        assert_eq!(sources[1].index, 1);
+        assert_eq!(sources[1].name, "alsa_input.pci-0000_00_1f.3.analog-stereo");
+        assert_eq!(sources[1].sample_spec, "s16le 2ch 48000Hz");
+        assert!(!sources[1].is_monitor);
+        assert!(sources[2].is_monitor);
+        assert_eq!(sources[2].driver, "module-null-sink.c");
+    }
+
+    #[test]
+    fn prefers_default_sink_monitor_for_system_audio() {
+        let sources = parse_short_sources(SOURCES);
+        assert_eq!(
+            pick_system_audio_source(&sources, Some("momentum_null.monitor")),
+            "momentum_null.monitor"
+        );
+        assert_eq!(
+            pick_system_audio_source(&sources, Some("missing.monitor")),
+            "alsa_output.pci-0000_00_1f.3.analog-stereo.monitor"
+        );
+        assert_eq!(pick_system_audio_source(&[], None), "@DEFAULT_MONITOR@");
+    }
+
+    #[test]
+    fn never_uses_a_monitor_as_microphone() {
+        let sources = parse_short_sources(SOURCES);
+        assert_eq!(
+            pick_mic_source(&sources, Some("momentum_null.monitor")),
+            "alsa_input.pci-0000_00_1f.3.analog-stereo"
+        );
+        assert_eq!(pick_mic_source(&sources, Some("usb_mic")), "usb_mic");
+        assert_eq!(pick_mic_source(&[], None), "@DEFAULT_SOURCE@");
+    }
+}
@@ -0,0 +1,287 @@
+use std::fs::OpenOptions;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::Serialize;
+
+use crate::error::{CoreError, CoreResult};
+use crate::camera_device::{spawn_mjpeg_ffmpeg, CameraDevice, MjpegStream};
+use crate::platform::macos::ffmpeg::FfmpegLocator;
+
+const PREFERRED_SIZE: (u32, u32) = (640, 480);
+
+// Subset of <linux/videodev2.h> needed to enumerate capture formats.
+const VIDIOC_QUERYCAP: u64 = 0x8068_5600;
+const VIDIOC_ENUM_FMT: u64 = 0xC040_5602;
+const VIDIOC_ENUM_FRAMESIZES: u64 = 0xC02C_564A;
+const V4L2_BUF_TYPE_VIDEO_CAPTURE: u32 = 1;
+const V4L2_CAP_VIDEO_CAPTURE: u32 = 0x0000_0001;
+const V4L2_CAP_DEVICE_CAPS: u32 = 0x8000_0000;
+const V4L2_FRMSIZE_TYPE_DISCRETE: u32 = 1;
+const V4L2_PIX_FMT_MJPEG: u32 = fourcc(b"MJPG");
+
+#[repr(C)]
+struct V4l2Capability {
+    driver: [u8; 16],
+    card: [u8; 32],
+    bus_info: [u8; 32],
+    version: u32,
+    capabilities: u32,
+    device_caps: u32,
+    reserved: [u32; 3],
+}
+
+#[repr(C)]
+struct V4l2FmtDesc {
+    index: u32,
+    buf_type: u32,
+    flags: u32,
+    description: [u8; 32],
+    pixelformat: u32,
+    mbus_code: u32,
+    reserved: [u32; 3],
+}
+
+#[repr(C)]
+struct V4l2FrmSizeEnum {
+    index: u32,
+    pixel_format: u32,
+    size_type: u32,
+    // discrete: width, height; stepwise: min/max/step for width then height
+    sizes: [u32; 6],
+    reserved: [u32; 2],
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct V4l2Format {
+    pub fourcc: String,
+    pub description: String,
+    /// Discrete frame sizes; empty when the driver reports a stepwise range.
+    pub sizes: Vec<(u32, u32)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct V4l2DeviceInfo {
+    pub path: PathBuf,
+    pub card: String,
+    pub driver: String,
+    pub formats: Vec<V4l2Format>,
+}
+
+/// Lists `/dev/video*` nodes that can capture video, with their formats.
+/// Metadata-only nodes (which UVC drivers expose alongside each camera) are
+/// skipped.
+pub fn list_devices() -> CoreResult<Vec<V4l2DeviceInfo>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir("/dev")?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.starts_with("video"))
+                .unwrap_or(false)
+        })
+        .collect();
+    paths.sort_by_key(|path| device_number(path));
+
+    let mut devices = Vec::new();
+    for path in paths {
+        match query_device(&path) {
+            Ok(Some(device)) => devices.push(device),
+            Ok(None) => {}
+            Err(err) => eprintln!("[V4L2] Skipping {}: {}", path.display(), err),
+        }
+    }
+    Ok(devices)
+}
+
+fn query_device(path: &Path) -> CoreResult<Option<V4l2DeviceInfo>> {
+    let file = OpenOptions::new().read(true).write(true).open(path)?;
+    let fd = file.as_raw_fd();
+
+    let mut caps: V4l2Capability = unsafe { std::mem::zeroed() };
+    ioctl(fd, VIDIOC_QUERYCAP, &mut caps)?;
+    let effective_caps = if caps.capabilities & V4L2_CAP_DEVICE_CAPS != 0 {
+        caps.device_caps
+    } else {
+        caps.capabilities
+    };
+    if effective_caps & V4L2_CAP_VIDEO_CAPTURE == 0 {
+        return Ok(None);
+    }
+
+    let mut formats = Vec::new();
+    for index in 0.. {
+        let mut desc: V4l2FmtDesc = unsafe { std::mem::zeroed() };
+        desc.index = index;
+        desc.buf_type = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        if ioctl(fd, VIDIOC_ENUM_FMT, &mut desc).is_err() {
+            break;
+        }
+
+        let mut sizes = Vec::new();
+        for size_index in 0.. {
+            let mut frame_size: V4l2FrmSizeEnum = unsafe { std::mem::zeroed() };
+            frame_size.index = size_index;
+            frame_size.pixel_format = desc.pixelformat;
+            if ioctl(fd, VIDIOC_ENUM_FRAMESIZES, &mut frame_size).is_err()
+                || frame_size.size_type != V4L2_FRMSIZE_TYPE_DISCRETE
+            {
+                break;
+            }
+            sizes.push((frame_size.sizes[0], frame_size.sizes[1]));
+        }
+
+        formats.push(V4l2Format {
+            fourcc: fourcc_to_string(desc.pixelformat),
+            description: c_string(&desc.description),
+            sizes,
+        });
+    }
+
+    Ok(Some(V4l2DeviceInfo {
+        path: path.to_path_buf(),
+        card: c_string(&caps.card),
+        driver: c_string(&caps.driver),
+        formats,
+    }))
+}
+
+fn ioctl<T>(fd: i32, request: u64, arg: &mut T) -> CoreResult<()> {
+    let result = unsafe { libc::ioctl(fd, request as _, arg as *mut T) };
+    if result < 0 {
+        return Err(CoreError::Io(std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+const fn fourcc(code: &[u8; 4]) -> u32 {
+    (code[0] as u32) | ((code[1] as u32) << 8) | ((code[2] as u32) << 16) | ((code[3] as u32) << 24)
+}
+
+fn fourcc_to_string(code: u32) -> String {
+    code.to_le_bytes().iter().map(|b| *b as char).collect()
+}
+
+fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+fn device_number(path: &Path) -> u32 {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.trim_start_matches("video").parse().ok())
+        .unwrap_or(u32::MAX)
+}
+
+/// A V4L2 webcam read through FFmpeg. Cameras that deliver MJPEG natively are
+/// passed through; anything else is transcoded to MJPEG.
+pub struct V4l2Camera {
+    device: V4l2DeviceInfo,
+}
+
+impl V4l2Camera {
+    pub fn new(device: V4l2DeviceInfo) -> Self {
+        Self { device }
+    }
+
+    /// Returns the first capture-capable device, if any.
+    pub fn first_available() -> CoreResult<Option<Self>> {
+        Ok(list_devices()?.into_iter().next().map(Self::new))
+    }
+
+    fn capture_mode(&self) -> (Option<&'static str>, (u32, u32)) {
+        let mjpeg = fourcc_to_string(V4L2_PIX_FMT_MJPEG);
+        let format = self
+            .device
+            .formats
+            .iter()
+            .find(|format| format.fourcc == mjpeg)
+            .or_else(|| self.device.formats.first());
+        let input_format = format
+            .filter(|format| format.fourcc == mjpeg)
+            .map(|_| "mjpeg");
+        let size = format
+            .map(|format| choose_size(&format.sizes))
+            .unwrap_or(PREFERRED_SIZE);
+        (input_format, size)
+    }
+}
+
+impl CameraDevice for V4l2Camera {
+    fn label(&self) -> String {
+        format!("{} ({})", self.device.card, self.device.path.display())
+    }
+
+    fn id(&self) -> String {
+        self.device.path.display().to_string()
+    }
+
+    fn open_mjpeg(&self, ffmpeg_locator: &FfmpegLocator, fps: u32) -> CoreResult<MjpegStream> {
+        let ffmpeg_path = ffmpeg_locator
+            .resolve()
+            .map_err(|err| CoreError::Camera(err.to_string()))?;
+        let (input_format, (width, height)) = self.capture_mode();
+
+        let mut cmd = Command::new(&ffmpeg_path);
+        cmd.args(["-hide_banner", "-loglevel", "warning", "-f", "v4l2"]);
+        if let Some(format) = input_format {
+            cmd.args(["-input_format", format]);
+        }
+        cmd.args([
+            "-framerate",
+            &fps.to_string(),
+            "-video_size",
+            &format!("{}x{}", width, height),
+            "-i",
+        ]);
+        cmd.arg(&self.device.path);
+        cmd.args(["-f", "image2pipe"]);
+        if input_format.is_some() {
+            cmd.args(["-vcodec", "copy"]);
+        } else {
+            cmd.args(["-vf", &format!("fps={}", fps), "-vcodec", "mjpeg", "-q:v", "3"]);
+        }
+        cmd.arg("-");
+        cmd.stdin(Stdio::null());
+
+        spawn_mjpeg_ffmpeg(cmd, width, height)
+    }
+}
+
+/// Picks 640x480 when offered, otherwise the smallest size at least that wide,
+/// otherwise the largest available.
+fn choose_size(sizes: &[(u32, u32)]) -> (u32, u32) {
+    if sizes.is_empty() || sizes.contains(&PREFERRED_SIZE) {
+        return PREFERRED_SIZE;
+    }
+    sizes
+        .iter()
+        .filter(|(width, _)| *width >= PREFERRED_SIZE.0)
+        .min_by_key(|(width, height)| width * height)
+        .or_else(|| sizes.iter().max_by_key(|(width, height)| width * height))
+        .copied()
+        .unwrap_or(PREFERRED_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{choose_size, fourcc, fourcc_to_string, V4L2_PIX_FMT_MJPEG};
+
+    #[test]
+    fn fourcc_round_trips() {
+        assert_eq!(V4L2_PIX_FMT_MJPEG, 0x4750_4A4D);
+        assert_eq!(fourcc_to_string(fourcc(b"YUYV")), "YUYV");
+    }
+
+    #[test]
+    fn prefers_vga_then_smallest_larger_size() {
+        assert_eq!(choose_size(&[(1280, 720), (640, 480)]), (640, 480));
+        assert_eq!(choose_size(&[(1920, 1080), (1280, 720)]), (1280, 720));
+        assert_eq!(choose_size(&[(320, 240), (160, 120)]), (320, 240));
+        assert_eq!(choose_size(&[]), (640, 480));
+    }
+}
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use crate::error::{CoreError, CoreResult};
+use crate::platform::ffmpeg_capabilities::FfmpegCapabilities;
+
+pub struct FfmpegLocator {
+    fallback_paths: Vec<PathBuf>,
+    capabilities: Mutex<Option<Arc<FfmpegCapabilities>>>,
+}
+
+impl Default for FfmpegLocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FfmpegLocator {
+    pub fn new() -> Self {
+        let mut fallback_paths: Vec<PathBuf> = Vec::new();
+        if let Ok(custom_path) = std::env::var("FFMPEG_PATH") {
+            fallback_paths.push(PathBuf::from(custom_path));
+        }
+
+        fallback_paths.extend([
+            PathBuf::from("/opt/homebrew/bin/ffmpeg"),
+            PathBuf::from("/usr/local/bin/ffmpeg"),
+            PathBuf::from("/usr/bin/ffmpeg"),
+            PathBuf::from("ffmpeg"),
+        ]);
+
+        Self {
+            fallback_paths,
+            capabilities: Mutex::new(None),
+        }
+    }
+
+    pub fn resolve(&self) -> CoreResult<PathBuf> {
+        for path in &self.fallback_paths {
+            if is_executable(path) {
+                println!("[FFmpeg] Found FFmpeg at: {}", path.display());
+                return Ok(path.clone());
+            }
+        }
+
+        Err(CoreError::Recording(
+            "FFmpeg not found. Install via Homebrew or set FFMPEG_PATH.".to_string(),
+        ))
+    }
+
+    /// What the resolved FFmpeg supports, probed on first use and again
+    /// whenever a different FFmpeg resolves. A failed probe is not cached, so
+    /// installing FFmpeg later works.
+    pub fn capabilities(&self) -> CoreResult<Arc<FfmpegCapabilities>> {
+        let ffmpeg_path = self.resolve()?;
+        let mut cached = self.capabilities.lock().unwrap();
+        if let Some(capabilities) = cached
+            .as_ref()
+            .filter(|capabilities| capabilities.ffmpeg_path == ffmpeg_path)
+        {
+            return Ok(capabilities.clone());
+        }
+        let capabilities = Arc::new(FfmpegCapabilities::probe(&ffmpeg_path)?);
+        *cached = Some(capabilities.clone());
+        Ok(capabilities)
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    Command::new(path).arg("-version").output().is_ok()
+}
@@ -0,0 +1,245 @@
+//! Minimal streaming Matroska writer for raw BGRA frames. It exists only so
+//! the encoder pipe can carry a timestamp with every frame; FFmpeg reads it as
+//! `-f matroska` and sees a `V_UNCOMPRESSED` track tagged `BGRA`. The same
+//! writer stores camera JPEGs as a `V_MJPEG` track.
+
+use std::io::{self, Write};
+
+const EBML_HEADER: u32 = 0x1A45_DFA3;
+const EBML_VERSION: u32 = 0x4286;
+const EBML_READ_VERSION: u32 = 0x42F7;
+const EBML_MAX_ID_LENGTH: u32 = 0x42F2;
+const EBML_MAX_SIZE_LENGTH: u32 = 0x42F3;
+const DOC_TYPE: u32 = 0x4282;
+const DOC_TYPE_VERSION: u32 = 0x4287;
+const DOC_TYPE_READ_VERSION: u32 = 0x4285;
+
+const SEGMENT: u32 = 0x1853_8067;
+const INFO: u32 = 0x1549_A966;
+const TIMESTAMP_SCALE: u32 = 0x2A_D7B1;
+const MUXING_APP: u32 = 0x4D80;
+const WRITING_APP: u32 = 0x5741;
+const TRACKS: u32 = 0x1654_AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const TRACK_UID: u32 = 0x73C5;
+const TRACK_TYPE: u32 = 0x83;
+const FLAG_LACING: u32 = 0x9C;
+const CODEC_ID: u32 = 0x86;
+const VIDEO: u32 = 0xE0;
+const PIXEL_WIDTH: u32 = 0xB0;
+const PIXEL_HEIGHT: u32 = 0xBA;
+const COLOUR_SPACE: u32 = 0x2E_B524;
+const CLUSTER: u32 = 0x1F43_B675;
+const CLUSTER_TIMESTAMP: u32 = 0xE7;
+const SIMPLE_BLOCK: u32 = 0xA3;
+
+/// Reserved "unknown size" marker: the segment ends when the pipe closes.
+const UNKNOWN_SIZE: [u8; 8] = [0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+/// FFmpeg decoders for the tracks [`RawVideoMkvWriter::new`] and
+/// [`RawVideoMkvWriter::mjpeg`] write.
+pub const BGRA_DECODER: &str = "rawvideo";
+pub const MJPEG_DECODER: &str = "mjpeg";
+/// One timestamp tick is a millisecond.
+pub const TIMESTAMP_SCALE_NS: u64 = 1_000_000;
+const VIDEO_TRACK_NUMBER: u8 = 1;
+
+pub struct RawVideoMkvWriter<W: Write> {
+    out: W,
+    /// Required size of every frame; `None` for compressed frames.
+    frame_bytes: Option<usize>,
+}
+
+impl<W: Write> RawVideoMkvWriter<W> {
+    /// Writes the EBML header, segment info and the single BGRA track.
+    pub fn new(out: W, width: u32, height: u32) -> io::Result<Self> {
+        let mut writer = Self::with_track(out, width, height, b"V_UNCOMPRESSED", Some(b"BGRA"))?;
+        writer.frame_bytes = Some(width as usize * height as usize * 4);
+        Ok(writer)
+    }
+
+    /// Same as [`Self::new`] for a track of whole JPEG images.
+    pub fn mjpeg(out: W, width: u32, height: u32) -> io::Result<Self> {
+        Self::with_track(out, width, height, b"V_MJPEG", None)
+    }
+
+    fn with_track(
+        mut out: W,
+        width: u32,
+        height: u32,
+        codec_id: &[u8],
+        colour_space: Option<&[u8]>,
+    ) -> io::Result<Self> {
+        let mut header = Vec::new();
+        element(
+            &mut header,
+            EBML_HEADER,
+            &[
+                uint_element(EBML_VERSION, 1),
+                uint_element(EBML_READ_VERSION, 1),
+                uint_element(EBML_MAX_ID_LENGTH, 4),
+                uint_element(EBML_MAX_SIZE_LENGTH, 8),
+                bytes_element(DOC_TYPE, b"matroska"),
+                uint_element(DOC_TYPE_VERSION, 4),
+                uint_element(DOC_TYPE_READ_VERSION, 2),
+            ]
+            .concat(),
+        );
+        write_id(&mut header, SEGMENT);
+        header.extend_from_slice(&UNKNOWN_SIZE);
+        element(
+            &mut header,
+            INFO,
+            &[
+                uint_element(TIMESTAMP_SCALE, TIMESTAMP_SCALE_NS),
+                bytes_element(MUXING_APP, b"momentum"),
+                bytes_element(WRITING_APP, b"momentum"),
+            ]
+            .concat(),
+        );
+        let mut video = [
+            uint_element(PIXEL_WIDTH, u64::from(width)),
+            uint_element(PIXEL_HEIGHT, u64::from(height)),
+        ]
+        .concat();
+        if let Some(colour_space) = colour_space {
+            video.extend_from_slice(&bytes_element(COLOUR_SPACE, colour_space));
+        }
+        let track = [
+            uint_element(TRACK_NUMBER, u64::from(VIDEO_TRACK_NUMBER)),
+            uint_element(TRACK_UID, 1),
+            uint_element(TRACK_TYPE, 1),
+            uint_element(FLAG_LACING, 0),
+            bytes_element(CODEC_ID, codec_id),
+            bytes_element(VIDEO, &video),
+        ]
+        .concat();
+        element(&mut header, TRACKS, &bytes_element(TRACK_ENTRY, &track));
+        out.write_all(&header)?;
+
+        Ok(Self {
+            out,
+            frame_bytes: None,
+        })
+    }
+
+    /// Writes one frame in its own cluster. Timestamps are in milliseconds
+    /// and must increase; the caller is responsible for that.
+    pub fn write_frame(&mut self, timestamp_ms: u64, pixels: &[u8]) -> io::Result<()> {
+        if let Some(frame_bytes) = self.frame_bytes.filter(|&bytes| bytes != pixels.len()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("frame is {} bytes, expected {}", pixels.len(), frame_bytes),
+            ));
+        }
+
+        // SimpleBlock body: track number, 16-bit cluster-relative time (always
+        // 0 here), keyframe flag, then the pixels.
+        let block_header = [0x80 | VIDEO_TRACK_NUMBER, 0x00, 0x00, 0x80];
+        let block_size = (block_header.len() + pixels.len()) as u64;
+        let timestamp = uint_element(CLUSTER_TIMESTAMP, timestamp_ms);
+
+        let mut simple_block_head = Vec::with_capacity(12);
+        write_id(&mut simple_block_head, SIMPLE_BLOCK);
+        write_size(&mut simple_block_head, block_size);
+        let cluster_size =
+            (timestamp.len() + simple_block_head.len() + block_header.len() + pixels.len()) as u64;
+
+        let mut head = Vec::with_capacity(32);
+        write_id(&mut head, CLUSTER);
+        write_size(&mut head, cluster_size);
+        head.extend_from_slice(&timestamp);
+        head.extend_from_slice(&simple_block_head);
+        head.extend_from_slice(&block_header);
+
+        self.out.write_all(&head)?;
+        self.out.write_all(pixels)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn element(out: &mut Vec<u8>, id: u32, body: &[u8]) {
+    write_id(out, id);
+    write_size(out, body.len() as u64);
+    out.extend_from_slice(body);
+}
+
+fn bytes_element(id: u32, body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    element(&mut out, id, body);
+    out
+}
+
+fn uint_element(id: u32, value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let first = bytes
+        .iter()
+        .position(|b| *b != 0)
+        .unwrap_or(bytes.len() - 1);
+    bytes_element(id, &bytes[first..])
+}
+
+/// Element IDs already carry their length marker, so they are written as-is.
+fn write_id(out: &mut Vec<u8>, id: u32) {
+    let bytes = id.to_be_bytes();
+    let first = bytes.iter().position(|b| *b != 0).unwrap_or(3);
+    out.extend_from_slice(&bytes[first..]);
+}
+
+/// EBML variable-length size in the fewest bytes. All-ones values are
+/// reserved, hence the `- 1`.
+fn write_size(out: &mut Vec<u8>, size: u64) {
+    let mut length = 1;
+    while length < 8 && size >= (1u64 << (7 * length)) - 1 {
+        length += 1;
+    }
+    let marked = size | (1u64 << (7 * length));
+    out.extend_from_slice(&marked.to_be_bytes()[8 - length..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_size, RawVideoMkvWriter};
+
+    fn size_bytes(size: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_size(&mut out, size);
+        out
+    }
+
+    #[test]
+    fn encodes_sizes_as_shortest_vints() {
+        assert_eq!(size_bytes(0), vec![0x80]);
+        assert_eq!(size_bytes(126), vec![0xFE]);
+        assert_eq!(size_bytes(127), vec![0x40, 0x7F]);
+        assert_eq!(size_bytes(8_294_404), vec![0x10, 0x7E, 0x90, 0x04]);
+    }
+
+    #[test]
+    fn writes_header_and_one_cluster_per_frame() {
+        let mut out = Vec::new();
+        {
+            let mut writer = RawVideoMkvWriter::new(&mut out, 2, 1).expect("header");
+            writer
+                .write_frame(1_500, &[1, 2, 3, 4, 5, 6, 7, 8])
+                .expect("frame");
+            assert!(writer.write_frame(1_533, &[0; 4]).is_err());
+        }
+
+        assert_eq!(&out[..4], &[0x1A, 0x45, 0xDF, 0xA3]);
+        let find = |needle: &[u8]| out.windows(needle.len()).position(|w| w == needle);
+        assert!(find(b"V_UNCOMPRESSED").is_some());
+        assert!(find(b"BGRA").is_some());
+
+        // Cluster(size 18) { Timestamp 1500 ms, SimpleBlock(size 12) { track 1, +0, key, pixels } }
+        let cluster = [
+            0x1F, 0x43, 0xB6, 0x75, 0x92, 0xE7, 0x82, 0x05, 0xDC, 0xA3, 0x8C, 0x81, 0x00, 0x00,
+            0x80, 1, 2, 3, 4, 5, 6, 7, 8,
+        ];
+        let start = find(&cluster[..4]).expect("cluster");
+        assert_eq!(&out[start..], &cluster);
+    }
+}
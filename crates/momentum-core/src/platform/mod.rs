@@ -0,0 +1,42 @@
+pub mod audio_alignment;
+pub mod audio_mixer;
+pub mod capture_backend;
+pub(crate) mod camera_track;
+#[cfg(target_os = "macos")]
+pub mod device_resolver;
+pub mod drift_resampler;
+pub mod ffmpeg_capabilities;
+pub mod ffmpeg_pipeline;
+pub(crate) mod ffmpeg_process;
+#[cfg(target_os = "linux")]
+pub mod linux;
+pub mod macos;
+pub(crate) mod matroska;
+pub(crate) mod mux;
+pub mod pcm_capture;
+#[cfg(target_os = "macos")]
+pub mod screencapturekit_recorder;
+pub mod selectable_backend;
+pub mod synthetic_recorder;
+pub(crate) mod video_timeline;
+#[cfg(target_os = "linux")]
+pub mod x11_recorder;
+
+pub use capture_backend::{AudioDeviceIds, CaptureBackend, CaptureRequest};
+
+#[cfg(target_os = "macos")]
+pub type PlatformCaptureBackend = screencapturekit_recorder::ScreenCaptureKitRecorder;
+
+#[cfg(target_os = "linux")]
+pub type PlatformCaptureBackend = x11_recorder::X11Recorder;
+
+/// Displays and windows the platform backend can record, with thumbnails.
+#[cfg(target_os = "macos")]
+pub use screencapturekit_recorder::list_capture_targets;
+
+#[cfg(target_os = "linux")]
+pub use x11_recorder::list_capture_targets;
+
+/// The backend the app runs with: the platform recorder, or synthetic sources
+/// when requested.
+pub type DefaultCaptureBackend = selectable_backend::SelectableCaptureBackend<PlatformCaptureBackend>;
@@ -0,0 +1,342 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::encoder::EncoderProfile;
+use crate::error::{CoreError, CoreResult};
+use crate::models::{CameraOutput, CameraShape, RecordingOptions, CAMERA_ROUNDED_RADIUS};
+use crate::output::camera_sidecar_path;
+use crate::platform::audio_mixer::{MIX_CHANNELS, MIX_SAMPLE_RATE};
+use crate::platform::camera_track::CameraTrack;
+use crate::platform::ffmpeg_capabilities::FfmpegRequirements;
+use crate::platform::ffmpeg_process;
+use crate::platform::matroska;
+use crate::time::NANOS_PER_SECOND;
+
+const MIX_LIMITER: &str = "alimiter=limit=0.97";
+const AUDIO_ENCODER: &str = "aac";
+/// Decoder for the raw f32le mix.
+const MIX_DECODER: &str = "pcm_f32le";
+
+/// Encodes the live mix (raw f32le from [`AudioMixer`]) next to the video.
+/// Alignment, gain and length were settled while mixing, so the only audio
+/// filter left is the limiter. `metadata` is written as container tags.
+///
+/// A `camera` track goes where its [`CameraOutput`] says: composited over
+/// the screen, which re-encodes the video; added as a second video stream;
+/// or encoded to a sidecar next to `output_path`. The screen video is copied
+/// unless it is composited; anything re-encoded uses `encoder` at `fps`, like
+/// the live pass.
+///
+/// [`AudioMixer`]: crate::platform::audio_mixer::AudioMixer
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn mux_final_video(
+    video_path: &Path,
+    mixed_audio_path: Option<&Path>,
+    camera: Option<&CameraTrack>,
+    output_path: &Path,
+    ffmpeg_path: &Path,
+    encoder: &EncoderProfile,
+    fps: u32,
+    metadata: &[(String, String)],
+) -> CoreResult<()> {
+    let mut cmd = Command::new(ffmpeg_path);
+    cmd.args(["-y", "-hide_banner", "-loglevel", "warning"]);
+    ffmpeg_process::isolate_from_terminal(&mut cmd);
+
+    // Input 0: Video (mp4)
+    cmd.args(["-i", video_path.to_str().unwrap()]);
+    let mut next_input = 1;
+
+    // Input 1: Camera (MJPEG Matroska), unless it goes to a sidecar. A
+    // composited camera is only needed if it was ever shown.
+    let (camera, sidecar) = match camera {
+        Some(track) if track.overlay.output == CameraOutput::Sidecar => (None, Some(track)),
+        Some(track)
+            if track.overlay.output == CameraOutput::Composite
+                && track.visible_spans.is_empty() =>
+        {
+            (None, None)
+        }
+        camera => (camera, None),
+    };
+    if let Some(track) = camera {
+        add_camera_input(&mut cmd, track);
+        next_input += 1;
+    }
+
+    // Next input: Mixed audio (raw f32le)
+    let mixed_audio_path = mixed_audio_path.filter(|path| {
+        std::fs::metadata(path)
+            .map(|m| m.len() > 1000) // More than a few frames
+            .unwrap_or(false)
+    });
+    if let Some(mixed_audio_path) = mixed_audio_path {
+        cmd.args(mix_input_args(mixed_audio_path));
+    }
+
+    match camera {
+        Some(track) if track.overlay.output == CameraOutput::Track => {
+            cmd.args(["-map", "0:v", "-map", "1:v", "-c:v:0", "copy"]);
+            cmd.args(copy_tag_args(encoder, "v:0"));
+            cmd.args(["-fps_mode", "passthrough"]);
+            cmd.args(encoder.video_args("v:1", fps));
+            // Players should open on the screen, not the camera
+            cmd.args(["-metadata:s:v:1", "title=Camera", "-disposition:v:1", "0"]);
+        }
+        Some(track) => {
+            let filter = camera_overlay_filter(track, 1, encoder.pixel_format());
+            cmd.args(["-filter_complex", &filter]);
+            cmd.args(["-map", "[v]", "-fps_mode", "passthrough"]);
+            cmd.args(encoder.video_args("v", fps));
+        }
+        None => {
+            cmd.args(["-map", "0:v", "-c:v", "copy"]);
+            cmd.args(copy_tag_args(encoder, "v"));
+        }
+    }
+    if mixed_audio_path.is_some() {
+        cmd.args(["-map", &format!("{}:a", next_input)]);
+        cmd.args(["-af", MIX_LIMITER]);
+        cmd.args(["-c:a", AUDIO_ENCODER, "-b:a", "128k"]);
+        // A camera stream may end before the screen; the mix already
+        // matches the screen's length
+        if camera.is_none_or(|track| track.overlay.output != CameraOutput::Track) {
+            cmd.arg("-shortest");
+        }
+    }
+    for (key, value) in metadata {
+        cmd.args(["-metadata", &format!("{}={}", key, value)]);
+    }
+    // Keep custom keys in the MP4 instead of dropping them
+    cmd.args(["-movflags", "+faststart+use_metadata_tags"]);
+    cmd.arg(output_path.to_str().unwrap());
+
+    println!(
+        "[Mux] Muxing: video{}{}",
+        match camera.map(|track| track.overlay.output) {
+            Some(CameraOutput::Track) => " + camera track",
+            Some(_) => " + camera",
+            None => "",
+        },
+        match mixed_audio_path {
+            Some(path) => format!(" + mixed audio ({:?})", path),
+            None => " (no audio)".to_string(),
+        }
+    );
+    run(cmd)?;
+
+    if let Some(track) = sidecar {
+        // The recording itself is fine without it
+        if let Err(err) = write_camera_sidecar(track, output_path, ffmpeg_path, encoder, fps) {
+            eprintln!("[Mux] ⚠ Failed to write camera sidecar: {}", err);
+        }
+    }
+    Ok(())
+}
+
+/// What [`mux_final_video`] runs for a recording with `options`, camera
+/// sidecar included.
+pub(crate) fn add_mux_requirements(
+    requirements: &mut FfmpegRequirements,
+    options: &RecordingOptions,
+) {
+    requirements.add_input(&mix_input_args(Path::new("mix.f32le")));
+    requirements.add_decoder(MIX_DECODER);
+    requirements.add_graph(MIX_LIMITER);
+    requirements.add_encoder(AUDIO_ENCODER);
+    if !options.include_camera {
+        return;
+    }
+
+    let track = CameraTrack {
+        path: "camera.mkv".into(),
+        overlay: options.camera_overlay,
+        screen_width: 1920,
+        screen_height: 1080,
+        start_ns: 0,
+        frames: 1,
+        visible_spans: vec![(0, NANOS_PER_SECOND)],
+    };
+    requirements.add_input(&camera_input_args(&track));
+    requirements.add_decoder(matroska::MJPEG_DECODER);
+    requirements.add_encoder(options.encoder.codec.encoder_name());
+    match options.camera_overlay.output {
+        CameraOutput::Composite => {
+            let pixel_format = options.encoder.pixel_format();
+            requirements.add_graph(&camera_overlay_filter(&track, 1, pixel_format));
+        }
+        // Encoded without a graph; FFmpeg converts the JPEG pixel format
+        // with an automatically inserted scale
+        CameraOutput::Track | CameraOutput::Sidecar => requirements.add_filter("scale"),
+    }
+}
+
+/// Input options for the raw f32le mix at `path`.
+fn mix_input_args(path: &Path) -> Vec<String> {
+    vec![
+        "-f".to_string(),
+        "f32le".to_string(),
+        "-ar".to_string(),
+        MIX_SAMPLE_RATE.to_string(),
+        "-ac".to_string(),
+        MIX_CHANNELS.to_string(),
+        "-i".to_string(),
+        path.to_str().unwrap().to_string(),
+    ]
+}
+
+/// Stream copies get FFmpeg's default MP4 tag, not the one the encoder pass
+/// asked for.
+fn copy_tag_args(encoder: &EncoderProfile, stream: &str) -> Vec<String> {
+    encoder
+        .codec
+        .mp4_tag()
+        .map(|tag| vec![format!("-tag:{}", stream), tag.to_string()])
+        .unwrap_or_default()
+}
+
+fn add_camera_input(cmd: &mut Command, track: &CameraTrack) {
+    cmd.args(camera_input_args(track));
+}
+
+/// FFmpeg moves every input to start at 0, so the first camera frame's
+/// position on the timeline is put back with -itsoffset.
+fn camera_input_args(track: &CameraTrack) -> Vec<String> {
+    vec![
+        "-itsoffset".to_string(),
+        seconds(track.start_ns),
+        "-f".to_string(),
+        "matroska".to_string(),
+        "-i".to_string(),
+        track.path.to_str().unwrap().to_string(),
+    ]
+}
+
+/// Encodes the camera alone to [`camera_sidecar_path`], on the same timeline
+/// as the screen video.
+fn write_camera_sidecar(
+    track: &CameraTrack,
+    output_path: &Path,
+    ffmpeg_path: &Path,
+    encoder: &EncoderProfile,
+    fps: u32,
+) -> CoreResult<()> {
+    let sidecar_path = camera_sidecar_path(output_path);
+    let mut cmd = Command::new(ffmpeg_path);
+    cmd.args(["-y", "-hide_banner", "-loglevel", "warning"]);
+    ffmpeg_process::isolate_from_terminal(&mut cmd);
+    add_camera_input(&mut cmd, track);
+    cmd.args(["-map", "0:v", "-fps_mode", "passthrough"]);
+    cmd.args(encoder.video_args("v", fps));
+    cmd.args(["-movflags", "+faststart"]);
+    cmd.arg(sidecar_path.to_str().unwrap());
+
+    println!("[Mux] Writing camera sidecar: {:?}", sidecar_path);
+    run(cmd)
+}
+
+/// Crops the camera at `camera_input` to a square, cuts it to the overlay
+/// shape and lays it over input 0 while the overlay was visible. The result
+/// is labelled `[v]`, in `pixel_format`.
+fn camera_overlay_filter(track: &CameraTrack, camera_input: usize, pixel_format: &str) -> String {
+    let overlay = &track.overlay;
+    let side = overlay.side_px(track.screen_width);
+    let (x, y) = overlay.origin_px(track.screen_width, track.screen_height);
+
+    let mask = match overlay.shape {
+        CameraShape::Rectangle => String::new(),
+        CameraShape::Rounded => {
+            let radius = (f64::from(side) * CAMERA_ROUNDED_RADIUS).round() as u32;
+            alpha_mask(&format!(
+                "if(gt(abs(X-W/2),W/2-{r})*gt(abs(Y-H/2),H/2-{r}),\
+                 if(lte(hypot(abs(X-W/2)-(W/2-{r}),abs(Y-H/2)-(H/2-{r})),{r}),255,0),255)",
+                r = radius
+            ))
+        }
+        CameraShape::Circle => alpha_mask("if(lte(hypot(X-W/2,Y-H/2),W/2),255,0)"),
+    };
+    let enable = track
+        .visible_spans
+        .iter()
+        .map(|&(start, end)| format!("between(t,{},{})", seconds(start), seconds(end)))
+        .collect::<Vec<_>>()
+        .join("+");
+
+    format!(
+        "[{input}:v]crop='min(iw,ih)':'min(iw,ih)',scale={side}:{side}{mask}[cam];\
+         [0:v][cam]overlay=x={x}:y={y}:enable='{enable}',format={pixel_format}[v]",
+        input = camera_input,
+        side = side,
+        mask = mask,
+        x = x,
+        y = y,
+        enable = enable,
+        pixel_format = pixel_format
+    )
+}
+
+fn alpha_mask(alpha: &str) -> String {
+    format!(
+        ",format=rgba,geq=r='r(X,Y)':g='g(X,Y)':b='b(X,Y)':a='{}'",
+        alpha
+    )
+}
+
+fn seconds(ns: u64) -> String {
+    format!("{:.3}", ns as f64 / NANOS_PER_SECOND as f64)
+}
+
+fn run(mut cmd: Command) -> CoreResult<()> {
+    let status = cmd
+        .status()
+        .map_err(|e| CoreError::Recording(format!("Mux failed: {}", e)))?;
+
+    if !status.success() {
+        return Err(CoreError::Recording("Mux process failed".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::camera_overlay_filter;
+    use crate::models::{CameraCorner, CameraOverlay, CameraShape};
+    use crate::platform::camera_track::CameraTrack;
+    use std::path::PathBuf;
+
+    #[test]
+    fn composites_the_camera_only_while_visible() {
+        let mut track = CameraTrack {
+            path: PathBuf::from("camera.mkv"),
+            overlay: CameraOverlay {
+                corner: CameraCorner::TopLeft,
+                size: 0.2,
+                shape: CameraShape::Rectangle,
+                ..CameraOverlay::default()
+            },
+            screen_width: 1920,
+            screen_height: 1080,
+            start_ns: 120_000_000,
+            frames: 10,
+            visible_spans: vec![(0, 2_500_000_000), (4_000_000_000, 6_250_000_000)],
+        };
+        // 384 px square, 29 px (1.5%) in from the top-left corner.
+        assert_eq!(
+            camera_overlay_filter(&track, 1, "yuv420p"),
+            "[1:v]crop='min(iw,ih)':'min(iw,ih)',scale=384:384[cam];\
+             [0:v][cam]overlay=x=29:y=29:enable='between(t,0.000,2.500)+between(t,4.000,6.250)',format=yuv420p[v]"
+        );
+
+        track.overlay.corner = CameraCorner::BottomRight;
+        track.overlay.shape = CameraShape::Circle;
+        // Lossless profiles keep the composite in 4:4:4.
+        let filter = camera_overlay_filter(&track, 1, "yuv444p");
+        assert!(filter.ends_with(",format=yuv444p[v]"), "{}", filter);
+        assert!(filter.contains("overlay=x=1507:y=667:"), "{}", filter);
+        assert!(
+            filter.contains("a='if(lte(hypot(X-W/2,Y-H/2),W/2),255,0)'"),
+            "{}",
+            filter
+        );
+    }
+}
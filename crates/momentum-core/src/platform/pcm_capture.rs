@@ -0,0 +1,204 @@
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::error::{CoreError, CoreResult};
+use crate::models::RecordingOptions;
+use crate::platform::audio_mixer::{s16le_to_f32, MixerInput};
+use crate::platform::drift_resampler::DriftResampler;
+use crate::platform::ffmpeg_capabilities::FfmpegRequirements;
+use crate::platform::ffmpeg_process;
+use crate::time::{duration_to_ns, samples_to_ns, ArrivalMarker, SharedClock};
+
+pub const PCM_SAMPLE_RATE: u32 = 48_000;
+pub const PCM_CHANNEL_COUNT: u32 = 2;
+
+// 10 ms of 48 kHz stereo s16le; keeps every read aligned to whole frames so a
+// skipped (paused) chunk never shifts the channel order.
+const CHUNK_FRAMES: usize = 480;
+
+/// An FFmpeg audio input that is captured as 48 kHz stereo s16le.
+pub struct AudioSource {
+    /// Input arguments, e.g. `["-f", "pulse", "-i", "default"]`.
+    pub input_args: Vec<String>,
+    /// Optional `-af` chain applied after the input is read.
+    pub output_filter: Option<String>,
+    /// Simulated device start-up latency: chunks produced before it elapses
+    /// are discarded, so the first-arrival marker lands after it.
+    pub start_latency: Duration,
+    /// Key into [`DeviceOffsets`](crate::models::DeviceOffsets).
+    pub device_id: String,
+    /// Fixed device latency the mixer compensates, see
+    /// [`AudioMixer::add_input`](crate::platform::audio_mixer::AudioMixer::add_input).
+    pub latency_ns: i64,
+}
+
+impl AudioSource {
+    pub fn new(device_id: impl Into<String>, input_args: Vec<String>) -> Self {
+        Self {
+            input_args,
+            output_filter: None,
+            start_latency: Duration::ZERO,
+            device_id: device_id.into(),
+            latency_ns: 0,
+        }
+    }
+
+    /// Takes the latency configured for this device in `options`.
+    pub fn with_offsets_from(mut self, options: &RecordingOptions) -> Self {
+        self.latency_ns = options.audio_latency_ns(&self.device_id);
+        self
+    }
+
+    /// What capturing this source through [`spawn_pcm_capture`] runs.
+    pub fn add_requirements(&self, requirements: &mut FfmpegRequirements) {
+        requirements.add_input(&self.input_args);
+        if let Some(filter) = &self.output_filter {
+            requirements.add_graph(filter);
+        }
+    }
+}
+
+pub struct PcmCapture {
+    pub process: Child,
+    pub reader: Option<JoinHandle<()>>,
+}
+
+impl PcmCapture {
+    /// Asks FFmpeg to finish, then waits for the last chunk to reach the mixer.
+    pub fn stop(mut self, label: &str) {
+        ffmpeg_process::interrupt_and_wait(&mut self.process, Duration::from_secs(3), label);
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+pub struct PcmCaptureSpec {
+    pub mixer_input: MixerInput,
+    pub log_label: &'static str,
+    pub muted: Arc<AtomicBool>,
+    pub recording_paused: Arc<AtomicBool>,
+    pub samples_written: Arc<AtomicU64>,
+    pub first_arrival: ArrivalMarker,
+    /// Resample against the host clock; for devices with their own sample
+    /// clock, like the mic.
+    pub correct_drift: bool,
+    pub clock: SharedClock,
+    /// Host time the session started at; `start_latency` counts from here.
+    pub capture_started_ns: u64,
+}
+
+/// Captures `source` through FFmpeg and feeds it to the mixer in timestamped
+/// chunks, honouring the pause and mute flags. Shared by every backend that
+/// records a device through FFmpeg, including the macOS mic.
+pub fn spawn_pcm_capture(
+    ffmpeg_path: &Path,
+    source: &AudioSource,
+    spec: PcmCaptureSpec,
+) -> CoreResult<PcmCapture> {
+    let mut cmd = Command::new(ffmpeg_path);
+    cmd.args(["-y", "-hide_banner", "-loglevel", "warning"]);
+    cmd.args(&source.input_args);
+    if let Some(filter) = &source.output_filter {
+        cmd.args(["-af", filter]);
+    }
+    cmd.args([
+        "-ac",
+        &PCM_CHANNEL_COUNT.to_string(),
+        "-ar",
+        &PCM_SAMPLE_RATE.to_string(),
+        "-f",
+        "s16le",
+        "-",
+    ]);
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    ffmpeg_process::isolate_from_terminal(&mut cmd);
+
+    let mut process = cmd.spawn().map_err(|e| {
+        CoreError::Recording(format!("Failed to start {}: {}", spec.log_label, e))
+    })?;
+    println!(
+        "[Audio] {} started with {:?} (PID: {})",
+        spec.log_label,
+        source.input_args,
+        process.id()
+    );
+    ffmpeg_process::forward_stderr(&mut process, spec.log_label);
+
+    let stdout = process.stdout.take().ok_or_else(|| {
+        CoreError::Recording(format!("Failed to capture {} stdout", spec.log_label))
+    })?;
+
+    let PcmCaptureSpec {
+        mixer_input,
+        log_label,
+        muted,
+        recording_paused,
+        samples_written,
+        first_arrival,
+        correct_drift,
+        clock,
+        capture_started_ns,
+    } = spec;
+    let start_latency_ns = duration_to_ns(source.start_latency);
+    let chunk_ns = samples_to_ns(CHUNK_FRAMES as u64, PCM_SAMPLE_RATE);
+    let reader = thread::spawn(move || {
+        let mut reader = BufReader::new(stdout);
+        let bytes_per_frame = 2 * PCM_CHANNEL_COUNT as usize;
+        let mut buffer = vec![0u8; CHUNK_FRAMES * bytes_per_frame];
+        let mut resampler =
+            correct_drift.then(|| DriftResampler::new(PCM_SAMPLE_RATE, PCM_CHANNEL_COUNT));
+        loop {
+            if let Err(err) = reader.read_exact(&mut buffer) {
+                if err.kind() != std::io::ErrorKind::UnexpectedEof {
+                    eprintln!("[Audio] {} reader error: {}", log_label, err);
+                }
+                break;
+            }
+            let now_ns = clock.now_ns();
+            if now_ns.saturating_sub(capture_started_ns) < start_latency_ns {
+                continue;
+            }
+            first_arrival.mark(now_ns);
+            if recording_paused.load(Ordering::Relaxed) {
+                if let Some(resampler) = resampler.as_mut() {
+                    resampler.reset();
+                }
+                continue;
+            }
+            if muted.load(Ordering::Relaxed) {
+                buffer.fill(0);
+            }
+            // The chunk was complete when read, so it started one chunk ago.
+            let pts_ns = now_ns.saturating_sub(chunk_ns);
+            let samples = s16le_to_f32(&buffer);
+            match resampler.as_mut() {
+                Some(resampler) => {
+                    let corrected = resampler.process(pts_ns, &samples);
+                    mixer_input.push_f32(pts_ns, &corrected, PCM_CHANNEL_COUNT);
+                }
+                None => mixer_input.push_f32(pts_ns, &samples, PCM_CHANNEL_COUNT),
+            }
+            samples_written.fetch_add(CHUNK_FRAMES as u64, Ordering::Relaxed);
+        }
+        if let Some(resampler) = resampler {
+            println!(
+                "[Audio] {} drift correction: {:.0} ppm",
+                log_label,
+                resampler.correction_ppm()
+            );
+        }
+    });
+
+    Ok(PcmCapture {
+        process,
+        reader: Some(reader),
+    })
+}
@@ -1,10 +1,12 @@
-use std::io::Write;
+use std::borrow::Cow;
 use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
 
-use crate::services::camera::CameraSyncHandle;
-use crate::services::time::cm_time_to_ns;
+use crate::camera::CameraSyncHandle;
+use crate::platform::audio_mixer::MixerInput;
+use crate::platform::camera_track::{self, CameraTrackWriter};
+use crate::platform::video_timeline::TimelineVideoWriter;
+use crate::time::{cm_time_seconds, cm_time_to_ns, ArrivalMarker};
 use screencapturekit::output::{CVImageBufferLockExt, PixelBufferLockFlags};
 use screencapturekit::prelude::*;
 
@@ -12,8 +14,11 @@ static SCREEN_PTS_COUNTER: AtomicU64 = AtomicU64::new(0);
 
 // Handler for ScreenCaptureKit callbacks
 pub(super) struct FrameHandler {
-    pub(super) video_writer: Arc<Mutex<Option<std::process::ChildStdin>>>,
-    pub(super) audio_writer: Arc<Mutex<Option<std::fs::File>>>,
+    pub(super) video_writer: Arc<Mutex<Option<TimelineVideoWriter<std::process::ChildStdin>>>>,
+    pub(super) camera_track: Arc<Mutex<Option<CameraTrackWriter>>>,
+    pub(super) frame_width: u32,
+    pub(super) frame_height: u32,
+    pub(super) system_audio: Option<MixerInput>,
     pub(super) video_frame_count: Arc<std::sync::atomic::AtomicU64>,
     pub(super) audio_frame_count: Arc<std::sync::atomic::AtomicU64>,
     pub(super) audio_sample_rate: Arc<AtomicU32>,
@@ -22,9 +27,8 @@ pub(super) struct FrameHandler {
     pub(super) audio_samples_written: Arc<AtomicU64>,
     pub(super) system_audio_muted: Arc<AtomicBool>,
     pub(super) recording_paused: Arc<AtomicBool>,
-    pub(super) capture_started_at: Instant,
-    pub(super) first_screen_frame_arrival_ns: Arc<AtomicU64>,
-    pub(super) first_system_audio_arrival_ns: Arc<AtomicU64>,
+    pub(super) first_screen_frame_arrival: ArrivalMarker,
+    pub(super) first_system_audio_arrival: ArrivalMarker,
     pub(super) camera_sync: Option<Arc<CameraSyncHandle>>,
 }
 
@@ -32,14 +36,10 @@ impl SCStreamOutputTrait for FrameHandler {
     fn did_output_sample_buffer(&self, sample: CMSampleBuffer, of_type: SCStreamOutputType) {
         match of_type {
             SCStreamOutputType::Screen => {
-                let now_ns = self.capture_started_at.elapsed().as_nanos() as u64;
-                let _ = self.first_screen_frame_arrival_ns.compare_exchange(
-                    0,
-                    now_ns,
-                    Ordering::Relaxed,
-                    Ordering::Relaxed,
-                );
+                // Presentation timestamps are host time, the same clock the
+                // mic reader and pause toggles stamp against.
                 let screen_pts_ns = cm_time_to_ns(sample.presentation_timestamp());
+                self.first_screen_frame_arrival.mark(screen_pts_ns);
                 let duration_ns = cm_time_to_ns(sample.duration());
                 let tick = SCREEN_PTS_COUNTER.fetch_add(1, Ordering::Relaxed) + 1;
                 if tick <= 5 || tick % 60 == 0 {
@@ -48,18 +48,18 @@ impl SCStreamOutputTrait for FrameHandler {
                         tick, screen_pts_ns, duration_ns
                     );
                 }
-                if let Some(sync) = &self.camera_sync {
-                    sync.emit_for_screen_pts(screen_pts_ns);
-                }
-                if self.recording_paused.load(Ordering::Relaxed) {
-                    return;
-                }
-                // Write video frame to FFmpeg stdin
+                let camera_frame = self
+                    .camera_sync
+                    .as_ref()
+                    .and_then(|sync| sync.emit_for_screen_pts(screen_pts_ns));
+                // Write video frame to FFmpeg stdin at its presentation time;
+                // the timeline drops frames that arrive while paused.
                 if let Some(ref mut writer) = *self.video_writer.lock().unwrap() {
                     if let Some(buffer) = sample.image_buffer() {
                         if let Ok(guard) = buffer.lock(PixelBufferLockFlags::ReadOnly) {
-                            let pixels = guard.as_slice();
-                            if writer.write_all(pixels).is_ok() {
+                            let pixels =
+                                packed_rows(guard.as_slice(), self.frame_width, self.frame_height);
+                            if let Ok(true) = writer.write_frame(screen_pts_ns, &pixels) {
                                 let count = self
                                     .video_frame_count
                                     .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
@@ -71,26 +71,29 @@ impl SCStreamOutputTrait for FrameHandler {
                                 } else if count % 30 == 0 {
                                     println!("[SCK] Video frames: {}", count + 1);
                                 }
+                                if let Some(sync) = &self.camera_sync {
+                                    camera_track::record_tick(
+                                        &self.camera_track,
+                                        "SCK",
+                                        screen_pts_ns,
+                                        sync,
+                                        camera_frame.as_ref(),
+                                    );
+                                }
                             }
                         }
                     }
                 }
             }
             SCStreamOutputType::Audio => {
-                // Write audio to named pipe (convert Float32 to s16le)
-                let now_ns = self.capture_started_at.elapsed().as_nanos() as u64;
-                let _ = self.first_system_audio_arrival_ns.compare_exchange(
-                    0,
-                    now_ns,
-                    Ordering::Relaxed,
-                    Ordering::Relaxed,
-                );
+                // Hand Float32 audio to the mixer at its presentation time
+                let audio_pts_ns = cm_time_to_ns(sample.presentation_timestamp());
+                self.first_system_audio_arrival.mark(audio_pts_ns);
                 self.capture_audio_metadata(&sample);
                 if self.recording_paused.load(Ordering::Relaxed) {
                     return;
                 }
-                let mut writer_guard = self.audio_writer.lock().unwrap();
-                if let Some(ref mut writer) = *writer_guard {
+                if let Some(input) = &self.system_audio {
                     if let Some(audio_buffers) = sample.audio_buffer_list() {
                         let planes: Vec<AudioPlane<'_>> = audio_buffers
                             .iter()
@@ -143,33 +146,32 @@ impl SCStreamOutputTrait for FrameHandler {
                             sample.duration(),
                         );
 
-                        let mut s16_data = if planar_layout {
-                            convert_planar_buffers(&planes)
+                        let (mut samples, channels) = if planar_layout {
+                            (convert_planar_buffers(&planes), planes.len() as u32)
                         } else {
-                            convert_interleaved_buffers(&planes)
+                            (convert_interleaved_buffers(&planes), planes[0].channels.max(1))
                         };
 
-                        if s16_data.is_empty() {
+                        if samples.is_empty() {
                             return;
                         }
 
                         if self.system_audio_muted.load(Ordering::Relaxed) {
-                            s16_data.iter_mut().for_each(|b| *b = 0);
+                            samples.fill(0.0);
                         }
 
-                        if writer.write_all(&s16_data).is_ok() {
-                            self.audio_samples_written
-                                .fetch_add(frames_per_channel as u64, Ordering::Relaxed);
-                            let count = self.audio_frame_count.fetch_add(1, Ordering::Relaxed);
-                            if count == 0 {
-                                println!(
-                                    "[SCK] First audio frame written ({} bytes, planar={})",
-                                    s16_data.len(),
-                                    planar_layout
-                                );
-                            } else if count % 100 == 0 {
-                                println!("[SCK] Audio frames: {}", count + 1);
-                            }
+                        input.push_f32(audio_pts_ns, &samples, channels);
+                        self.audio_samples_written
+                            .fetch_add(frames_per_channel as u64, Ordering::Relaxed);
+                        let count = self.audio_frame_count.fetch_add(1, Ordering::Relaxed);
+                        if count == 0 {
+                            println!(
+                                "[SCK] First audio frame mixed ({} samples, planar={})",
+                                samples.len(),
+                                planar_layout
+                            );
+                        } else if count % 100 == 0 {
+                            println!("[SCK] Audio frames: {}", count + 1);
                         }
                     }
                 }
@@ -272,69 +274,30 @@ impl FrameHandler {
     }
 }
 
-fn convert_interleaved_buffers(planes: &[AudioPlane<'_>]) -> Vec<u8> {
-    if planes.is_empty() {
-        return Vec::new();
-    }
-
-    // Treat all buffers as sequential interleaved data.
-    let mut result = Vec::new();
-    for plane in planes {
-        if plane.samples.is_empty() {
-            continue;
-        }
-
-        if result.capacity() < result.len() + plane.samples.len() * 2 {
-            result.reserve(plane.samples.len() * 2);
-        }
-
-        for &sample in plane.samples {
-            let s16 = float_to_s16(sample);
-            result.extend_from_slice(&s16.to_le_bytes());
-        }
-    }
-    result
+/// Treats all buffers as sequential interleaved data.
+fn convert_interleaved_buffers(planes: &[AudioPlane<'_>]) -> Vec<f32> {
+    planes
+        .iter()
+        .flat_map(|plane| plane.samples.iter().copied())
+        .collect()
 }
 
-fn convert_planar_buffers(planes: &[AudioPlane<'_>]) -> Vec<u8> {
-    if planes.is_empty() {
-        return Vec::new();
-    }
-
+fn convert_planar_buffers(planes: &[AudioPlane<'_>]) -> Vec<f32> {
     let frames_per_channel = planes
         .iter()
         .map(|plane| plane.samples.len())
         .min()
         .unwrap_or(0);
-    if frames_per_channel == 0 {
-        return Vec::new();
-    }
 
-    let mut result = Vec::with_capacity(frames_per_channel * planes.len() * 2);
+    let mut result = Vec::with_capacity(frames_per_channel * planes.len());
     for frame_idx in 0..frames_per_channel {
         for plane in planes {
-            let sample = plane.samples[frame_idx];
-            let s16 = float_to_s16(sample);
-            result.extend_from_slice(&s16.to_le_bytes());
+            result.push(plane.samples[frame_idx]);
         }
     }
     result
 }
 
-#[inline]
-fn float_to_s16(sample: f32) -> i16 {
-    let clamped = sample.max(-1.0).min(1.0);
-    (clamped * 32767.0) as i16
-}
-
-#[inline]
-fn cm_time_seconds(time: CMTime) -> f64 {
-    if time.timescale == 0 {
-        return 0.0;
-    }
-    time.value as f64 / time.timescale as f64
-}
-
 struct AudioPlane<'a> {
     channels: u32,
     samples: &'a [f32],
@@ -0,0 +1,168 @@
+mod frame_handler;
+mod start;
+mod state;
+mod stop;
+mod targets;
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::error::{CoreError, CoreResult};
+use crate::camera::CameraSyncHandle;
+use crate::models::RecordingOptions;
+use crate::platform::capture_backend::{AudioDeviceIds, CaptureBackend, CaptureRequest};
+use crate::platform::ffmpeg_capabilities::FfmpegRequirements;
+use crate::platform::macos::ffmpeg::FfmpegLocator;
+use crate::platform::video_timeline::VideoTimeline;
+use crate::time::{host_clock, SharedClock};
+
+use state::RecordingState;
+
+pub use targets::list_capture_targets;
+
+pub struct ScreenCaptureKitRecorder {
+    state: Mutex<Option<RecordingState>>,
+    ffmpeg_locator: Arc<FfmpegLocator>,
+    mic_muted: Arc<AtomicBool>,
+    system_audio_muted: Arc<AtomicBool>,
+    recording_paused: Arc<AtomicBool>,
+    video_timeline: Arc<Mutex<VideoTimeline>>,
+    /// Always the host clock: SCK presentation timestamps are host time.
+    clock: SharedClock,
+}
+
+impl ScreenCaptureKitRecorder {
+    pub fn new(ffmpeg_locator: Arc<FfmpegLocator>) -> Self {
+        Self {
+            state: Mutex::new(None),
+            ffmpeg_locator,
+            mic_muted: Arc::new(AtomicBool::new(false)),
+            system_audio_muted: Arc::new(AtomicBool::new(false)),
+            recording_paused: Arc::new(AtomicBool::new(false)),
+            video_timeline: Arc::new(Mutex::new(VideoTimeline::new())),
+            clock: host_clock(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state.lock().unwrap().is_some()
+    }
+
+    pub fn start(
+        &self,
+        output_path: &PathBuf,
+        options: &RecordingOptions,
+        ffmpeg_path: &Path,
+        camera_sync: Option<Arc<CameraSyncHandle>>,
+    ) -> CoreResult<()> {
+        if self.is_active() {
+            return Err(CoreError::Recording("Already recording".to_string()));
+        }
+
+        self.set_recording_paused(false);
+        *self.video_timeline.lock().unwrap() = VideoTimeline::new();
+        start::start_recording(
+            &self.state,
+            &self.mic_muted,
+            &self.system_audio_muted,
+            &self.recording_paused,
+            &self.video_timeline,
+            &self.clock,
+            output_path,
+            options,
+            ffmpeg_path,
+            camera_sync,
+        )
+    }
+
+    pub fn stop(&self) -> CoreResult<PathBuf> {
+        self.set_recording_paused(false);
+        stop::stop_recording(&self.state, &self.recording_paused)
+    }
+
+    pub fn set_mic_muted(&self, muted: bool) {
+        let old = self.mic_muted.swap(muted, Ordering::Relaxed);
+        if old != muted {
+            println!("[SCK] Microphone mute state updated -> {}", muted);
+        }
+    }
+
+    pub fn set_system_audio_muted(&self, muted: bool) {
+        let old = self.system_audio_muted.swap(muted, Ordering::Relaxed);
+        if old != muted {
+            println!("[SCK] System audio mute state updated -> {}", muted);
+        }
+    }
+
+    pub fn set_recording_paused(&self, paused: bool) {
+        self.video_timeline
+            .lock()
+            .unwrap()
+            .set_paused(paused, self.clock.now_ns());
+        let old = self.recording_paused.swap(paused, Ordering::Relaxed);
+        if old != paused {
+            println!("[SCK] Recording pause state -> {}", paused);
+        }
+    }
+
+    pub fn is_recording_paused(&self) -> bool {
+        self.recording_paused.load(Ordering::Relaxed)
+    }
+}
+
+impl CaptureBackend for ScreenCaptureKitRecorder {
+    fn start(&self, request: CaptureRequest<'_>) -> CoreResult<()> {
+        let ffmpeg_path = self.ffmpeg_locator.resolve()?;
+        ScreenCaptureKitRecorder::start(
+            self,
+            &request.output_path.to_path_buf(),
+            request.options,
+            &ffmpeg_path,
+            request.camera_sync,
+        )
+    }
+
+    fn stop(&self) -> CoreResult<PathBuf> {
+        ScreenCaptureKitRecorder::stop(self)
+    }
+
+    fn set_recording_paused(&self, paused: bool) {
+        ScreenCaptureKitRecorder::set_recording_paused(self, paused)
+    }
+
+    fn set_mic_muted(&self, muted: bool) {
+        ScreenCaptureKitRecorder::set_mic_muted(self, muted)
+    }
+
+    fn set_system_audio_muted(&self, muted: bool) {
+        ScreenCaptureKitRecorder::set_system_audio_muted(self, muted)
+    }
+
+    fn is_active(&self) -> bool {
+        ScreenCaptureKitRecorder::is_active(self)
+    }
+
+    fn audio_device_ids(&self, options: &RecordingOptions) -> CoreResult<AudioDeviceIds> {
+        let mic = if options.include_microphone {
+            Some(start::mic_device_id(start::resolve_mic_index()?))
+        } else {
+            None
+        };
+        Ok(AudioDeviceIds {
+            system_audio: start::SYSTEM_AUDIO_DEVICE_ID.to_string(),
+            mic,
+        })
+    }
+
+    fn check_ffmpeg(&self, options: &RecordingOptions) -> CoreResult<()> {
+        // Video and system audio reach FFmpeg through the shared encoder and
+        // mix; only the mic is an FFmpeg input, on any device index.
+        let mut requirements = FfmpegRequirements::for_options(options);
+        if options.include_microphone {
+            start::mic_audio_source(0).add_requirements(&mut requirements);
+        }
+        self.ffmpeg_locator.capabilities()?.check(&requirements)
+    }
+}
@@ -0,0 +1,467 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64};
+use std::sync::{Arc, Mutex};
+
+use crate::error::{CoreError, CoreResult};
+use crate::camera::CameraSyncHandle;
+use crate::capture_target::CaptureTarget;
+use crate::models::RecordingOptions;
+use crate::platform::audio_mixer::{AudioMixer, Timestamps};
+use crate::platform::camera_track::CameraTrackWriter;
+use crate::platform::pcm_capture::{spawn_pcm_capture, AudioSource, PcmCaptureSpec};
+use crate::platform::video_timeline::{TimelineVideoWriter, VideoTimeline};
+use crate::platform::{device_resolver, ffmpeg_process};
+use crate::time::{ArrivalMarker, SharedClock};
+use crate::MIC_VOLUME_GAIN;
+use screencapturekit::prelude::*;
+
+use super::frame_handler::FrameHandler;
+use super::state::RecordingState;
+
+/// Device ids for [`crate::models::DeviceOffsets`]. AVFoundation indices are
+/// the ones the Swift resolver reports.
+pub(super) const SYSTEM_AUDIO_DEVICE_ID: &str = "screencapturekit:system-audio";
+
+pub(super) fn mic_device_id(index: i32) -> String {
+    format!("avfoundation:audio:{}", index)
+}
+
+/// AVFoundation index of the mic to record: the built-in one, else the first.
+pub(super) fn resolve_mic_index() -> CoreResult<i32> {
+    let resolved = device_resolver::resolve_avf_indices()?;
+    Ok(resolved.audio_index_builtin_mic.unwrap_or(0))
+}
+
+/// FFmpeg input for an AVFoundation mic, recorded by the shared PCM capture.
+pub(super) fn mic_audio_source(index: i32) -> AudioSource {
+    AudioSource::new(
+        mic_device_id(index),
+        vec![
+            "-f".to_string(),
+            "avfoundation".to_string(),
+            "-i".to_string(),
+            format!(":{}", index),
+        ],
+    )
+}
+
+/// Title of the app's `camera-overlay` window (see `tauri.conf.json`).
+const CAMERA_OVERLAY_WINDOW_TITLE: &str = "Momentum Camera";
+
+fn is_own_camera_window(window: &SCWindow) -> bool {
+    let own_process = window
+        .owning_application()
+        .is_some_and(|app| app.process_id() == std::process::id() as i32);
+    own_process && window.title().as_deref() == Some(CAMERA_OVERLAY_WINDOW_TITLE)
+}
+
+/// What SCK records for a [`CaptureTarget`].
+struct CaptureSource {
+    filter: SCContentFilter,
+    /// Size of the captured content, before output scaling.
+    width: u32,
+    height: u32,
+    /// Part of the display to capture, for regions.
+    source_rect: Option<CGRect>,
+    description: String,
+}
+
+/// Resolves `target` (the first display when `None`) against the shareable
+/// content. `hide_camera_window` leaves our camera window out of display and
+/// region captures.
+fn capture_source(
+    content: &SCShareableContent,
+    target: Option<CaptureTarget>,
+    hide_camera_window: bool,
+) -> CoreResult<CaptureSource> {
+    let displays = content.displays();
+    let find_display = |id: Option<u32>| {
+        let display = match id {
+            Some(id) => displays.iter().find(|display| display.display_id() == id),
+            None => displays.first(),
+        };
+        display.ok_or_else(|| match id {
+            Some(id) => CoreError::Recording(format!("Display {} not found", id)),
+            None => CoreError::Recording("No displays found".to_string()),
+        })
+    };
+    let windows = content.windows();
+    let display_filter = |display: &SCDisplay| {
+        let excluded: Vec<&SCWindow> = windows
+            .iter()
+            .filter(|window| hide_camera_window && is_own_camera_window(window))
+            .collect();
+        SCContentFilter::builder()
+            .display(display)
+            .exclude_windows(&excluded)
+            .build()
+    };
+
+    let whole_display = |display: &SCDisplay| CaptureSource {
+        filter: display_filter(display),
+        width: display.width(),
+        height: display.height(),
+        source_rect: None,
+        description: format!("display {}", display.display_id()),
+    };
+
+    match target {
+        None => Ok(whole_display(find_display(None)?)),
+        Some(CaptureTarget::Display(id)) => Ok(whole_display(find_display(Some(id))?)),
+        Some(CaptureTarget::Window(id)) => {
+            let window = windows
+                .iter()
+                .find(|window| window.window_id() == id)
+                .ok_or_else(|| CoreError::Recording(format!("Window {} not found", id)))?;
+            let frame = window.frame();
+            Ok(CaptureSource {
+                filter: SCContentFilter::builder().window(window).build(),
+                width: (frame.width.round() as u32).max(2),
+                height: (frame.height.round() as u32).max(2),
+                source_rect: None,
+                description: format!("window {} ({})", id, window.title().unwrap_or_default()),
+            })
+        }
+        Some(CaptureTarget::Region { display, rect }) => {
+            let display = find_display(Some(display))?;
+            // Regions are in pixels, while SCDisplay sizes and source rects
+            // are in points.
+            let scale = pixels_per_point(display.display_id());
+            let pixel_width = (f64::from(display.width()) * scale).round() as u32;
+            let pixel_height = (f64::from(display.height()) * scale).round() as u32;
+            let rect = rect.clip_to(pixel_width, pixel_height).ok_or_else(|| {
+                CoreError::Recording(format!(
+                    "Capture region is outside display {} ({}x{})",
+                    display.display_id(),
+                    pixel_width,
+                    pixel_height
+                ))
+            })?;
+            let (x, y, width, height) = rect.to_points(scale);
+            Ok(CaptureSource {
+                filter: display_filter(display),
+                width: rect.width,
+                height: rect.height,
+                source_rect: Some(CGRect::new(x, y, width, height)),
+                description: format!("region of display {}", display.display_id()),
+            })
+        }
+    }
+}
+
+/// Backing pixels per point of a display: 2 on Retina displays, 1 when the
+/// display mode cannot be read.
+fn pixels_per_point(display_id: u32) -> f64 {
+    use std::ffi::c_void;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGDisplayCopyDisplayMode(display: u32) -> *mut c_void;
+        fn CGDisplayModeGetWidth(mode: *mut c_void) -> usize;
+        fn CGDisplayModeGetPixelWidth(mode: *mut c_void) -> usize;
+        fn CGDisplayModeRelease(mode: *mut c_void);
+    }
+
+    unsafe {
+        let mode = CGDisplayCopyDisplayMode(display_id);
+        if mode.is_null() {
+            return 1.0;
+        }
+        let points = CGDisplayModeGetWidth(mode);
+        let pixels = CGDisplayModeGetPixelWidth(mode);
+        CGDisplayModeRelease(mode);
+        if points == 0 || pixels == 0 {
+            1.0
+        } else {
+            pixels as f64 / points as f64
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn start_recording(
+    state: &Mutex<Option<RecordingState>>,
+    mic_muted: &Arc<AtomicBool>,
+    system_audio_muted: &Arc<AtomicBool>,
+    recording_paused: &Arc<AtomicBool>,
+    video_timeline: &Arc<Mutex<VideoTimeline>>,
+    clock: &SharedClock,
+    output_path: &PathBuf,
+    options: &RecordingOptions,
+    ffmpeg_path: &Path,
+    camera_sync: Option<Arc<CameraSyncHandle>>,
+) -> CoreResult<()> {
+    // TWO-PASS APPROACH:
+    // 1. Record video to temp file (no audio) - from SCK via stdin, each frame
+    //    carrying its presentation timestamp
+    // 2. Mix system audio (SCK callbacks) and mic (separate FFmpeg process,
+    //    if enabled) live, each chunk placed by its timestamp
+    // 3. On stop: encode the mix and mux it with the video
+    recording_paused.store(false, std::sync::atomic::Ordering::Relaxed);
+    let capture_started_ns = clock.now_ns();
+    let mic_enabled = options.include_microphone;
+
+    println!("[SCK] Starting recording (two-pass mode)...");
+    println!("[SCK]   Final output: {:?}", output_path);
+    println!("[SCK]   Mic: {}", mic_enabled);
+
+    if state.lock().unwrap().is_some() {
+        return Err(CoreError::Recording("Already recording".to_string()));
+    }
+
+    // Get screen info
+    let content = SCShareableContent::get()
+        .map_err(|e| CoreError::Recording(format!("Failed to get shareable content: {:?}", e)))?;
+
+    // A recorded camera is composited at mux, so our own camera window is
+    // left out of display captures instead of showing twice.
+    let source = capture_source(&content, options.capture_target()?, camera_sync.is_some())?;
+    // SCK scales to the output size itself, so frames arrive ready to encode.
+    // The frame rate is an upper bound: idle screens produce fewer frames and
+    // the timeline follows their timestamps.
+    let (width, height) = options
+        .output_scale
+        .output_size(source.width, source.height);
+    let fps = options.frame_rate.fps();
+    println!(
+        "[SCK] Capturing {}: {}x{}, output {}x{} @ {} fps",
+        source.description, source.width, source.height, width, height, fps
+    );
+
+    // Create temp paths
+    let temp_dir = std::env::temp_dir();
+    let session_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
+    let temp_video_path = temp_dir.join(format!("sck_video_{}.mp4", session_id));
+    let mixed_audio_path = temp_dir.join(format!("sck_mix_{}.raw", session_id));
+    let camera_path = camera_sync
+        .as_ref()
+        .map(|_| temp_dir.join(format!("sck_camera_{}.mkv", session_id)));
+    // Removed by `abort_start` if anything after the encoder spawn fails
+    let mut temp_files = vec![temp_video_path.clone(), mixed_audio_path.clone()];
+    temp_files.extend(camera_path.clone());
+    let camera_track = Arc::new(Mutex::new(camera_path.map(|camera_path| {
+        CameraTrackWriter::new(
+            camera_path,
+            options.camera_overlay,
+            width,
+            height,
+            video_timeline.clone(),
+        )
+    })));
+
+    println!("[SCK] Temp video: {:?}", temp_video_path);
+    println!("[SCK] Temp audio mix: {:?}", mixed_audio_path);
+
+    // Resolve mic device if needed
+    let mic_index = if mic_enabled {
+        let idx = resolve_mic_index()?;
+        println!("[SCK] Mic device index: {}", idx);
+        idx
+    } else {
+        0
+    };
+
+    // === PASS 1: VIDEO ONLY FFmpeg ===
+    println!("[SCK] Starting video FFmpeg...");
+    let mut ffmpeg = ffmpeg_process::spawn_bgra_encoder(
+        ffmpeg_path,
+        width,
+        height,
+        fps,
+        &options.encoder,
+        &temp_video_path,
+    )?;
+
+    let ffmpeg_pid = ffmpeg.id();
+    println!("[SCK] Video FFmpeg started (PID: {})", ffmpeg_pid);
+
+    // Capture FFmpeg stderr
+    ffmpeg_process::forward_stderr(&mut ffmpeg, "FFmpeg-Video");
+
+    // Get stdin for video
+    let video_stdin = ffmpeg
+        .stdin
+        .take()
+        .ok_or_else(|| CoreError::Recording("Failed to get FFmpeg stdin".to_string()))
+        .map_err(|err| ffmpeg_process::abort_start(&mut [&mut ffmpeg], &temp_files, err))?;
+    let video_writer = TimelineVideoWriter::new(
+        video_stdin,
+        width,
+        height,
+        fps,
+        video_timeline.clone(),
+    )
+    .map_err(|e| {
+        let err = CoreError::Recording(format!("Failed to write video stream header: {}", e));
+        ffmpeg_process::abort_start(&mut [&mut ffmpeg], &temp_files, err)
+    })?;
+    let video_writer = Arc::new(Mutex::new(Some(video_writer)));
+
+    // === AUDIO MIX: system audio + mic on the video timeline ===
+    let mixer = AudioMixer::create(&mixed_audio_path, clock.clone(), video_timeline.clone())
+        .map_err(|err| ffmpeg_process::abort_start(&mut [&mut ffmpeg], &temp_files, err))?;
+    let system_audio_latency_ns = options.audio_latency_ns(SYSTEM_AUDIO_DEVICE_ID);
+    println!(
+        "[SCK] System audio device: {} (latency offset {:.1} ms)",
+        SYSTEM_AUDIO_DEVICE_ID,
+        system_audio_latency_ns as f64 / 1_000_000.0
+    );
+    let system_audio_input =
+        mixer.add_input("system", 1.0, Timestamps::Device, system_audio_latency_ns);
+    println!("[SCK] Audio mixer started");
+
+    // === MIC RECORDING: Separate FFmpeg process ===
+    let mic_samples_written = Arc::new(AtomicU64::new(0));
+    let first_mic_audio_arrival = ArrivalMarker::new();
+    let mut mic_audio = if mic_enabled {
+        println!("[SCK] Starting mic recording...");
+        let mic_source = mic_audio_source(mic_index).with_offsets_from(options);
+        println!(
+            "[SCK] Mic device: {} (latency offset {:.1} ms)",
+            mic_source.device_id,
+            mic_source.latency_ns as f64 / 1_000_000.0
+        );
+        // The mic runs on its own sample clock; lock it to host time
+        let capture = match spawn_pcm_capture(
+            ffmpeg_path,
+            &mic_source,
+            PcmCaptureSpec {
+                mixer_input: mixer.add_input(
+                    "mic",
+                    MIC_VOLUME_GAIN,
+                    Timestamps::Arrival,
+                    mic_source.latency_ns,
+                ),
+                log_label: "FFmpeg-Mic",
+                muted: mic_muted.clone(),
+                recording_paused: recording_paused.clone(),
+                samples_written: mic_samples_written.clone(),
+                first_arrival: first_mic_audio_arrival.clone(),
+                correct_drift: true,
+                clock: clock.clone(),
+                capture_started_ns,
+            },
+        ) {
+            Ok(capture) => capture,
+            Err(err) => {
+                temp_files.extend(mixer.stem_paths());
+                return Err(ffmpeg_process::abort_start(&mut [&mut ffmpeg], &temp_files, err));
+            }
+        };
+        Some(capture)
+    } else {
+        None
+    };
+
+    // Configure ScreenCaptureKit
+    let mut config = SCStreamConfiguration::new();
+    config.set_width(width);
+    config.set_height(height);
+    config.set_scales_to_fit(true);
+    if let Some(rect) = source.source_rect {
+        config.set_source_rect(rect);
+    }
+    config.set_minimum_frame_interval(&CMTime::new(1, fps as i32));
+    config.set_pixel_format(PixelFormat::BGRA);
+    config.set_captures_audio(true);
+    config.set_sample_rate(48000);
+    config.set_channel_count(2);
+
+    // Create stream
+    let mut stream = SCStream::new(&source.filter, &config);
+
+    // Frame counters for debugging
+    let video_frame_count = Arc::new(AtomicU64::new(0));
+    let audio_frame_count = Arc::new(AtomicU64::new(0));
+    let audio_samples_written = Arc::new(AtomicU64::new(0));
+    let system_audio_sample_rate = Arc::new(AtomicU32::new(0));
+    let system_audio_channel_count = Arc::new(AtomicU32::new(0));
+    let audio_layout_logged = Arc::new(AtomicBool::new(false));
+    let first_screen_frame_arrival = ArrivalMarker::new();
+    let first_system_audio_arrival = ArrivalMarker::new();
+
+    // Add video handler
+    let handler = FrameHandler {
+        video_writer: video_writer.clone(),
+        camera_track: camera_track.clone(),
+        frame_width: width,
+        frame_height: height,
+        system_audio: None,
+        video_frame_count: video_frame_count.clone(),
+        audio_frame_count: audio_frame_count.clone(),
+        audio_sample_rate: system_audio_sample_rate.clone(),
+        audio_channel_count: system_audio_channel_count.clone(),
+        audio_layout_logged: audio_layout_logged.clone(),
+        audio_samples_written: audio_samples_written.clone(),
+        system_audio_muted: system_audio_muted.clone(),
+        recording_paused: recording_paused.clone(),
+        first_screen_frame_arrival: first_screen_frame_arrival.clone(),
+        first_system_audio_arrival: first_system_audio_arrival.clone(),
+        camera_sync: camera_sync.clone(),
+    };
+    stream.add_output_handler(handler, SCStreamOutputType::Screen);
+
+    // Add audio handler for system audio
+    let audio_handler = FrameHandler {
+        video_writer: Arc::new(Mutex::new(None)),
+        camera_track: Arc::new(Mutex::new(None)),
+        frame_width: width,
+        frame_height: height,
+        system_audio: Some(system_audio_input),
+        video_frame_count: video_frame_count.clone(),
+        audio_frame_count: audio_frame_count.clone(),
+        audio_sample_rate: system_audio_sample_rate.clone(),
+        audio_channel_count: system_audio_channel_count.clone(),
+        audio_layout_logged: audio_layout_logged.clone(),
+        audio_samples_written: audio_samples_written.clone(),
+        system_audio_muted: system_audio_muted.clone(),
+        recording_paused: recording_paused.clone(),
+        first_screen_frame_arrival: first_screen_frame_arrival.clone(),
+        first_system_audio_arrival: first_system_audio_arrival.clone(),
+        camera_sync: camera_sync.clone(),
+    };
+    stream.add_output_handler(audio_handler, SCStreamOutputType::Audio);
+
+    // Start capture
+    println!("[SCK] Starting capture...");
+    if let Err(e) = stream.start_capture() {
+        temp_files.extend(mixer.stem_paths());
+        let mut processes = vec![&mut ffmpeg];
+        if let Some(mic) = mic_audio.as_mut() {
+            processes.push(&mut mic.process);
+        }
+        let err = CoreError::Recording(format!("Failed to start capture: {:?}", e));
+        return Err(ffmpeg_process::abort_start(&mut processes, &temp_files, err));
+    }
+    println!("[SCK] ✓ Capture started");
+
+    // Store state
+    *state.lock().unwrap() = Some(RecordingState {
+        ffmpeg_process: ffmpeg,
+        stream,
+        video_writer,
+        camera_track,
+        mixer,
+        temp_video_path,
+        mixed_audio_path,
+        output_path: output_path.clone(),
+        mic_audio,
+        system_audio_sample_rate,
+        system_audio_channel_count,
+        video_frame_count,
+        audio_frame_count,
+        audio_samples_written,
+        mic_samples_written,
+        clock: clock.clone(),
+        capture_started_ns,
+        first_screen_frame_arrival,
+        first_system_audio_arrival,
+        first_mic_audio_arrival,
+        requested_fps: fps,
+        ffmpeg_path: ffmpeg_path.to_path_buf(),
+        encoder: options.encoder,
+    });
+
+    println!("[SCK] ✓ Recording started successfully");
+    Ok(())
+}
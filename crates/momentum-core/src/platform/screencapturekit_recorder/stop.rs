@@ -1,18 +1,19 @@
 use std::path::PathBuf;
-use std::process::Command;
 use std::sync::atomic::Ordering;
 use std::sync::Mutex;
 use std::thread;
 
-use crate::error::{AppError, AppResult};
+use crate::error::{CoreError, CoreResult};
+use crate::platform::audio_alignment::align_mic_to_system;
+use crate::platform::camera_track::CameraTrack;
+use crate::platform::mux::mux_final_video;
 
-use super::mux::mux_final_video;
 use super::state::RecordingState;
 
 pub fn stop_recording(
     state: &Mutex<Option<RecordingState>>,
     recording_paused: &std::sync::Arc<std::sync::atomic::AtomicBool>,
-) -> AppResult<PathBuf> {
+) -> CoreResult<PathBuf> {
     println!("[SCK] === STOP RECORDING START ===");
     let stop_start = std::time::Instant::now();
 
@@ -20,22 +21,15 @@ pub fn stop_recording(
         .lock()
         .unwrap()
         .take()
-        .ok_or_else(|| AppError::Recording("No active recording".to_string()))?;
+        .ok_or_else(|| CoreError::Recording("No active recording".to_string()))?;
+    let timeline_end_ns = state.clock.now_ns();
     recording_paused.store(false, Ordering::Relaxed);
 
     let output_path = state.output_path.clone();
     let temp_video_path = state.temp_video_path.clone();
-    let system_audio_path = state.system_audio_path.clone();
-    let mic_audio_path = state.mic_audio_path.clone();
+    let mixed_audio_path = state.mixed_audio_path.clone();
     let system_audio_sample_rate = state.system_audio_sample_rate.load(Ordering::Relaxed);
-    let system_audio_channel_count = state.system_audio_channel_count.load(Ordering::Relaxed);
     let system_audio_samples = state.audio_samples_written.load(Ordering::Relaxed);
-    let mic_audio_samples = state.mic_samples_written.load(Ordering::Relaxed);
-    let first_screen_arrival_ns = state.first_screen_frame_arrival_ns.load(Ordering::Relaxed);
-    let first_system_audio_arrival_ns = state.first_system_audio_arrival_ns.load(Ordering::Relaxed);
-    let first_mic_audio_arrival_ns = state.first_mic_audio_arrival_ns.load(Ordering::Relaxed);
-    let mic_sample_rate = state.mic_sample_rate;
-    let mic_channel_count = state.mic_channel_count;
 
     // STEP 1: Stop ScreenCaptureKit capture
     println!("[SCK] Stopping ScreenCaptureKit capture...");
@@ -47,17 +41,15 @@ pub fn stop_recording(
 
     // STEP 3: Close writers
     println!("[SCK] Closing writers...");
-    {
-        let mut guard = state.video_writer.lock().unwrap();
-        *guard = None;
-    }
+    let video_duration = match state.video_writer.lock().unwrap().take() {
+        Some(mut writer) => writer.finish(timeline_end_ns).unwrap_or_else(|e| {
+            println!("[SCK] ⚠ Failed to finish video timeline: {}", e);
+            std::time::Duration::ZERO
+        }),
+        None => std::time::Duration::ZERO,
+    };
     println!("[SCK] ✓ Video writer closed");
-
-    {
-        let mut guard = state.audio_writer.lock().unwrap();
-        *guard = None;
-    }
-    println!("[SCK] ✓ Audio writer closed");
+    let camera_track = finish_camera_track(&state, timeline_end_ns);
 
     // STEP 4: Wait for video FFmpeg to finish (should finish quickly since stdin is closed)
     println!("[SCK] Waiting for video FFmpeg to finish...");
@@ -88,113 +80,103 @@ pub fn stop_recording(
         }
     }
 
-    // STEP 5: Stop mic FFmpeg (if running)
-    if let Some(mut mic_proc) = state.mic_process.take() {
+    // STEP 5: Stop mic FFmpeg (if running) and let its last chunk reach the mix
+    if let Some(mic_audio) = state.mic_audio.take() {
         println!("[SCK] Stopping mic FFmpeg...");
-        let mic_pid = mic_proc.id();
-        let _ = Command::new("kill")
-            .args(["-INT", &mic_pid.to_string()])
-            .status();
-
-        // Wait for mic FFmpeg
-        let mic_wait = std::time::Instant::now();
-        loop {
-            match mic_proc.try_wait() {
-                Ok(Some(status)) => {
-                    println!("[SCK] ✓ Mic FFmpeg exited: {:?}", status);
-                    break;
-                }
-                Ok(None) => {
-                    if mic_wait.elapsed() > std::time::Duration::from_secs(3) {
-                        let _ = mic_proc.kill();
-                        let _ = mic_proc.wait();
-                        break;
-                    }
-                    thread::sleep(std::time::Duration::from_millis(100));
-                }
-                Err(_) => {
-                    let _ = mic_proc.kill();
-                    break;
-                }
-            }
-        }
+        mic_audio.stop("FFmpeg-Mic");
     }
 
+    // STEP 6: Write the rest of the mix, padded to the video length
+    let mix = state.mixer.finish(timeline_end_ns);
+    let mic_audio_samples = state.mic_samples_written.load(Ordering::Relaxed);
+
     let video_frames = state.video_frame_count.load(Ordering::Relaxed);
     let audio_packets = state.audio_frame_count.load(Ordering::Relaxed);
     let audio_samples = system_audio_samples;
-    let approx_video_seconds = if state.requested_fps > 0 {
-        video_frames as f64 / state.requested_fps as f64
-    } else {
-        0.0
-    };
+    let video_seconds = video_duration.as_secs_f64();
     let approx_audio_seconds = if system_audio_sample_rate > 0 {
         audio_samples as f64 / system_audio_sample_rate as f64
     } else {
         0.0
     };
     println!(
-        "[SCK] Frame stats: video={} (~{:.2}s @ {} fps), audio_packets={} samples={} (~{:.2}s @ {} Hz)",
+        "[SCK] Frame stats: video={} ({:.3}s, up to {} fps), audio_packets={} samples={} (~{:.2}s @ {} Hz), mic_samples={}",
         video_frames,
-        approx_video_seconds,
+        video_seconds,
         state.requested_fps,
         audio_packets,
         audio_samples,
         approx_audio_seconds,
-        system_audio_sample_rate
+        system_audio_sample_rate,
+        mic_audio_samples
     );
-    let capture_elapsed_ms = state.capture_started_at.elapsed().as_millis();
+    let started_ns = state.capture_started_ns;
     println!(
         "[SCK] Timeline markers (from recorder start): screen={}ms system={}ms mic={}ms total={}ms",
-        first_screen_arrival_ns / 1_000_000,
-        first_system_audio_arrival_ns / 1_000_000,
-        first_mic_audio_arrival_ns / 1_000_000,
-        capture_elapsed_ms
+        state.first_screen_frame_arrival.millis_since(started_ns),
+        state.first_system_audio_arrival.millis_since(started_ns),
+        state.first_mic_audio_arrival.millis_since(started_ns),
+        timeline_end_ns.saturating_sub(started_ns) / 1_000_000
     );
 
-    let audio_anchor_ns = first_screen_arrival_ns;
-    let system_audio_offset_seconds = if audio_anchor_ns > 0 && first_system_audio_arrival_ns > 0 {
-        Some((first_system_audio_arrival_ns as f64 - audio_anchor_ns as f64) / 1_000_000_000.0)
-    } else {
-        None
-    };
-    let mic_audio_offset_seconds = if audio_anchor_ns > 0 && first_mic_audio_arrival_ns > 0 {
-        Some((first_mic_audio_arrival_ns as f64 - audio_anchor_ns as f64) / 1_000_000_000.0)
-    } else {
-        None
+    let mut metadata = Vec::new();
+    let mixed_audio = match &mix {
+        Ok(summary) => {
+            println!(
+                "[SCK] Audio mix: {} frames ({:.3}s)",
+                summary.frames,
+                summary.duration.as_secs_f64()
+            );
+            for source in &summary.sources {
+                println!(
+                    "[SCK]   {}: {} gaps ({} frames of silence), {} overlaps ({} frames dropped), {} late frames, {} stray frames",
+                    source.label,
+                    source.gaps,
+                    source.gap_frames,
+                    source.overlaps,
+                    source.overlap_frames,
+                    source.late_frames,
+                    source.stray_frames
+                );
+            }
+            metadata = align_mic_to_system(summary, &mixed_audio_path, "SCK");
+            Some(mixed_audio_path.as_path())
+        }
+        Err(err) => {
+            println!("[SCK] ⚠ Audio mix failed: {}, muxing video only", err);
+            None
+        }
     };
 
-    // STEP 6: Mux video + audio together
+    // STEP 7: Encode the mix and mux it with the video
     println!("[SCK] Muxing video + audio...");
     let mux_result = mux_final_video(
         &temp_video_path,
-        &system_audio_path,
-        mic_audio_path.as_ref(),
+        mixed_audio,
+        camera_track.as_ref(),
         &output_path,
-        if system_audio_sample_rate > 0 {
-            Some(system_audio_sample_rate)
-        } else {
-            None
-        },
-        if system_audio_channel_count > 0 {
-            Some(system_audio_channel_count)
-        } else {
-            None
-        },
-        mic_sample_rate.zip(mic_channel_count),
-        system_audio_samples,
-        mic_audio_samples,
-        approx_video_seconds,
-        system_audio_offset_seconds,
-        mic_audio_offset_seconds,
         &state.ffmpeg_path,
+        &state.encoder,
+        state.requested_fps,
+        &metadata,
     );
 
+    // If mux failed, keep the video-only recording before the temp files go
+    if let Err(e) = mux_result {
+        println!("[SCK] ⚠ Mux failed: {}, returning video-only", e);
+        if temp_video_path.exists() {
+            let _ = std::fs::copy(&temp_video_path, &output_path);
+        }
+    }
+
     // Clean up temp files
     let _ = std::fs::remove_file(&temp_video_path);
-    let _ = std::fs::remove_file(&system_audio_path);
-    if let Some(mic_path) = &mic_audio_path {
-        let _ = std::fs::remove_file(mic_path);
+    let _ = std::fs::remove_file(&mixed_audio_path);
+    if let Some(track) = &camera_track {
+        let _ = std::fs::remove_file(&track.path);
+    }
+    for stem in state.mixer.stem_paths() {
+        let _ = std::fs::remove_file(stem);
     }
 
     println!(
@@ -202,15 +184,6 @@ pub fn stop_recording(
         stop_start.elapsed()
     );
 
-    // Check result
-    if let Err(e) = mux_result {
-        println!("[SCK] ⚠ Mux failed: {}, returning video-only", e);
-        // If mux failed, copy video-only
-        if temp_video_path.exists() {
-            let _ = std::fs::copy(&temp_video_path, &output_path);
-        }
-    }
-
     if output_path.exists() {
         let size = std::fs::metadata(&output_path)
             .map(|m| m.len())
@@ -221,9 +194,30 @@ pub fn stop_recording(
         );
         Ok(output_path)
     } else {
-        Err(AppError::Recording(format!(
+        Err(CoreError::Recording(format!(
             "Output file not created: {:?}",
             output_path
         )))
     }
 }
+
+fn finish_camera_track(state: &RecordingState, timeline_end_ns: u64) -> Option<CameraTrack> {
+    let writer = state.camera_track.lock().unwrap().take()?;
+    match writer.finish(timeline_end_ns) {
+        Ok(Some(track)) => {
+            println!(
+                "[SCK] Camera track: {} frames, visible {:?}",
+                track.frames, track.visible_spans
+            );
+            Some(track)
+        }
+        Ok(None) => {
+            println!("[SCK] ⚠ No camera frames recorded");
+            None
+        }
+        Err(err) => {
+            println!("[SCK] ⚠ Failed to finish camera track: {}", err);
+            None
+        }
+    }
+}
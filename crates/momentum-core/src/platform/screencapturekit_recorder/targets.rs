@@ -0,0 +1,93 @@
+use base64::{engine::general_purpose, Engine as _};
+use screencapturekit::prelude::*;
+use screencapturekit::screenshot_manager::SCScreenshotManager;
+
+use crate::capture_target::{thumbnail_size, CaptureTarget, CaptureTargetInfo, TargetBounds};
+use crate::error::{CoreError, CoreResult};
+use crate::platform::macos::ffmpeg::FfmpegLocator;
+
+/// Windows smaller than this (menu bar items, status icons) are not listed.
+const MIN_WINDOW_SIDE: f64 = 40.0;
+
+/// Every display, and the on-screen application windows of other apps, with
+/// thumbnails from `SCScreenshotManager` (macOS 14+; `None` before that).
+pub fn list_capture_targets(_ffmpeg_locator: &FfmpegLocator) -> CoreResult<Vec<CaptureTargetInfo>> {
+    let content = SCShareableContent::get()
+        .map_err(|e| CoreError::Recording(format!("Failed to get shareable content: {:?}", e)))?;
+    let own_pid = std::process::id() as i32;
+    let mut targets = Vec::new();
+
+    for (index, display) in content.displays().iter().enumerate() {
+        let frame = display.frame();
+        let mut info = CaptureTargetInfo::new(
+            CaptureTarget::Display(display.display_id()),
+            format!("Display {}", index + 1),
+            None,
+            TargetBounds {
+                x: frame.x.round() as i32,
+                y: frame.y.round() as i32,
+                width: display.width(),
+                height: display.height(),
+            },
+        );
+        let filter = SCContentFilter::builder()
+            .display(display)
+            .exclude_windows(&[])
+            .build();
+        info.thumbnail_base64 = thumbnail(&filter, display.width(), display.height());
+        targets.push(info);
+    }
+
+    for window in content.windows() {
+        let frame = window.frame();
+        let app = window.owning_application();
+        let listed = window.is_on_screen()
+            && window.window_layer() == 0
+            && frame.width >= MIN_WINDOW_SIDE
+            && frame.height >= MIN_WINDOW_SIDE
+            && app.as_ref().is_some_and(|app| app.process_id() != own_pid);
+        if !listed {
+            continue;
+        }
+        let app_name = app.map(|app| app.application_name());
+        let title = window
+            .title()
+            .filter(|title| !title.is_empty())
+            .or_else(|| app_name.clone())
+            .unwrap_or_default();
+        let bounds = TargetBounds {
+            x: frame.x.round() as i32,
+            y: frame.y.round() as i32,
+            width: frame.width.round() as u32,
+            height: frame.height.round() as u32,
+        };
+        let mut info = CaptureTargetInfo::new(
+            CaptureTarget::Window(window.window_id()),
+            title,
+            app_name,
+            bounds,
+        );
+        let filter = SCContentFilter::builder().window(&window).build();
+        info.thumbnail_base64 = thumbnail(&filter, bounds.width, bounds.height);
+        targets.push(info);
+    }
+    Ok(targets)
+}
+
+/// Captures `filter` once at thumbnail size. The screenshot API only writes
+/// PNGs to disk, so it goes through a temp file.
+fn thumbnail(filter: &SCContentFilter, width: u32, height: u32) -> Option<String> {
+    let (thumb_width, thumb_height) = thumbnail_size(width, height);
+    let mut config = SCStreamConfiguration::new();
+    config.set_width(thumb_width);
+    config.set_height(thumb_height);
+    let image = SCScreenshotManager::capture_image(filter, &config).ok()?;
+
+    let path = std::env::temp_dir().join(format!("sck_thumb_{}.png", uuid::Uuid::new_v4()));
+    let png = image
+        .save_png(path.to_str()?)
+        .ok()
+        .and_then(|_| std::fs::read(&path).ok());
+    let _ = std::fs::remove_file(&path);
+    png.map(|png| general_purpose::STANDARD.encode(png))
+}
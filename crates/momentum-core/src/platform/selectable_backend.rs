@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+
+use crate::error::CoreResult;
+use crate::models::{CaptureMode, RecordingOptions};
+use crate::platform::capture_backend::{AudioDeviceIds, CaptureBackend, CaptureRequest};
+use crate::platform::synthetic_recorder::SyntheticRecorder;
+
+/// Environment variable selecting the capture mode when `RecordingOptions`
+/// does not: `synthetic` or `native`.
+pub const CAPTURE_MODE_ENV: &str = "MOMENTUM_CAPTURE_MODE";
+
+pub fn capture_mode_from_env() -> Option<CaptureMode> {
+    let value = std::env::var(CAPTURE_MODE_ENV).ok()?;
+    match value.trim().to_ascii_lowercase().as_str() {
+        "synthetic" => Some(CaptureMode::Synthetic),
+        "native" => Some(CaptureMode::Native),
+        other => {
+            eprintln!("[Capture] Ignoring unknown {}={}", CAPTURE_MODE_ENV, other);
+            None
+        }
+    }
+}
+
+/// Options win over the environment; native capture is the default.
+pub fn resolve_capture_mode(options: &RecordingOptions) -> CaptureMode {
+    options
+        .capture_mode
+        .or_else(capture_mode_from_env)
+        .unwrap_or_default()
+}
+
+/// Routes each session to the platform backend or to [`SyntheticRecorder`]
+/// according to [`resolve_capture_mode`].
+pub struct SelectableCaptureBackend<N: CaptureBackend> {
+    native: N,
+    synthetic: SyntheticRecorder,
+}
+
+impl<N: CaptureBackend> SelectableCaptureBackend<N> {
+    pub fn new(native: N, synthetic: SyntheticRecorder) -> Self {
+        Self { native, synthetic }
+    }
+}
+
+impl<N: CaptureBackend> CaptureBackend for SelectableCaptureBackend<N> {
+    fn start(&self, request: CaptureRequest<'_>) -> CoreResult<()> {
+        match resolve_capture_mode(request.options) {
+            CaptureMode::Native => self.native.start(request),
+            CaptureMode::Synthetic => {
+                println!("[Capture] Using synthetic sources");
+                self.synthetic.start(request)
+            }
+        }
+    }
+
+    fn stop(&self) -> CoreResult<PathBuf> {
+        if self.synthetic.is_active() {
+            self.synthetic.stop()
+        } else {
+            self.native.stop()
+        }
+    }
+
+    // Flags go to both backends so mute state survives switching modes.
+    fn set_recording_paused(&self, paused: bool) {
+        self.native.set_recording_paused(paused);
+        self.synthetic.set_recording_paused(paused);
+    }
+
+    fn set_mic_muted(&self, muted: bool) {
+        self.native.set_mic_muted(muted);
+        self.synthetic.set_mic_muted(muted);
+    }
+
+    fn set_system_audio_muted(&self, muted: bool) {
+        self.native.set_system_audio_muted(muted);
+        self.synthetic.set_system_audio_muted(muted);
+    }
+
+    fn is_active(&self) -> bool {
+        self.native.is_active() || self.synthetic.is_active()
+    }
+
+    fn audio_device_ids(&self, options: &RecordingOptions) -> CoreResult<AudioDeviceIds> {
+        match resolve_capture_mode(options) {
+            CaptureMode::Native => self.native.audio_device_ids(options),
+            CaptureMode::Synthetic => self.synthetic.audio_device_ids(options),
+        }
+    }
+
+    fn check_ffmpeg(&self, options: &RecordingOptions) -> CoreResult<()> {
+        match resolve_capture_mode(options) {
+            CaptureMode::Native => self.native.check_ffmpeg(options),
+            CaptureMode::Synthetic => self.synthetic.check_ffmpeg(options),
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::CoreResult;
+use crate::models::RecordingOptions;
+use crate::platform::capture_backend::{AudioDeviceIds, CaptureBackend, CaptureRequest};
+use crate::platform::ffmpeg_capabilities::FfmpegRequirements;
+use crate::platform::ffmpeg_pipeline::{FfmpegPipeline, PipelineSources, VideoSource};
+use crate::platform::macos::ffmpeg::FfmpegLocator;
+use crate::platform::pcm_capture::{AudioSource, PCM_SAMPLE_RATE};
+
+/// What the synthetic sources show and play.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyntheticPattern {
+    /// `testsrc2`, a 440 Hz sine and pink noise.
+    #[default]
+    TestCard,
+    /// Black screen flashing white for [`FLASH_SECONDS`] at every whole
+    /// second, a system audio beep at the same instants and a mic beep half a
+    /// second later. Used by [`crate::calibration`] to measure A/V offsets.
+    FlashBeep,
+}
+
+pub const FLASH_SECONDS: f64 = 0.1;
+pub const BEEP_SECONDS: f64 = 0.05;
+/// Mic beeps trail the flashes by this much so they can be told apart from
+/// system audio beeps in the mix.
+pub const MIC_BEEP_DELAY_SECONDS: f64 = 0.5;
+
+/// Device ids of the generators, for [`crate::models::DeviceOffsets`].
+pub const SYSTEM_AUDIO_DEVICE_ID: &str = "synthetic:system-audio";
+pub const MIC_DEVICE_ID: &str = "synthetic:mic";
+
+/// Known timing of the synthetic sources. The defaults deliberately start the
+/// mic late and run its clock fast so that timestamp placement in the audio
+/// mixer and the mic drift correction are exercised on every run.
+#[derive(Debug, Clone)]
+pub struct SyntheticTiming {
+    pub width: u32,
+    pub height: u32,
+    pub system_audio_start_latency: Duration,
+    pub mic_start_latency: Duration,
+    /// Samples the mic produces per second of wall time while claiming to run
+    /// at 48 kHz.
+    pub mic_actual_sample_rate: u32,
+    pub pattern: SyntheticPattern,
+}
+
+impl Default for SyntheticTiming {
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 720,
+            system_audio_start_latency: Duration::ZERO,
+            mic_start_latency: Duration::from_millis(250),
+            // 0.5% fast, 300 ms of drift per minute left uncorrected
+            mic_actual_sample_rate: 48_240,
+            pattern: SyntheticPattern::TestCard,
+        }
+    }
+}
+
+impl SyntheticTiming {
+    /// Everything a session with `options` runs, generators included.
+    pub fn requirements(&self, options: &RecordingOptions) -> FfmpegRequirements {
+        let mut requirements = FfmpegRequirements::for_options(options);
+        requirements.add_input(&self.video_source(options.frame_rate.fps()).input_args);
+        self.system_audio_source()
+            .add_requirements(&mut requirements);
+        if options.include_microphone {
+            self.mic_source().add_requirements(&mut requirements);
+        }
+        requirements
+    }
+
+    /// The screen pattern at the recording's frame rate.
+    fn video_source(&self, fps: u32) -> VideoSource {
+        let graph = match self.pattern {
+            SyntheticPattern::TestCard => format!(
+                "testsrc2=size={}x{}:rate={}",
+                self.width, self.height, fps
+            ),
+            SyntheticPattern::FlashBeep => flash_graph(self.width, self.height, fps),
+        };
+        VideoSource {
+            input_args: lavfi_input(graph),
+            width: self.width,
+            height: self.height,
+            fps,
+        }
+    }
+
+    fn system_audio_source(&self) -> AudioSource {
+        AudioSource {
+            start_latency: self.system_audio_start_latency,
+            ..AudioSource::new(SYSTEM_AUDIO_DEVICE_ID, lavfi_input(match self.pattern {
+                SyntheticPattern::TestCard => {
+                    format!("sine=frequency=440:sample_rate={}", PCM_SAMPLE_RATE)
+                }
+                SyntheticPattern::FlashBeep => beep_graph(1_000, 0.0, PCM_SAMPLE_RATE),
+            }))
+        }
+    }
+
+    fn mic_source(&self) -> AudioSource {
+        // -re paces the generator at its own rate; relabelling afterwards (no
+        // resampling) makes the file claim 48 kHz while holding more samples.
+        let graph = match self.pattern {
+            SyntheticPattern::TestCard => format!(
+                "anoisesrc=color=pink:amplitude=0.2:sample_rate={}",
+                self.mic_actual_sample_rate
+            ),
+            SyntheticPattern::FlashBeep => beep_graph(
+                2_000,
+                MIC_BEEP_DELAY_SECONDS,
+                self.mic_actual_sample_rate,
+            ),
+        };
+        AudioSource {
+            output_filter: Some(format!("asetrate={}", PCM_SAMPLE_RATE)),
+            start_latency: self.mic_start_latency,
+            ..AudioSource::new(MIC_DEVICE_ID, lavfi_input(graph))
+        }
+    }
+}
+
+/// Black frames turning white for [`FLASH_SECONDS`] at every whole second.
+fn flash_graph(width: u32, height: u32, fps: u32) -> String {
+    format!(
+        "color=c=black:size={}x{}:rate={},drawbox=c=white:t=fill:enable='lt(mod(t,1),{})'",
+        width, height, fps, FLASH_SECONDS
+    )
+}
+
+/// The flashes and system audio beeps of [`SyntheticPattern::FlashBeep`] as
+/// one lavfi graph with a video and an audio output, for playing the pattern
+/// on real devices.
+pub fn flash_beep_player_graph(width: u32, height: u32, fps: u32) -> String {
+    format!(
+        "{}[out0];{}[out1]",
+        flash_graph(width, height, fps),
+        beep_graph(1_000, 0.0, PCM_SAMPLE_RATE)
+    )
+}
+
+/// A `frequency` Hz tone for [`BEEP_SECONDS`] at `delay` past every second.
+fn beep_graph(frequency: u32, delay: f64, sample_rate: u32) -> String {
+    format!(
+        "aevalsrc=exprs='if(lt(mod(t+{lead},1),{beep}),0.8*sin(2*PI*{frequency}*t),0)':sample_rate={sample_rate}",
+        lead = 1.0 - delay,
+        beep = BEEP_SECONDS,
+        frequency = frequency,
+        sample_rate = sample_rate
+    )
+}
+
+/// Real-time (`-re`) lavfi input for a generator graph.
+fn lavfi_input(graph: String) -> Vec<String> {
+    vec![
+        "-re".to_string(),
+        "-f".to_string(),
+        "lavfi".to_string(),
+        "-i".to_string(),
+        graph,
+    ]
+}
+
+/// Capture backend fed entirely by FFmpeg test generators: `testsrc2` for the
+/// screen, `sine` for system audio and `anoisesrc` for the mic. Runs the same
+/// pipeline and mux as the X11 backend, so it works without a display or
+/// audio server.
+pub struct SyntheticRecorder {
+    pipeline: FfmpegPipeline,
+    ffmpeg_locator: Arc<FfmpegLocator>,
+    timing: SyntheticTiming,
+}
+
+impl SyntheticRecorder {
+    pub fn new(ffmpeg_locator: Arc<FfmpegLocator>) -> Self {
+        Self::with_timing(ffmpeg_locator, SyntheticTiming::default())
+    }
+
+    pub fn with_timing(ffmpeg_locator: Arc<FfmpegLocator>, timing: SyntheticTiming) -> Self {
+        Self {
+            pipeline: FfmpegPipeline::new("Synthetic"),
+            ffmpeg_locator,
+            timing,
+        }
+    }
+}
+
+impl CaptureBackend for SyntheticRecorder {
+    fn start(&self, request: CaptureRequest<'_>) -> CoreResult<()> {
+        let ffmpeg_path = self.ffmpeg_locator.resolve()?;
+        let options = request.options;
+        let mic = options
+            .include_microphone
+            .then(|| self.timing.mic_source().with_offsets_from(options));
+
+        self.pipeline.start(
+            &ffmpeg_path,
+            PipelineSources {
+                video: self.timing.video_source(options.frame_rate.fps()),
+                system_audio: self.timing.system_audio_source().with_offsets_from(options),
+                mic,
+            },
+            request,
+        )
+    }
+
+    fn stop(&self) -> CoreResult<PathBuf> {
+        self.pipeline.stop()
+    }
+
+    fn set_recording_paused(&self, paused: bool) {
+        self.pipeline.set_recording_paused(paused);
+    }
+
+    fn set_mic_muted(&self, muted: bool) {
+        self.pipeline.set_mic_muted(muted);
+    }
+
+    fn set_system_audio_muted(&self, muted: bool) {
+        self.pipeline.set_system_audio_muted(muted);
+    }
+
+    fn is_active(&self) -> bool {
+        self.pipeline.is_active()
+    }
+
+    fn audio_device_ids(&self, options: &RecordingOptions) -> CoreResult<AudioDeviceIds> {
+        Ok(AudioDeviceIds {
+            system_audio: self.timing.system_audio_source().device_id,
+            mic: options
+                .include_microphone
+                .then(|| self.timing.mic_source().device_id),
+        })
+    }
+
+    fn check_ffmpeg(&self, options: &RecordingOptions) -> CoreResult<()> {
+        self.ffmpeg_locator
+            .capabilities()?
+            .check(&self.timing.requirements(options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SyntheticRecorder, SyntheticTiming};
+    use crate::models::{CaptureMode, RecordingOptions};
+    use crate::camera::CameraSyncHandle;
+    use crate::platform::macos::ffmpeg::FfmpegLocator;
+    use crate::Recorder;
+    use std::process::Command;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn mic_source_starts_late_and_runs_fast() {
+        let mic = SyntheticTiming::default().mic_source();
+        assert_eq!(mic.start_latency, Duration::from_millis(250));
+        assert_eq!(mic.output_filter.as_deref(), Some("asetrate=48000"));
+        assert!(mic.input_args.last().unwrap().contains("sample_rate=48240"));
+        assert_eq!(mic.input_args[0], "-re");
+    }
+
+    #[test]
+    #[ignore = "needs ffmpeg and ffprobe; run with `cargo test -- --ignored`"]
+    fn synthetic_session_produces_paused_and_muxed_mp4() {
+        let locator = Arc::new(FfmpegLocator::new());
+        let ffmpeg_path = locator.resolve().expect("ffmpeg");
+        let recorder = Recorder::new(
+            Arc::new(SyntheticRecorder::new(locator)),
+            Arc::new(CameraSyncHandle::new()),
+        );
+
+        recorder
+            .start(RecordingOptions {
+                include_microphone: true,
+                capture_mode: Some(CaptureMode::Synthetic),
+                ..RecordingOptions::default()
+            })
+            .expect("start");
+        thread::sleep(Duration::from_millis(1500));
+        recorder.pause().expect("pause");
+        thread::sleep(Duration::from_millis(700));
+        recorder.resume().expect("resume");
+        thread::sleep(Duration::from_millis(1000));
+        let result = recorder.stop().expect("stop");
+
+        let ffprobe = ffmpeg_path.with_file_name("ffprobe");
+        let output = Command::new(&ffprobe)
+            .args([
+                "-v",
+                "error",
+                "-show_entries",
+                "format=duration:stream=codec_type",
+                "-of",
+                "json",
+            ])
+            .arg(&result.output_path)
+            .output()
+            .expect("ffprobe");
+        let _ = std::fs::remove_file(&result.output_path);
+        let probe: serde_json::Value = serde_json::from_slice(&output.stdout).expect("json");
+
+        let codec_types: Vec<&str> = probe["streams"]
+            .as_array()
+            .expect("streams")
+            .iter()
+            .filter_map(|stream| stream["codec_type"].as_str())
+            .collect();
+        assert_eq!(codec_types, vec!["video", "audio"]);
+
+        // 1.5 s + 1.0 s recorded; the 0.7 s pause must not appear in the file.
+        let duration: f64 = probe["format"]["duration"]
+            .as_str()
+            .and_then(|value| value.parse().ok())
+            .expect("duration");
+        assert!(
+            (duration - 2.5).abs() < 0.4,
+            "unexpected duration {:.3}s",
+            duration
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Places captured frames on the output video timeline. The first frame is
+//! t=0 and paused spans are cut out, so the encoded video lasts exactly as long
+//! as the recording was live instead of `frame count / fps`.
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::platform::matroska::{RawVideoMkvWriter, TIMESTAMP_SCALE_NS};
+
+/// Pause spans in capture-clock nanoseconds, plus the timeline origin.
+/// Shared between the pause toggle and whichever thread writes frames.
+#[derive(Debug, Default)]
+pub struct VideoTimeline {
+    origin_ns: Option<u64>,
+    /// `(start, end)`; the last span is open while paused.
+    pauses: Vec<(u64, Option<u64>)>,
+}
+
+impl VideoTimeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_paused(&mut self, paused: bool, at_ns: u64) {
+        match self.pauses.last_mut() {
+            Some((_, None)) if paused => {}
+            Some((start, end @ None)) => *end = Some(at_ns.max(*start)),
+            _ if paused => self.pauses.push((at_ns, None)),
+            _ => {}
+        }
+    }
+
+    /// Position of a frame captured at `capture_ns`, or `None` when it was
+    /// captured while paused or before the first frame.
+    pub fn position_ns(&mut self, capture_ns: u64) -> Option<u64> {
+        if self.is_paused_at(capture_ns) {
+            return None;
+        }
+        let origin = *self.origin_ns.get_or_insert(capture_ns);
+        if capture_ns < origin {
+            return None;
+        }
+        Some(capture_ns - origin - self.paused_between(origin, capture_ns))
+    }
+
+    /// Whether a frame has fixed t=0 yet.
+    pub fn has_origin(&self) -> bool {
+        self.origin_ns.is_some()
+    }
+
+    /// Position of any capture-clock instant, for sources other than video.
+    /// Negative before the first frame; `None` while paused or before the
+    /// origin is known. Unlike [`Self::position_ns`] it never sets the origin.
+    pub fn offset_ns(&self, capture_ns: u64) -> Option<i64> {
+        let origin = self.origin_ns?;
+        if self.is_paused_at(capture_ns) {
+            return None;
+        }
+        if capture_ns < origin {
+            return Some(-((origin - capture_ns) as i64));
+        }
+        Some((capture_ns - origin - self.paused_between(origin, capture_ns)) as i64)
+    }
+
+    /// Live time between the first frame and `end_ns`.
+    pub fn duration_ns(&self, end_ns: u64) -> u64 {
+        match self.origin_ns {
+            Some(origin) if end_ns > origin => {
+                end_ns - origin - self.paused_between(origin, end_ns)
+            }
+            _ => 0,
+        }
+    }
+
+    fn is_paused_at(&self, capture_ns: u64) -> bool {
+        self.pauses.iter().any(|&(start, end)| {
+            capture_ns >= start && end.map(|end| capture_ns < end).unwrap_or(true)
+        })
+    }
+
+    fn paused_between(&self, from_ns: u64, to_ns: u64) -> u64 {
+        self.pauses
+            .iter()
+            .map(|&(start, end)| {
+                let start = start.max(from_ns);
+                let end = end.unwrap_or(to_ns).min(to_ns);
+                end.saturating_sub(start)
+            })
+            .sum()
+    }
+}
+
+/// Feeds the encoder timestamped frames through [`RawVideoMkvWriter`].
+pub struct TimelineVideoWriter<W: Write> {
+    mkv: RawVideoMkvWriter<W>,
+    timeline: Arc<Mutex<VideoTimeline>>,
+    frame_interval_ms: u64,
+    last_ms: Option<u64>,
+    /// Kept so a screen that stops changing before the end is still shown
+    /// until the end; ScreenCaptureKit sends nothing for idle frames.
+    last_frame: Vec<u8>,
+}
+
+impl<W: Write> TimelineVideoWriter<W> {
+    pub fn new(
+        out: W,
+        width: u32,
+        height: u32,
+        fps: u32,
+        timeline: Arc<Mutex<VideoTimeline>>,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            mkv: RawVideoMkvWriter::new(out, width, height)?,
+            timeline,
+            frame_interval_ms: 1_000 / u64::from(fps.max(1)),
+            last_ms: None,
+            last_frame: Vec::new(),
+        })
+    }
+
+    /// Returns `Ok(false)` when the frame was dropped: captured while paused,
+    /// or within the same millisecond as the previous one.
+    pub fn write_frame(&mut self, capture_ns: u64, pixels: &[u8]) -> io::Result<bool> {
+        let Some(position_ns) = self.timeline.lock().unwrap().position_ns(capture_ns) else {
+            return Ok(false);
+        };
+        let timestamp_ms = position_ns / TIMESTAMP_SCALE_NS;
+        if self.last_ms.is_some_and(|last| timestamp_ms <= last) {
+            return Ok(false);
+        }
+
+        self.mkv.write_frame(timestamp_ms, pixels)?;
+        self.last_ms = Some(timestamp_ms);
+        self.last_frame.clear();
+        self.last_frame.extend_from_slice(pixels);
+        Ok(true)
+    }
+
+    /// Repeats the last frame near `end_ns` if the screen went quiet, flushes,
+    /// and returns the video duration. Dropping the writer afterwards closes
+    /// the encoder input.
+    pub fn finish(&mut self, end_ns: u64) -> io::Result<Duration> {
+        let duration_ns = self.timeline.lock().unwrap().duration_ns(end_ns);
+        let duration_ms = duration_ns / TIMESTAMP_SCALE_NS;
+        if let Some(last_ms) = self.last_ms {
+            let hold_ms = duration_ms.saturating_sub(self.frame_interval_ms);
+            if hold_ms > last_ms + self.frame_interval_ms {
+                self.mkv.write_frame(hold_ms, &self.last_frame)?;
+                self.last_ms = Some(hold_ms);
+            }
+        }
+        self.mkv.flush()?;
+        Ok(Duration::from_nanos(duration_ns))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TimelineVideoWriter, VideoTimeline};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    const MS: u64 = 1_000_000;
+
+    #[test]
+    fn cuts_paused_spans_out_of_the_timeline() {
+        let mut timeline = VideoTimeline::new();
+        timeline.set_paused(true, 5 * MS);
+        timeline.set_paused(false, 7 * MS);
+        assert_eq!(timeline.position_ns(10 * MS), Some(0));
+        assert_eq!(timeline.position_ns(43 * MS), Some(33 * MS));
+
+        timeline.set_paused(true, 50 * MS);
+        timeline.set_paused(true, 60 * MS);
+        assert_eq!(timeline.position_ns(49 * MS), Some(39 * MS));
+        assert_eq!(timeline.position_ns(500 * MS), None);
+        timeline.set_paused(false, 1_050 * MS);
+        assert_eq!(timeline.position_ns(1_060 * MS), Some(50 * MS));
+        assert_eq!(timeline.duration_ns(2_050 * MS), 1_040 * MS);
+    }
+
+    #[test]
+    fn writer_drops_paused_frames_and_holds_the_last_one() {
+        let timeline = Arc::new(Mutex::new(VideoTimeline::new()));
+        let mut out = Vec::new();
+        let mut writer =
+            TimelineVideoWriter::new(&mut out, 1, 1, 30, timeline.clone()).expect("writer");
+
+        assert!(writer
+            .write_frame(1_000 * MS, &[1, 1, 1, 1])
+            .expect("write"));
+        assert!(!writer
+            .write_frame(1_000 * MS, &[2, 2, 2, 2])
+            .expect("same ms"));
+        assert!(writer
+            .write_frame(1_033 * MS, &[3, 3, 3, 3])
+            .expect("write"));
+        timeline.lock().unwrap().set_paused(true, 1_050 * MS);
+        assert!(!writer
+            .write_frame(1_066 * MS, &[4, 4, 4, 4])
+            .expect("paused"));
+        timeline.lock().unwrap().set_paused(false, 2_050 * MS);
+
+        // Screen idle from 1033 ms until stop at 3050 ms, one second of it paused.
+        let duration = writer.finish(3_050 * MS).expect("finish");
+        assert_eq!(duration, Duration::from_millis(1_050));
+        assert_eq!(writer.last_ms, Some(1_017));
+        drop(writer);
+
+        let clusters = out
+            .windows(4)
+            .filter(|w| w == &[0x1F, 0x43, 0xB6, 0x75])
+            .count();
+        assert_eq!(clusters, 3);
+        assert_eq!(&out[out.len() - 4..], &[3, 3, 3, 3]);
+    }
+}
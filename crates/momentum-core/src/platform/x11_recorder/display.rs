@@ -0,0 +1,127 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::capture_target::CaptureTarget;
+use crate::error::{CoreError, CoreResult};
+
+/// `x11grab` input options for `target` on `display` (the `DISPLAY` value),
+/// with the size of the frames they produce.
+pub(super) fn grab_input(
+    ffmpeg_path: &Path,
+    display: &str,
+    target: Option<CaptureTarget>,
+) -> CoreResult<(Vec<String>, u32, u32)> {
+    match target {
+        None => {
+            let (width, height) = probe_grab_size(ffmpeg_path, &[], display)?;
+            Ok((video_size_input(display, width, height), width, height))
+        }
+        Some(CaptureTarget::Display(screen)) => {
+            let display = screen_display(display, screen);
+            let (width, height) = probe_grab_size(ffmpeg_path, &[], &display)?;
+            Ok((video_size_input(&display, width, height), width, height))
+        }
+        Some(CaptureTarget::Window(id)) => {
+            // x11grab sizes the capture to the window itself
+            let window_args = vec!["-window_id".to_string(), format!("0x{:x}", id)];
+            let (width, height) = probe_grab_size(ffmpeg_path, &window_args, display)?;
+            let mut args = window_args;
+            args.extend(["-i".to_string(), display.to_string()]);
+            Ok((args, width, height))
+        }
+        Some(CaptureTarget::Region {
+            display: screen,
+            rect,
+        }) => {
+            // X11 screens have no separate point space: regions are already
+            // in the pixels x11grab takes.
+            let display = screen_display(display, screen);
+            let (width, height) = probe_grab_size(ffmpeg_path, &[], &display)?;
+            let rect = rect.clip_to(width, height).ok_or_else(|| {
+                CoreError::Recording(format!(
+                    "Capture region is outside display {} ({}x{})",
+                    display, width, height
+                ))
+            })?;
+            let origin = format!("{}+{},{}", display, rect.x, rect.y);
+            Ok((
+                video_size_input(&origin, rect.width, rect.height),
+                rect.width,
+                rect.height,
+            ))
+        }
+    }
+}
+
+fn video_size_input(input: &str, width: u32, height: u32) -> Vec<String> {
+    vec![
+        "-video_size".to_string(),
+        format!("{}x{}", width, height),
+        "-i".to_string(),
+        input.to_string(),
+    ]
+}
+
+/// `display` with its screen number replaced by `screen`: `:0` -> `:0.1`.
+fn screen_display(display: &str, screen: u32) -> String {
+    let (host, number) = display.rsplit_once(':').unwrap_or(("", display));
+    let number = number.split('.').next().unwrap_or(number);
+    format!("{}:{}.{}", host, number, screen)
+}
+
+/// Grabs a single frame to learn the size of what `args` capture from
+/// `display`.
+fn probe_grab_size(ffmpeg_path: &Path, args: &[String], display: &str) -> CoreResult<(u32, u32)> {
+    let output = Command::new(ffmpeg_path)
+        .args(["-hide_banner", "-f", "x11grab"])
+        .args(args)
+        .args(["-i", display, "-frames:v", "1", "-f", "null", "-"])
+        .output()
+        .map_err(|e| CoreError::Recording(format!("Failed to probe X11 display: {}", e)))?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    parse_video_size(&stderr).ok_or_else(|| {
+        CoreError::Recording(format!(
+            "Could not open X11 display {}: {}",
+            display,
+            stderr.trim()
+        ))
+    })
+}
+
+/// Extracts `WIDTHxHEIGHT` from the first video stream line of FFmpeg's log.
+fn parse_video_size(ffmpeg_log: &str) -> Option<(u32, u32)> {
+    ffmpeg_log
+        .lines()
+        .filter(|line| line.contains("Stream #") && line.contains("Video:"))
+        .flat_map(|line| line.split(", "))
+        .find_map(|segment| {
+            let token = segment.split_whitespace().next()?;
+            let (w, h) = token.split_once('x')?;
+            Some((w.parse().ok()?, h.parse().ok()?))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_video_size, screen_display};
+
+    #[test]
+    fn parses_x11grab_stream_size() {
+        let log = "Input #0, x11grab, from ':99':\n  Duration: N/A, start: 1700000000.000000, bitrate: 1990656 kb/s\n  Stream #0:0: Video: rawvideo (BGR[0] / 0x30524742), bgr0, 1920x1080, 1990656 kb/s, 29.97 fps, 1000k tbr, 1000k tbn\n";
+        assert_eq!(parse_video_size(log), Some((1920, 1080)));
+    }
+
+    #[test]
+    fn replaces_the_screen_number() {
+        assert_eq!(screen_display(":0", 1), ":0.1");
+        assert_eq!(screen_display(":1.0", 2), ":1.2");
+        assert_eq!(screen_display("host:10.0", 0), "host:10.0");
+    }
+
+    #[test]
+    fn ignores_logs_without_video_stream() {
+        let log = "[x11grab @ 0x5581] Cannot open display :42, error 1.\n:42: Input/output error\n";
+        assert_eq!(parse_video_size(log), None);
+    }
+}
@@ -0,0 +1,135 @@
+mod display;
+mod targets;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::error::CoreResult;
+use crate::models::RecordingOptions;
+use crate::platform::capture_backend::{AudioDeviceIds, CaptureBackend, CaptureRequest};
+use crate::platform::ffmpeg_capabilities::FfmpegRequirements;
+use crate::platform::ffmpeg_pipeline::{FfmpegPipeline, PipelineSources, VideoSource};
+use crate::platform::linux::pulse;
+use crate::platform::macos::ffmpeg::FfmpegLocator;
+use crate::platform::pcm_capture::AudioSource;
+
+pub use targets::list_capture_targets;
+
+/// Linux capture backend: the X11 display through FFmpeg `x11grab`, with
+/// system audio and microphone recorded from Pulse sources.
+pub struct X11Recorder {
+    pipeline: FfmpegPipeline,
+    ffmpeg_locator: Arc<FfmpegLocator>,
+}
+
+impl X11Recorder {
+    pub fn new(ffmpeg_locator: Arc<FfmpegLocator>) -> Self {
+        Self {
+            pipeline: FfmpegPipeline::new("X11"),
+            ffmpeg_locator,
+        }
+    }
+}
+
+/// System audio and, if requested, the mic from the Pulse sources, with the
+/// latency configured for each.
+fn audio_sources(options: &RecordingOptions) -> (AudioSource, Option<AudioSource>) {
+    let sources = pulse::list_sources().unwrap_or_else(|err| {
+        eprintln!("[X11] Failed to enumerate Pulse sources: {}", err);
+        Vec::new()
+    });
+    let system_audio = pulse::audio_source(&pulse::resolve_system_audio_source(&sources))
+        .with_offsets_from(options);
+    let mic = options.include_microphone.then(|| {
+        pulse::audio_source(&pulse::resolve_mic_source(&sources)).with_offsets_from(options)
+    });
+    (system_audio, mic)
+}
+
+/// `x11grab` options ahead of those [`display::grab_input`] picks.
+fn x11grab_args(fps: u32) -> Vec<String> {
+    vec![
+        "-f".to_string(),
+        "x11grab".to_string(),
+        "-framerate".to_string(),
+        fps.to_string(),
+    ]
+}
+
+impl CaptureBackend for X11Recorder {
+    fn start(&self, request: CaptureRequest<'_>) -> CoreResult<()> {
+        let ffmpeg_path = self.ffmpeg_locator.resolve()?;
+
+        let display = std::env::var("DISPLAY").unwrap_or_else(|_| ":0".to_string());
+        let target = request.options.capture_target()?;
+        let fps = request.options.frame_rate.fps();
+        let (grab_args, width, height) = display::grab_input(&ffmpeg_path, &display, target)?;
+        match target {
+            Some(target) => println!(
+                "[X11] Capturing {} on {} ({}x{})",
+                target, display, width, height
+            ),
+            None => println!("[X11] Capturing display {} ({}x{})", display, width, height),
+        }
+
+        let (system_audio, mic) = audio_sources(request.options);
+
+        let mut input_args = x11grab_args(fps);
+        input_args.extend(grab_args);
+        let video = VideoSource {
+            input_args,
+            width,
+            height,
+            fps,
+        };
+
+        self.pipeline.start(
+            &ffmpeg_path,
+            PipelineSources {
+                video,
+                system_audio,
+                mic,
+            },
+            request,
+        )
+    }
+
+    fn stop(&self) -> CoreResult<PathBuf> {
+        self.pipeline.stop()
+    }
+
+    fn set_recording_paused(&self, paused: bool) {
+        self.pipeline.set_recording_paused(paused);
+    }
+
+    fn set_mic_muted(&self, muted: bool) {
+        self.pipeline.set_mic_muted(muted);
+    }
+
+    fn set_system_audio_muted(&self, muted: bool) {
+        self.pipeline.set_system_audio_muted(muted);
+    }
+
+    fn is_active(&self) -> bool {
+        self.pipeline.is_active()
+    }
+
+    fn audio_device_ids(&self, options: &RecordingOptions) -> CoreResult<AudioDeviceIds> {
+        let (system_audio, mic) = audio_sources(options);
+        Ok(AudioDeviceIds {
+            system_audio: system_audio.device_id,
+            mic: mic.map(|mic| mic.device_id),
+        })
+    }
+
+    fn check_ffmpeg(&self, options: &RecordingOptions) -> CoreResult<()> {
+        let mut requirements = FfmpegRequirements::for_options(options);
+        requirements.add_input(&x11grab_args(options.frame_rate.fps()));
+        let (system_audio, mic) = audio_sources(options);
+        system_audio.add_requirements(&mut requirements);
+        if let Some(mic) = mic {
+            mic.add_requirements(&mut requirements);
+        }
+        self.ffmpeg_locator.capabilities()?.check(&requirements)
+    }
+}
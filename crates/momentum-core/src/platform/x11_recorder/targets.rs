@@ -0,0 +1,219 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use base64::{engine::general_purpose, Engine as _};
+
+use crate::capture_target::{thumbnail_size, CaptureTarget, CaptureTargetInfo, TargetBounds};
+use crate::error::{CoreError, CoreResult};
+use crate::platform::macos::ffmpeg::FfmpegLocator;
+
+use super::display;
+
+/// The X screen of `DISPLAY` and the named client windows on it, with
+/// thumbnails grabbed through `x11grab`. Windows come from `xwininfo`; without
+/// it only the screen is listed.
+pub fn list_capture_targets(ffmpeg_locator: &FfmpegLocator) -> CoreResult<Vec<CaptureTargetInfo>> {
+    let ffmpeg_path = ffmpeg_locator.resolve()?;
+    let display = std::env::var("DISPLAY").map_err(|_| {
+        CoreError::Recording("DISPLAY is not set, screen capture is unavailable".to_string())
+    })?;
+
+    let screen = screen_number(&display);
+    let (grab_args, width, height) =
+        display::grab_input(&ffmpeg_path, &display, Some(CaptureTarget::Display(screen)))?;
+    let mut screen_info = CaptureTargetInfo::new(
+        CaptureTarget::Display(screen),
+        format!("X11 display {}", display),
+        None,
+        TargetBounds {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        },
+    );
+    screen_info.thumbnail_base64 = grab_thumbnail(&ffmpeg_path, &grab_args, width, height);
+    let mut targets = vec![screen_info];
+
+    let windows = match list_windows() {
+        Ok(windows) => windows,
+        Err(err) => {
+            eprintln!("[X11] Failed to list windows: {}", err);
+            Vec::new()
+        }
+    };
+    for window in windows {
+        let grab_args = [
+            "-window_id".to_string(),
+            format!("0x{:x}", window.id),
+            "-i".to_string(),
+            display.clone(),
+        ];
+        let mut info = CaptureTargetInfo::new(
+            CaptureTarget::Window(window.id),
+            window.title,
+            Some(window.class),
+            window.bounds,
+        );
+        info.thumbnail_base64 = grab_thumbnail(
+            &ffmpeg_path,
+            &grab_args,
+            window.bounds.width,
+            window.bounds.height,
+        );
+        targets.push(info);
+    }
+    Ok(targets)
+}
+
+/// Screen number of a `DISPLAY` value: `:0.1` -> 1, `:0` -> 0.
+fn screen_number(display: &str) -> u32 {
+    display
+        .rsplit_once(':')
+        .and_then(|(_, number)| number.split_once('.'))
+        .and_then(|(_, screen)| screen.parse().ok())
+        .unwrap_or(0)
+}
+
+#[derive(Debug, PartialEq)]
+struct X11Window {
+    id: u32,
+    title: String,
+    /// `WM_CLASS` class name.
+    class: String,
+    bounds: TargetBounds,
+}
+
+fn list_windows() -> CoreResult<Vec<X11Window>> {
+    let output = Command::new("xwininfo")
+        .args(["-root", "-tree"])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| CoreError::Recording(format!("Failed to run xwininfo: {}", e)))?;
+    if !output.status.success() {
+        return Err(CoreError::Recording(format!(
+            "xwininfo failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(parse_window_tree(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Windows from `xwininfo -root -tree` that have a title and a `WM_CLASS`,
+/// which leaves out frames, tooltips and other helper windows.
+fn parse_window_tree(tree: &str) -> Vec<X11Window> {
+    tree.lines().filter_map(parse_window_line).collect()
+}
+
+/// `0x2c00003 "Title": ("instance" "Class")  1280x720+10+40  +10+40`
+fn parse_window_line(line: &str) -> Option<X11Window> {
+    let line = line.trim();
+    let (id, rest) = line.split_once(' ')?;
+    let id = u32::from_str_radix(id.strip_prefix("0x")?, 16).ok()?;
+
+    let title_end = rest.rfind("\": (")?;
+    let title = rest.strip_prefix('"')?.get(..title_end - 1)?.to_string();
+    let rest = &rest[title_end + 4..];
+    let (class_part, geometry) = rest.split_once(')')?;
+    let class = class_part.split('"').nth(3)?.to_string();
+
+    let mut geometry = geometry.split_whitespace();
+    let size = geometry.next()?;
+    let absolute = geometry.next()?;
+    let (width, height) = size.split_once('+')?.0.split_once('x')?;
+    let (x, y) = absolute.strip_prefix('+')?.split_once('+')?;
+    let bounds = TargetBounds {
+        x: x.parse().ok()?,
+        y: y.parse().ok()?,
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+    };
+
+    let usable = !title.is_empty() && bounds.width > 1 && bounds.height > 1;
+    usable.then_some(X11Window {
+        id,
+        title,
+        class,
+        bounds,
+    })
+}
+
+/// One frame of the x11grab input in `grab_args`, scaled down to a PNG.
+fn grab_thumbnail(
+    ffmpeg_path: &Path,
+    grab_args: &[String],
+    width: u32,
+    height: u32,
+) -> Option<String> {
+    let (thumb_width, thumb_height) = thumbnail_size(width, height);
+    let output = Command::new(ffmpeg_path)
+        .args(["-hide_banner", "-loglevel", "error", "-f", "x11grab"])
+        .args(grab_args)
+        .args([
+            "-frames:v",
+            "1",
+            "-vf",
+            &format!("scale={}:{}", thumb_width, thumb_height),
+            "-f",
+            "image2pipe",
+            "-c:v",
+            "png",
+            "-",
+        ])
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    (output.status.success() && !output.stdout.is_empty())
+        .then(|| general_purpose::STANDARD.encode(&output.stdout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_window_tree, screen_number, X11Window};
+    use crate::capture_target::TargetBounds;
+
+    #[test]
+    fn parses_named_client_windows() {
+        let tree = r#"
+xwininfo: Window id: 0x1e3 (the root window) (has no name)
+
+  Root window id: 0x1e3 (the root window) (has no name)
+  Parent window id: 0x0 (none)
+     3 children:
+     0x1200001 "xterm": ("xterm" "XTerm")  484x316+0+0  +0+0
+        1 child:
+        0x1200002 (has no name): ()  1x1+-1+-1  +-1+-1
+     0x2c00003 "Notes: "draft": v2": ("notes" "Notes")  1280x720+10+40  +12+64
+     0x1000001 "tooltip": ("tip" "Tip")  1x1+0+0  +0+0
+"#;
+        assert_eq!(
+            parse_window_tree(tree),
+            vec![
+                X11Window {
+                    id: 0x1200001,
+                    title: "xterm".to_string(),
+                    class: "XTerm".to_string(),
+                    bounds: TargetBounds {
+                        x: 0,
+                        y: 0,
+                        width: 484,
+                        height: 316,
+                    },
+                },
+                X11Window {
+                    id: 0x2c00003,
+                    title: "Notes: \"draft\": v2".to_string(),
+                    class: "Notes".to_string(),
+                    bounds: TargetBounds {
+                        x: 12,
+                        y: 64,
+                        width: 1280,
+                        height: 720,
+                    },
+                },
+            ]
+        );
+        assert_eq!(screen_number(":0"), 0);
+        assert_eq!(screen_number("host:1.2"), 2);
+    }
+}
@@ -0,0 +1,487 @@
+use crate::error::{CoreError, CoreResult};
+use crate::models::RecordingOptions;
+use crate::camera::CameraSyncHandle;
+use crate::platform::{AudioDeviceIds, CaptureBackend, CaptureRequest, DefaultCaptureBackend};
+use crate::events::{CoreEvent, EventSink};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingStartInfo {
+    pub started_at_ms: u64,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingPausedInfo {
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingResumedInfo {
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingStoppedInfo {
+    pub elapsed_ms: u64,
+}
+
+pub struct RecordingStopResult {
+    pub elapsed_ms: u64,
+    pub output_path: PathBuf,
+}
+
+#[derive(Default)]
+struct RecorderState {
+    is_recording: bool,
+    is_paused: bool,
+    output_file: Option<PathBuf>,
+    include_microphone: bool,
+    include_camera: bool,
+    elapsed_task: Option<JoinHandle<()>>,
+    elapsed_cancel: Option<mpsc::Sender<()>>,
+}
+
+#[derive(Debug, Default)]
+struct RecordingClock {
+    start_instant: Option<Instant>,
+    accumulated: Duration,
+    running: bool,
+}
+
+impl RecordingClock {
+    fn start(&mut self) {
+        self.start_instant = Some(Instant::now());
+        self.accumulated = Duration::ZERO;
+        self.running = true;
+    }
+
+    fn pause(&mut self) {
+        if self.running {
+            if let Some(start) = self.start_instant.take() {
+                self.accumulated += start.elapsed();
+            }
+            self.running = false;
+        }
+    }
+
+    fn resume(&mut self) {
+        if !self.running {
+            self.start_instant = Some(Instant::now());
+            self.running = true;
+        }
+    }
+
+    fn stop(&mut self) {
+        self.start_instant = None;
+        self.accumulated = Duration::ZERO;
+        self.running = false;
+    }
+
+    fn elapsed(&self) -> Duration {
+        if self.running {
+            if let Some(start) = self.start_instant {
+                return self.accumulated + start.elapsed();
+            }
+        }
+        self.accumulated
+    }
+}
+
+pub struct Recorder<B: CaptureBackend = DefaultCaptureBackend> {
+    state: Arc<Mutex<RecorderState>>,
+    clock: Arc<Mutex<RecordingClock>>,
+    backend: Arc<B>,
+    camera_sync: Arc<CameraSyncHandle>,
+}
+
+impl<B: CaptureBackend> Clone for Recorder<B> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            clock: self.clock.clone(),
+            backend: self.backend.clone(),
+            camera_sync: self.camera_sync.clone(),
+        }
+    }
+}
+
+impl<B: CaptureBackend> Recorder<B> {
+    pub fn new(backend: Arc<B>, camera_sync: Arc<CameraSyncHandle>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(RecorderState::default())),
+            clock: Arc::new(Mutex::new(RecordingClock::default())),
+            backend,
+            camera_sync,
+        }
+    }
+
+    pub fn start(&self, options: RecordingOptions) -> CoreResult<RecordingStartInfo> {
+        let output_file = self.build_output_path();
+
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.is_recording || self.backend.is_active() {
+                return Err(CoreError::Recording("Recording already in progress".to_string()));
+            }
+            // Backends parse it again; a malformed target or encoder profile,
+            // or an FFmpeg build missing what the session needs, fails before
+            // any state changes, whichever backend is selected
+            options.capture_target()?;
+            options.encoder.validate()?;
+            self.backend.check_ffmpeg(&options)?;
+            state.is_recording = true;
+            state.is_paused = false;
+            state.output_file = Some(output_file.clone());
+            state.include_microphone = options.include_microphone;
+            state.include_camera = options.include_camera;
+        }
+
+        let camera_sync = if options.include_camera {
+            self.camera_sync.apply_device_offsets(&options);
+            self.camera_sync.set_frame_rate(options.frame_rate);
+            Some(self.camera_sync.clone())
+        } else {
+            None
+        };
+
+        match self.backend.start(CaptureRequest {
+            output_path: &output_file,
+            options: &options,
+            camera_sync,
+        }) {
+            Ok(_) => {
+                if options.include_camera {
+                    self.camera_sync.set_sync_enabled(true);
+                }
+                self.clock.lock().unwrap().start();
+                Ok(RecordingStartInfo {
+                    started_at_ms: current_time_ms(),
+                    elapsed_ms: 0,
+                })
+            }
+            Err(err) => {
+                let mut state = self.state.lock().unwrap();
+                state.is_recording = false;
+                state.output_file = None;
+                Err(err)
+            }
+        }
+    }
+
+    pub fn pause(&self) -> CoreResult<RecordingPausedInfo> {
+        let mut state = self.state.lock().unwrap();
+        if !state.is_recording {
+            return Err(CoreError::Recording("No recording in progress".to_string()));
+        }
+        if state.is_paused {
+            return Err(CoreError::Recording("Recording already paused".to_string()));
+        }
+
+        state.is_paused = true;
+        self.backend.set_recording_paused(true);
+        self.clock.lock().unwrap().pause();
+        Ok(RecordingPausedInfo {
+            elapsed_ms: self.elapsed_ms(),
+        })
+    }
+
+    pub fn resume(&self) -> CoreResult<RecordingResumedInfo> {
+        let mut state = self.state.lock().unwrap();
+        if !state.is_recording {
+            return Err(CoreError::Recording("No recording in progress".to_string()));
+        }
+        if !state.is_paused {
+            return Err(CoreError::Recording("Recording is not paused".to_string()));
+        }
+
+        state.is_paused = false;
+        self.backend.set_recording_paused(false);
+        self.clock.lock().unwrap().resume();
+        Ok(RecordingResumedInfo {
+            elapsed_ms: self.elapsed_ms(),
+        })
+    }
+
+    pub fn stop(&self) -> CoreResult<RecordingStopResult> {
+        if !self.state.lock().unwrap().is_recording {
+            return Err(CoreError::Recording("No recording in progress".to_string()));
+        }
+
+        let output_path = self.backend.stop()?;
+        self.camera_sync.set_sync_enabled(false);
+        self.stop_elapsed_task();
+
+        let elapsed_ms = self.elapsed_ms();
+
+        let mut state = self.state.lock().unwrap();
+        state.is_recording = false;
+        state.is_paused = false;
+        state.output_file = None;
+        state.include_microphone = false;
+        state.include_camera = false;
+
+        self.clock.lock().unwrap().stop();
+
+        Ok(RecordingStopResult {
+            elapsed_ms,
+            output_path,
+        })
+    }
+
+    /// The audio devices (see [`DeviceOffsets`](crate::models::DeviceOffsets))
+    /// a session started with `options` would record.
+    pub fn audio_device_ids(&self, options: &RecordingOptions) -> CoreResult<AudioDeviceIds> {
+        self.backend.audio_device_ids(options)
+    }
+
+    pub fn set_mic_muted(&self, muted: bool) {
+        self.backend.set_mic_muted(muted);
+    }
+
+    pub fn set_system_audio_muted(&self, muted: bool) {
+        self.backend.set_system_audio_muted(muted);
+    }
+
+    pub fn elapsed_ms(&self) -> u64 {
+        self.clock
+            .lock()
+            .unwrap()
+            .elapsed()
+            .as_millis()
+            .min(u128::from(u64::MAX)) as u64
+    }
+
+    /// Reports the elapsed time to `sink` immediately and then once a second
+    /// until [`stop_elapsed_task`](Self::stop_elapsed_task) is called.
+    pub fn start_elapsed_task(&self, sink: Arc<dyn EventSink>) {
+        let mut state = self.state.lock().unwrap();
+        if state.elapsed_task.is_some() {
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel::<()>();
+        let clock = self.clock.clone();
+
+        let handle = thread::spawn(move || loop {
+            sink.emit(CoreEvent::RecordingElapsed {
+                elapsed_ms: elapsed_ms_from_clock(&clock),
+            });
+            // Dropping the sender (or sending) ends the task.
+            if rx.recv_timeout(Duration::from_secs(1)) != Err(RecvTimeoutError::Timeout) {
+                break;
+            }
+        });
+
+        state.elapsed_cancel = Some(tx);
+        state.elapsed_task = Some(handle);
+    }
+
+    pub fn stop_elapsed_task(&self) {
+        let (cancel, handle) = {
+            let mut state = self.state.lock().unwrap();
+            (state.elapsed_cancel.take(), state.elapsed_task.take())
+        };
+        drop(cancel);
+        if let Some(handle) = handle {
+            let _ = handle.join();
+        }
+    }
+
+    fn build_output_path(&self) -> PathBuf {
+        let temp_dir = std::env::temp_dir();
+        let recording_id = Uuid::new_v4();
+        temp_dir.join(format!("momentum_screen_{}.mp4", recording_id))
+    }
+}
+
+fn elapsed_ms_from_clock(clock: &Arc<Mutex<RecordingClock>>) -> u64 {
+    clock
+        .lock()
+        .unwrap()
+        .elapsed()
+        .as_millis()
+        .min(u128::from(u64::MAX)) as u64
+}
+
+fn current_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_else(|_| Duration::from_secs(0))
+        .as_millis()
+        .min(u128::from(u64::MAX)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Recorder, RecordingClock};
+    use crate::error::{CoreError, CoreResult};
+    use crate::models::RecordingOptions;
+    use crate::camera::CameraSyncHandle;
+    use crate::platform::{AudioDeviceIds, CaptureBackend, CaptureRequest};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct FakeBackend {
+        output_path: Mutex<Option<PathBuf>>,
+        paused: AtomicBool,
+        mic_muted: AtomicBool,
+        fail_start: bool,
+        ffmpeg_missing: bool,
+    }
+
+    impl CaptureBackend for FakeBackend {
+        fn start(&self, request: CaptureRequest<'_>) -> CoreResult<()> {
+            if self.fail_start {
+                return Err(CoreError::Recording("fake start failure".to_string()));
+            }
+            *self.output_path.lock().unwrap() = Some(request.output_path.to_path_buf());
+            Ok(())
+        }
+
+        fn stop(&self) -> CoreResult<PathBuf> {
+            self.output_path
+                .lock()
+                .unwrap()
+                .take()
+                .ok_or_else(|| CoreError::Recording("fake backend not started".to_string()))
+        }
+
+        fn set_recording_paused(&self, paused: bool) {
+            self.paused.store(paused, Ordering::Relaxed);
+        }
+
+        fn set_mic_muted(&self, muted: bool) {
+            self.mic_muted.store(muted, Ordering::Relaxed);
+        }
+
+        fn set_system_audio_muted(&self, _muted: bool) {}
+
+        fn is_active(&self) -> bool {
+            self.output_path.lock().unwrap().is_some()
+        }
+
+        fn audio_device_ids(&self, options: &RecordingOptions) -> CoreResult<AudioDeviceIds> {
+            Ok(AudioDeviceIds {
+                system_audio: "fake:system-audio".to_string(),
+                mic: options.include_microphone.then(|| "fake:mic".to_string()),
+            })
+        }
+
+        fn check_ffmpeg(&self, _options: &RecordingOptions) -> CoreResult<()> {
+            if self.ffmpeg_missing {
+                return Err(CoreError::Recording("fake FFmpeg lacks libx264".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    fn options() -> RecordingOptions {
+        RecordingOptions {
+            include_microphone: true,
+            ..RecordingOptions::default()
+        }
+    }
+
+    fn recorder(backend: FakeBackend) -> (Recorder<FakeBackend>, Arc<FakeBackend>) {
+        let backend = Arc::new(backend);
+        let recorder = Recorder::new(backend.clone(), Arc::new(CameraSyncHandle::new()));
+        (recorder, backend)
+    }
+
+    #[test]
+    fn recorder_drives_backend_through_session() {
+        let (recorder, backend) = recorder(FakeBackend::default());
+
+        recorder.start(options()).expect("start");
+        assert!(backend.is_active());
+        // A second start is refused as a duplicate before its options are checked
+        let bad_target = RecordingOptions {
+            screen_target: Some("bogus".to_string()),
+            ..options()
+        };
+        let err = recorder.start(bad_target).expect_err("second start");
+        assert!(err.to_string().contains("already in progress"));
+
+        recorder.pause().expect("pause");
+        assert!(backend.paused.load(Ordering::Relaxed));
+        assert!(recorder.pause().is_err());
+
+        recorder.resume().expect("resume");
+        assert!(!backend.paused.load(Ordering::Relaxed));
+
+        recorder.set_mic_muted(true);
+        assert!(backend.mic_muted.load(Ordering::Relaxed));
+
+        let result = recorder.stop().expect("stop");
+        assert!(result.output_path.starts_with(std::env::temp_dir()));
+        assert!(!backend.is_active());
+        assert!(recorder.stop().is_err());
+    }
+
+    #[test]
+    fn recorder_resets_state_when_backend_fails_to_start() {
+        // Missing FFmpeg components fail before the backend is started
+        let (without_ffmpeg, backend) = recorder(FakeBackend {
+            ffmpeg_missing: true,
+            ..FakeBackend::default()
+        });
+        assert!(without_ffmpeg.start(options()).is_err());
+        assert!(!backend.is_active());
+        assert!(without_ffmpeg.stop().is_err());
+
+        let (recorder, _backend) = recorder(FakeBackend {
+            fail_start: true,
+            ..FakeBackend::default()
+        });
+
+        assert!(recorder.start(options()).is_err());
+        assert!(recorder.pause().is_err());
+        assert!(recorder.stop().is_err());
+    }
+
+    #[test]
+    fn elapsed_task_reports_to_sink_until_stopped() {
+        let (recorder, _backend) = recorder(FakeBackend::default());
+        let (tx, rx) = mpsc::channel();
+        recorder.start(options()).expect("start");
+        recorder.start_elapsed_task(Arc::new(tx));
+
+        let event = rx.recv_timeout(Duration::from_secs(1)).expect("first tick");
+        assert_eq!(event.name(), "recording-elapsed");
+
+        recorder.stop().expect("stop");
+        while rx.try_recv().is_ok() {}
+        assert!(rx.recv_timeout(Duration::from_millis(1200)).is_err());
+    }
+
+    #[test]
+    fn recording_clock_tracks_pause_and_resume() {
+        let mut clock = RecordingClock::default();
+        clock.start();
+        thread::sleep(Duration::from_millis(10));
+        clock.pause();
+
+        let paused = clock.elapsed();
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(clock.elapsed(), paused);
+
+        clock.resume();
+        thread::sleep(Duration::from_millis(10));
+        assert!(clock.elapsed() > paused);
+    }
+}
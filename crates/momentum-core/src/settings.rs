@@ -0,0 +1,168 @@
+use crate::error::{CoreError, CoreResult};
+use crate::models::AppSettings;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub struct SettingsStore {
+    settings_path: PathBuf,
+}
+
+impl SettingsStore {
+    pub fn new(base_dir: Option<PathBuf>) -> CoreResult<Self> {
+        let base_dir = match base_dir {
+            Some(dir) => dir,
+            None => dirs::config_dir()
+                .ok_or_else(|| CoreError::Settings("Could not find config directory".to_string()))?,
+        };
+        Ok(Self {
+            settings_path: base_dir.join("momentum").join("settings.json"),
+        })
+    }
+
+    pub fn load(&self) -> CoreResult<AppSettings> {
+        if !self.settings_path.exists() {
+            return Ok(AppSettings::default());
+        }
+
+        let content = fs::read_to_string(&self.settings_path)?;
+        let settings: AppSettings = serde_json::from_str(&content)
+            .map_err(|e| CoreError::Settings(format!("Failed to parse settings: {}", e)))?;
+        Ok(settings)
+    }
+
+    pub fn save(&self, settings: &AppSettings) -> CoreResult<()> {
+        if let Some(parent) = self.settings_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(settings)
+            .map_err(|e| CoreError::Settings(format!("Failed to serialize settings: {}", e)))?;
+        fs::write(&self.settings_path, content)?;
+        Ok(())
+    }
+
+    /// Reads one setting by its JSON key, e.g. `micEnabled`.
+    pub fn get_value(&self, key: &str) -> CoreResult<Value> {
+        let settings = to_object(&self.load()?)?;
+        settings
+            .get(key)
+            .cloned()
+            .ok_or_else(|| unknown_key(key, &settings))
+    }
+
+    /// Updates one setting by its JSON key. The value must deserialize into
+    /// the field's type; nothing is written otherwise.
+    pub fn set_value(&self, key: &str, value: Value) -> CoreResult<AppSettings> {
+        let mut settings = to_object(&self.load()?)?;
+        if !settings.contains_key(key) {
+            return Err(unknown_key(key, &settings));
+        }
+        settings.insert(key.to_string(), value);
+        let updated: AppSettings = serde_json::from_value(Value::Object(settings))
+            .map_err(|e| CoreError::Settings(format!("Invalid value for {}: {}", key, e)))?;
+        self.save(&updated)?;
+        Ok(updated)
+    }
+
+    #[cfg(test)]
+    pub fn path(&self) -> &PathBuf {
+        &self.settings_path
+    }
+}
+
+fn to_object(settings: &AppSettings) -> CoreResult<Map<String, Value>> {
+    match serde_json::to_value(settings) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(CoreError::Settings("Settings are not a JSON object".to_string())),
+        Err(e) => Err(CoreError::Settings(format!("Failed to serialize settings: {}", e))),
+    }
+}
+
+fn unknown_key(key: &str, settings: &Map<String, Value>) -> CoreError {
+    let known: Vec<&str> = settings.keys().map(String::as_str).collect();
+    CoreError::Settings(format!(
+        "Unknown setting '{}' (expected one of: {})",
+        key,
+        known.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SettingsStore;
+    use crate::models::{AppSettings, FrameRate, OutputScale};
+    use serde_json::json;
+
+    #[test]
+    fn saves_and_loads_settings() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SettingsStore::new(Some(temp_dir.path().to_path_buf())).expect("store");
+
+        let settings = AppSettings {
+            mic_enabled: true,
+            camera_enabled: true,
+            immersive_shortcut: "Command+Shift+I".to_string(),
+            save_location: Some("/tmp".to_string()),
+            device_offsets: Default::default(),
+            camera_overlay: Default::default(),
+            frame_rate: Default::default(),
+            output_scale: Default::default(),
+            encoder: Default::default(),
+        };
+
+        store.save(&settings).expect("save");
+        let loaded = store.load().expect("load");
+        assert!(loaded.mic_enabled);
+        assert!(loaded.camera_enabled);
+        assert_eq!(loaded.immersive_shortcut, "Command+Shift+I");
+        assert_eq!(loaded.save_location.as_deref(), Some("/tmp"));
+    }
+
+    #[test]
+    fn uses_default_settings_when_missing() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SettingsStore::new(Some(temp_dir.path().to_path_buf())).expect("store");
+        let loaded = store.load().expect("load");
+        assert_eq!(loaded, AppSettings::default());
+    }
+
+    #[test]
+    fn reads_and_writes_single_keys() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SettingsStore::new(Some(temp_dir.path().to_path_buf())).expect("store");
+
+        assert_eq!(store.get_value("micEnabled").expect("get"), json!(false));
+        let updated = store.set_value("micEnabled", json!(true)).expect("set");
+        assert!(updated.mic_enabled);
+        assert!(store.load().expect("load").mic_enabled);
+
+        assert!(store.set_value("micEnabled", json!("loud")).is_err());
+        assert!(store.set_value("notASetting", json!(60)).is_err());
+        assert!(store.get_value("nope").is_err());
+    }
+
+    #[test]
+    fn validates_frame_rate_and_output_scale() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SettingsStore::new(Some(temp_dir.path().to_path_buf())).expect("store");
+
+        assert_eq!(store.get_value("frameRate").expect("get"), json!(30));
+        let updated = store.set_value("frameRate", json!(60)).expect("set");
+        assert_eq!(updated.frame_rate, FrameRate::Fps60);
+        assert!(store.set_value("frameRate", json!(25)).is_err());
+
+        assert_eq!(
+            store.get_value("outputScale").expect("get"),
+            json!("native")
+        );
+        let updated = store.set_value("outputScale", json!("720p")).expect("set");
+        assert_eq!(updated.output_scale, OutputScale::P720);
+        let updated = store
+            .set_value("outputScale", json!({ "width": 1600 }))
+            .expect("set");
+        assert_eq!(updated.output_scale, OutputScale::Width(1600));
+        assert!(store.set_value("outputScale", json!("4k")).is_err());
+    }
+}
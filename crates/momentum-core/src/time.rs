@@ -0,0 +1,245 @@
+//! One clock for every capture producer. Screen frames, camera frames, audio
+//! chunks, pause toggles and first-arrival markers are all stamped in host-time
+//! nanoseconds, so offsets between them are plain subtractions.
+//!
+//! On macOS host time is `mach_absolute_time`, which is also the clock behind
+//! ScreenCaptureKit presentation timestamps ([`cm_time_to_ns`] maps between
+//! them). Elsewhere it is a process-wide monotonic origin.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+pub const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// Source of host-time timestamps. Production code uses [`HostClock`]; tests
+/// drive a [`MockClock`] by hand.
+pub trait MediaClock: Send + Sync {
+    fn now_ns(&self) -> u64;
+}
+
+pub type SharedClock = Arc<dyn MediaClock>;
+
+/// The platform host clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HostClock;
+
+impl MediaClock for HostClock {
+    fn now_ns(&self) -> u64 {
+        host_time_now_ns()
+    }
+}
+
+pub fn host_clock() -> SharedClock {
+    Arc::new(HostClock)
+}
+
+/// A clock that only moves when told to.
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now_ns: AtomicU64,
+}
+
+impl MockClock {
+    pub fn new(start_ns: u64) -> Self {
+        Self {
+            now_ns: AtomicU64::new(start_ns),
+        }
+    }
+
+    pub fn set(&self, now_ns: u64) {
+        self.now_ns.store(now_ns, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.now_ns.fetch_add(duration_to_ns(by), Ordering::Relaxed);
+    }
+}
+
+impl MediaClock for MockClock {
+    fn now_ns(&self) -> u64 {
+        self.now_ns.load(Ordering::Relaxed)
+    }
+}
+
+/// First time a producer delivered data, shared between the producer thread
+/// and whoever computes offsets at stop.
+#[derive(Debug, Default, Clone)]
+pub struct ArrivalMarker(Arc<AtomicU64>);
+
+impl ArrivalMarker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `at_ns` unless an earlier arrival was already recorded.
+    pub fn mark(&self, at_ns: u64) {
+        // 0 means "unset", so a (theoretical) arrival at 0 is stored as 1.
+        let _ = self
+            .0
+            .compare_exchange(0, at_ns.max(1), Ordering::Relaxed, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> Option<u64> {
+        match self.0.load(Ordering::Relaxed) {
+            0 => None,
+            at_ns => Some(at_ns),
+        }
+    }
+
+    /// Milliseconds between `origin_ns` and the arrival, for logs.
+    pub fn millis_since(&self, origin_ns: u64) -> u64 {
+        self.get()
+            .map(|at_ns| at_ns.saturating_sub(origin_ns) / 1_000_000)
+            .unwrap_or(0)
+    }
+}
+
+pub fn duration_to_ns(duration: Duration) -> u64 {
+    duration.as_nanos().min(u128::from(u64::MAX)) as u64
+}
+
+/// Duration of `samples` frames at `sample_rate`.
+pub fn samples_to_ns(samples: u64, sample_rate: u32) -> u64 {
+    if sample_rate == 0 {
+        return 0;
+    }
+    let nanos = u128::from(samples) * u128::from(NANOS_PER_SECOND) / u128::from(sample_rate);
+    nanos.min(u128::from(u64::MAX)) as u64
+}
+
+/// Converts a `value / timescale` rational time (CMTime's representation)
+/// into nanoseconds, clamping negatives to 0 and overflow to `u64::MAX`.
+pub fn rational_to_ns(value: i64, timescale: i32) -> u64 {
+    if timescale == 0 {
+        return 0;
+    }
+    let nanos = i128::from(value)
+        .saturating_mul(i128::from(NANOS_PER_SECOND))
+        .checked_div(i128::from(timescale))
+        .unwrap_or(0);
+    nanos.clamp(0, i128::from(u64::MAX)) as u64
+}
+
+static MONOTONIC_START: OnceLock<Instant> = OnceLock::new();
+
+/// Monotonic nanoseconds since the first call in this process.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+fn monotonic_now_ns() -> u64 {
+    let start = MONOTONIC_START.get_or_init(Instant::now);
+    duration_to_ns(Instant::now().duration_since(*start))
+}
+
+#[cfg(target_os = "macos")]
+mod mac_host_time {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Once;
+
+    #[repr(C)]
+    #[derive(Default, Copy, Clone)]
+    struct MachTimebaseInfo {
+        numer: u32,
+        denom: u32,
+    }
+
+    extern "C" {
+        fn mach_absolute_time() -> u64;
+        fn mach_timebase_info(info: *mut MachTimebaseInfo) -> i32;
+    }
+
+    static INIT: Once = Once::new();
+    static NUMER: AtomicU64 = AtomicU64::new(0);
+    static DENOM: AtomicU64 = AtomicU64::new(0);
+
+    fn ensure_timebase() {
+        INIT.call_once(|| {
+            let mut info = MachTimebaseInfo::default();
+            unsafe {
+                mach_timebase_info(&mut info);
+            }
+            NUMER.store(info.numer as u64, Ordering::Relaxed);
+            DENOM.store(info.denom.max(1) as u64, Ordering::Relaxed);
+        });
+    }
+
+    pub fn host_time_now_ns() -> u64 {
+        ensure_timebase();
+        let numer = NUMER.load(Ordering::Relaxed);
+        let denom = DENOM.load(Ordering::Relaxed);
+        let raw = unsafe { mach_absolute_time() };
+        let nanos = (raw as u128)
+            .saturating_mul(numer as u128)
+            .checked_div(denom as u128)
+            .unwrap_or(0);
+        nanos.min(u128::from(u64::MAX)) as u64
+    }
+}
+
+/// Current host time. Prefer a [`MediaClock`] where one is available so the
+/// caller can be tested with a [`MockClock`].
+#[cfg(target_os = "macos")]
+pub use mac_host_time::host_time_now_ns;
+
+/// Off macOS there is no shared host clock with the capture APIs, so producers
+/// stamp against the process-wide monotonic origin instead.
+#[cfg(not(target_os = "macos"))]
+pub fn host_time_now_ns() -> u64 {
+    monotonic_now_ns()
+}
+
+#[cfg(target_os = "macos")]
+use screencapturekit::CMTime;
+
+#[cfg(target_os = "macos")]
+/// Converts a CoreMedia CMTime into host-time nanoseconds. SCK sample buffers
+/// are timed on the host clock, so the result is comparable with
+/// [`host_time_now_ns`].
+pub fn cm_time_to_ns(time: CMTime) -> u64 {
+    rational_to_ns(time.value, time.timescale)
+}
+
+#[cfg(target_os = "macos")]
+pub fn cm_time_seconds(time: CMTime) -> f64 {
+    if time.timescale == 0 {
+        return 0.0;
+    }
+    time.value as f64 / time.timescale as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_clock_drives_markers() {
+        let clock = MockClock::new(5 * NANOS_PER_SECOND);
+        let screen = ArrivalMarker::new();
+        assert_eq!(screen.get(), None);
+
+        clock.advance(Duration::from_millis(100));
+        screen.mark(clock.now_ns());
+        clock.advance(Duration::from_millis(500));
+        screen.mark(clock.now_ns());
+
+        assert_eq!(screen.get(), Some(5 * NANOS_PER_SECOND + 100_000_000));
+        assert_eq!(screen.millis_since(5 * NANOS_PER_SECOND), 100);
+    }
+
+    #[test]
+    fn converts_rational_and_sample_times() {
+        assert_eq!(rational_to_ns(1, 30), 33_333_333);
+        assert_eq!(rational_to_ns(90_000, 600), 150 * NANOS_PER_SECOND);
+        assert_eq!(rational_to_ns(-5, 1), 0);
+        assert_eq!(rational_to_ns(5, 0), 0);
+        assert_eq!(samples_to_ns(48_000, 48_000), NANOS_PER_SECOND);
+        assert_eq!(samples_to_ns(480, 48_000), 10_000_000);
+        assert_eq!(samples_to_ns(1, 0), 0);
+    }
+
+    #[test]
+    fn host_clock_is_monotonic() {
+        let clock = HostClock;
+        let first = clock.now_ns();
+        assert!(clock.now_ns() >= first);
+    }
+}
@@ -1,25 +1,41 @@
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime};
 
 use serde_json::json;
 use tauri::{AppHandle, Emitter, Manager, State};
 
 use crate::error::{AppError, AppResult};
-use crate::models::{AppSettings, RecordingOptions};
-use crate::services::camera::CameraPreview;
+use crate::services::control::options_from_settings;
+use crate::services::events::event_sink;
 use crate::services::immersive::ImmersiveMode;
-use crate::services::recording::{
+use momentum_core::calibration::{
+    run_calibration, CalibrationReport, DEFAULT_CALIBRATION_DURATION,
+};
+use momentum_core::camera::CameraPreview;
+use momentum_core::capture_target::CaptureTargetInfo;
+use momentum_core::encoder::{self, EncoderPreset};
+use momentum_core::models::{AppSettings, RecordingOptions};
+use momentum_core::output;
+use momentum_core::platform;
+use momentum_core::platform::ffmpeg_capabilities::FfmpegCapabilities;
+use momentum_core::platform::macos::ffmpeg::FfmpegLocator;
+use momentum_core::recording::{
     Recorder, RecordingPausedInfo, RecordingResumedInfo, RecordingStoppedInfo,
 };
-use crate::services::settings::SettingsStore;
+use momentum_core::settings::SettingsStore;
 
 #[tauri::command]
 pub async fn start_recording(
-    options: RecordingOptions,
+    mut options: RecordingOptions,
     app: AppHandle,
 ) -> AppResult<()> {
     let app_handle = app.clone();
+    let settings = app.state::<SettingsStore>().load()?;
+    options.device_offsets = settings.device_offsets;
+    options.camera_overlay = settings.camera_overlay;
+    options.frame_rate = settings.frame_rate;
+    options.output_scale = settings.output_scale;
+    options.encoder = settings.encoder;
     let options_clone = options.clone();
 
     tauri::async_runtime::spawn(async move {
@@ -27,7 +43,7 @@ pub async fn start_recording(
         let result = recorder.start(options_clone);
         match result {
             Ok(info) => {
-                recorder.start_elapsed_task(app_handle.clone());
+                recorder.start_elapsed_task(event_sink(&app_handle));
                 let _ = app_handle.emit("recording-started", info);
 
                 if options.include_camera {
@@ -132,7 +148,13 @@ pub async fn stop_recording(
 
 #[tauri::command]
 pub async fn get_settings(settings_store: State<'_, SettingsStore>) -> AppResult<AppSettings> {
-    settings_store.load()
+    Ok(settings_store.load()?)
+}
+
+/// The named profiles offered for `AppSettings.encoder`.
+#[tauri::command]
+pub async fn get_encoder_presets() -> AppResult<Vec<EncoderPreset>> {
+    Ok(encoder::encoder_presets())
 }
 
 #[tauri::command]
@@ -142,10 +164,73 @@ pub async fn update_settings(
     app: AppHandle,
 ) -> AppResult<()> {
     settings_store.save(&settings)?;
+    crate::layout_camera_overlay(&app, &settings.camera_overlay);
+    crate::set_camera_frame_rate(&app, settings.frame_rate);
     app.emit("settings-updated", settings.clone())?;
     Ok(())
 }
 
+/// Records a flash+beep pattern on the devices a recording would use (or the
+/// synthetic sources) and reports the A/V offset of each audio source. With
+/// `save`, the offsets are added to the devices' `device_offsets`.
+#[tauri::command]
+pub async fn run_av_calibration(
+    save: bool,
+    app: AppHandle,
+    recorder: State<'_, Recorder>,
+    ffmpeg_locator: State<'_, Arc<FfmpegLocator>>,
+    settings_store: State<'_, SettingsStore>,
+) -> AppResult<CalibrationReport> {
+    let recorder = recorder.inner().clone();
+    let locator = ffmpeg_locator.inner().clone();
+    let options = options_from_settings(&app)?;
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        run_calibration(&recorder, locator, options, DEFAULT_CALIBRATION_DURATION)
+    })
+    .await
+    .map_err(|e| AppError::Recording(format!("Calibration task failed: {}", e)))??;
+
+    if save {
+        let mut current = settings_store.load()?;
+        if !report.apply_to(&mut current.device_offsets) {
+            return Err(AppError::Recording(
+                "Calibration could not measure every source".to_string(),
+            ));
+        }
+        settings_store.save(&current)?;
+        app.emit("settings-updated", current)?;
+    }
+    Ok(report)
+}
+
+/// Encoders, filters and hardware acceleration methods of the FFmpeg build
+/// recordings run with.
+#[tauri::command]
+pub async fn get_ffmpeg_capabilities(
+    ffmpeg_locator: State<'_, Arc<FfmpegLocator>>,
+) -> AppResult<FfmpegCapabilities> {
+    let locator = ffmpeg_locator.inner().clone();
+    let capabilities = tauri::async_runtime::spawn_blocking(move || locator.capabilities())
+        .await
+        .map_err(|e| AppError::Recording(format!("FFmpeg probe failed: {}", e)))??;
+    Ok(capabilities.as_ref().clone())
+}
+
+/// Displays and windows for the capture target picker; each entry's
+/// `target` goes into `RecordingOptions.screen_target`.
+#[tauri::command]
+pub async fn list_capture_targets(
+    ffmpeg_locator: State<'_, Arc<FfmpegLocator>>,
+) -> AppResult<Vec<CaptureTargetInfo>> {
+    let locator = ffmpeg_locator.inner().clone();
+    let targets = tauri::async_runtime::spawn_blocking(move || {
+        platform::list_capture_targets(&locator)
+    })
+    .await
+    .map_err(|e| AppError::Recording(format!("Capture target listing failed: {}", e)))??;
+    Ok(targets)
+}
+
 #[tauri::command]
 pub async fn set_camera_overlay_visible(
     visible: bool,
@@ -254,7 +339,8 @@ fn apply_camera_overlay_visibility(
     if requested_visible {
         {
             let mut preview = camera_preview.lock().unwrap();
-            preview.set_app_handle(app.clone());
+            preview.set_event_sink(event_sink(app));
+            preview.sync_handle().set_overlay_visible(true);
             if !preview.is_running() {
                 preview.start()?;
             }
@@ -268,8 +354,12 @@ fn apply_camera_overlay_visibility(
     } else {
         window.hide()?;
         {
+            // A recording keeps the camera running so it can be shown again;
+            // hiding only takes it out of the composited video from here on.
             let preview = camera_preview.lock().unwrap();
-            if preview.is_running() {
+            let sync = preview.sync_handle();
+            sync.set_overlay_visible(false);
+            if preview.is_running() && !sync.is_sync_enabled() {
                 let _ = preview.stop();
             }
         }
@@ -311,27 +401,8 @@ fn apply_immersive_state(
 fn save_recording_file(app: &AppHandle, temp_path: PathBuf) -> AppResult<()> {
     let settings_store = app.state::<SettingsStore>();
     let settings = settings_store.load().unwrap_or_default();
-    let target_dir = resolve_output_dir(&settings)?;
-    std::fs::create_dir_all(&target_dir)?;
-    let timestamp = current_time_seconds();
-    let final_path = target_dir.join(format!("momentum-recording-{}.mp4", timestamp));
-
-    if !temp_path.exists() {
-        return Err(AppError::Recording(format!(
-            "Recording file not found: {:?}",
-            temp_path
-        )));
-    }
-
-    std::fs::copy(&temp_path, &final_path)?;
-    if !final_path.exists() {
-        return Err(AppError::Recording(format!(
-            "Output file was not created: {:?}",
-            final_path
-        )));
-    }
-
-    let _ = std::fs::remove_file(&temp_path);
+    let final_path = output::default_output_path(&settings)?;
+    output::save_recording_file(&temp_path, &final_path)?;
 
     app.emit(
         "recording-saved",
@@ -340,20 +411,3 @@ fn save_recording_file(app: &AppHandle, temp_path: PathBuf) -> AppResult<()> {
 
     Ok(())
 }
-
-fn resolve_output_dir(settings: &AppSettings) -> AppResult<PathBuf> {
-    if let Some(path) = &settings.save_location {
-        return Ok(PathBuf::from(path));
-    }
-
-    dirs::download_dir().ok_or_else(|| {
-        AppError::Recording("Failed to resolve downloads directory".to_string())
-    })
-}
-
-fn current_time_seconds() -> u64 {
-    SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_else(|_| Duration::from_secs(0))
-        .as_secs()
-}
@@ -12,6 +12,8 @@ pub enum AppError {
     Utf8(#[from] std::string::FromUtf8Error),
     #[error("Tauri error: {0}")]
     Tauri(#[from] tauri::Error),
+    #[error(transparent)]
+    Core(#[from] momentum_core::CoreError),
 }
 
 impl serde::Serialize for AppError {
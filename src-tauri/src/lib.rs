@@ -1,17 +1,20 @@
 mod error;
-mod models;
 mod services;
 mod commands;
 
 use crate::error::{AppError, AppResult};
-use crate::models::AppSettings;
-use services::{Recorder, CameraPreview, immersive::ImmersiveMode};
-use services::platform::macos::ffmpeg::FfmpegLocator;
-use services::settings::SettingsStore;
+use momentum_core::models::{AppSettings, CameraOverlay, FrameRate};
+use momentum_core::platform::macos::ffmpeg::FfmpegLocator;
+use momentum_core::platform::selectable_backend::SelectableCaptureBackend;
+use momentum_core::platform::synthetic_recorder::SyntheticRecorder;
+use momentum_core::platform::PlatformCaptureBackend;
+use momentum_core::{CameraPreview, Recorder, SettingsStore};
+use services::events::event_sink;
+use services::immersive::ImmersiveMode;
 use std::sync::{mpsc, Arc, Mutex};
 use tauri::{
     menu::{Menu, MenuId, MenuItemBuilder, MenuItemKind, Submenu},
-    AppHandle, Manager, PhysicalPosition,
+    AppHandle, Manager, PhysicalPosition, PhysicalSize,
 };
 
 const TOGGLE_IMMERSIVE_MENU_ID: &str = "toggle-immersive-mode";
@@ -19,28 +22,51 @@ const OPEN_SETTINGS_MENU_ID: &str = "open-settings-window";
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    #[cfg(unix)]
+    let launch_actions =
+        momentum_core::instance::LaunchAction::parse_args(std::env::args().skip(1));
+    #[cfg(unix)]
+    let _instance_lock = match services::instance::claim_or_forward(&launch_actions) {
+        services::instance::LaunchRole::Primary(lock) => lock,
+        services::instance::LaunchRole::Secondary => return,
+    };
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .setup(|app| {
+        .setup(move |app| {
             let app_handle = app.handle();
 
             let ffmpeg_locator = Arc::new(FfmpegLocator::new());
             let camera_preview = CameraPreview::new(ffmpeg_locator.clone());
             let camera_sync = camera_preview.sync_handle();
 
-            app.manage(Recorder::new(ffmpeg_locator, camera_sync));
+            let capture_backend = Arc::new(SelectableCaptureBackend::new(
+                PlatformCaptureBackend::new(ffmpeg_locator.clone()),
+                SyntheticRecorder::new(ffmpeg_locator.clone()),
+            ));
+            app.manage(Recorder::new(capture_backend, camera_sync));
             app.manage(Mutex::new(camera_preview));
             app.manage(Arc::new(Mutex::new(ImmersiveMode::new())));
             app.manage(SettingsStore::new(None)?);
+            app.manage(ffmpeg_locator);
+
+            #[cfg(unix)]
+            if let Err(err) = services::control::start(&app_handle) {
+                eprintln!("[Control] Control socket disabled: {}", err);
+            }
 
             position_overlay_windows(&app_handle);
 
             let settings = app.state::<SettingsStore>().load().unwrap_or_default();
+            set_camera_frame_rate(&app_handle, settings.frame_rate);
             initialize_camera_overlay(&app_handle, &settings)?;
             build_app_menu(&app_handle, &settings)?;
             register_menu_handlers(&app_handle)?;
             register_immersive_shortcut_handler(&app_handle, &settings.immersive_shortcut)?;
 
+            #[cfg(unix)]
+            services::instance::apply_launch_actions(&app_handle, launch_actions);
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -50,6 +76,10 @@ pub fn run() {
             commands::stop_recording,
             commands::get_settings,
             commands::update_settings,
+            commands::get_encoder_presets,
+            commands::run_av_calibration,
+            commands::list_capture_targets,
+            commands::get_ffmpeg_capabilities,
             commands::set_camera_overlay_visible,
             commands::toggle_microphone_during_recording,
             commands::set_mic_muted,
@@ -77,22 +107,38 @@ fn position_overlay_windows(app: &tauri::AppHandle) {
         }
     }
 
+    let settings = app.state::<SettingsStore>().load().unwrap_or_default();
+    layout_camera_overlay(app, &settings.camera_overlay);
+}
+
+/// Sizes and places the camera window the way the camera is composited into
+/// recordings.
+pub(crate) fn layout_camera_overlay(app: &AppHandle, layout: &CameraOverlay) {
     if let Some(camera_window) = app.get_webview_window("camera-overlay") {
-        if let Ok(monitor) = camera_window.primary_monitor() {
-            if let Some(monitor) = monitor {
-                if let Ok(window_size) = camera_window.outer_size() {
-                    let monitor_size = monitor.size();
-                    let x = monitor_size.width as i32 - window_size.width as i32 - 20;
-                    let y = monitor_size.height as i32 - window_size.height as i32 - 20;
-                    camera_window
-                        .set_position(PhysicalPosition::new(x, y))
-                        .ok();
-                }
-            }
+        if let Ok(Some(monitor)) = camera_window.primary_monitor() {
+            let monitor_size = monitor.size();
+            let side = layout.side_px(monitor_size.width);
+            let (x, y) = layout.origin_px(monitor_size.width, monitor_size.height);
+            camera_window
+                .set_size(PhysicalSize::new(side, side))
+                .ok();
+            camera_window
+                .set_position(PhysicalPosition::new(x as i32, y as i32))
+                .ok();
         }
     }
 }
 
+/// Reads the camera preview at the recording frame rate, so the preview
+/// matches what gets recorded.
+pub(crate) fn set_camera_frame_rate(app: &AppHandle, frame_rate: FrameRate) {
+    app.state::<Mutex<CameraPreview>>()
+        .lock()
+        .unwrap()
+        .sync_handle()
+        .set_frame_rate(frame_rate);
+}
+
 fn initialize_camera_overlay(app: &tauri::AppHandle, settings: &AppSettings) -> AppResult<()> {
     let camera_state = app.state::<Mutex<CameraPreview>>();
     if settings.camera_enabled {
@@ -100,9 +146,11 @@ fn initialize_camera_overlay(app: &tauri::AppHandle, settings: &AppSettings) ->
             window.show()?;
         }
         let mut preview = camera_state.lock().unwrap();
-        preview.set_app_handle(app.clone());
+        preview.set_event_sink(event_sink(app));
         if !preview.is_running() {
-            preview.start()?;
+            if let Err(err) = preview.start() {
+                eprintln!("[CameraPreview] Failed to start camera preview: {}", err);
+            }
         }
     } else if let Some(window) = app.get_webview_window("camera-overlay") {
         window.hide()?;
@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use momentum_core::control::protocol::parse_params;
+use momentum_core::control::{self, ControlHandler, ControlServer, RpcError};
+use momentum_core::models::{AppSettings, RecordingOptions};
+use momentum_core::{CoreResult, SettingsStore};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::async_runtime::block_on;
+use tauri::{AppHandle, Emitter, Listener, Manager};
+
+use crate::commands;
+use crate::error::AppResult;
+
+/// Window events mirrored to socket subscribers. `camera-frame` is left out:
+/// base64 JPEGs at up to 30 fps are for the overlay, not for scripts.
+const FORWARDED_EVENTS: &[&str] = &[
+    "recording-started",
+    "recording-paused",
+    "recording-resumed",
+    "recording-stopped",
+    "recording-saved",
+    "recording-error",
+    "recording-elapsed",
+    "settings-updated",
+    "immersive-mode-changed",
+    "immersive-shortcut-updated",
+    "camera-error",
+];
+
+/// Methods share names and params with the Tauri commands, so
+/// `{"method":"set_mic_muted","params":{"muted":true}}` does what
+/// `invoke('set_mic_muted', { muted: true })` does.
+struct AppControlHandler {
+    app: AppHandle,
+}
+
+#[derive(Deserialize)]
+struct StartParams {
+    #[serde(default)]
+    options: Option<RecordingOptions>,
+}
+
+#[derive(Deserialize)]
+struct MutedParams {
+    muted: bool,
+}
+
+#[derive(Deserialize)]
+struct EnabledParams {
+    enabled: bool,
+}
+
+#[derive(Deserialize)]
+struct SettingsParams {
+    settings: AppSettings,
+}
+
+#[derive(Deserialize)]
+struct SettingParams {
+    key: String,
+    value: Value,
+}
+
+impl ControlHandler for AppControlHandler {
+    fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        dispatch(&self.app, method, params)
+    }
+}
+
+/// Runs one control method against the app. Also used for the flags this
+/// instance was launched with, so both paths behave the same.
+pub(crate) fn dispatch(app: &AppHandle, method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        "start_recording" => {
+            let params: StartParams = parse_params(params)?;
+            let options = match params.options {
+                Some(options) => options,
+                None => options_from_settings(app)?,
+            };
+            done(block_on(commands::start_recording(options, app.clone())))
+        }
+        "pause_recording" => done(block_on(commands::pause_recording(
+            app.state(),
+            app.clone(),
+        ))),
+        "resume_recording" => done(block_on(commands::resume_recording(
+            app.state(),
+            app.clone(),
+        ))),
+        "stop_recording" => done(block_on(commands::stop_recording(app.clone()))),
+        "set_mic_muted" => {
+            let params: MutedParams = parse_params(params)?;
+            done(block_on(commands::set_mic_muted(params.muted, app.state())))
+        }
+        "set_system_audio_muted" => {
+            let params: MutedParams = parse_params(params)?;
+            done(block_on(commands::set_system_audio_muted(
+                params.muted,
+                app.state(),
+            )))
+        }
+        "set_immersive_mode" => {
+            let params: EnabledParams = parse_params(params)?;
+            done(block_on(commands::set_immersive_mode(
+                params.enabled,
+                app.clone(),
+                app.state(),
+                app.state(),
+            )))
+        }
+        "toggle_immersive_mode" => done(block_on(commands::toggle_immersive_mode(
+            app.clone(),
+            app.state(),
+            app.state(),
+        ))),
+        "get_settings" => {
+            let settings =
+                block_on(commands::get_settings(app.state())).map_err(RpcError::server)?;
+            serde_json::to_value(settings).map_err(RpcError::server)
+        }
+        "update_settings" => {
+            let params: SettingsParams = parse_params(params)?;
+            done(block_on(commands::update_settings(
+                params.settings,
+                app.state(),
+                app.clone(),
+            )))
+        }
+        "open_settings" => done(crate::show_settings_window(app)),
+        "set_setting" => {
+            let params: SettingParams = parse_params(params)?;
+            let settings = app
+                .state::<SettingsStore>()
+                .set_value(&params.key, params.value)?;
+            crate::layout_camera_overlay(app, &settings.camera_overlay);
+            crate::set_camera_frame_rate(app, settings.frame_rate);
+            app.emit("settings-updated", settings.clone())
+                .map_err(RpcError::server)?;
+            serde_json::to_value(settings).map_err(RpcError::server)
+        }
+        _ => Err(RpcError::method_not_found(method)),
+    }
+}
+
+/// Options for a session started from the current settings.
+pub(crate) fn options_from_settings(app: &AppHandle) -> CoreResult<RecordingOptions> {
+    let settings = app.state::<SettingsStore>().load()?;
+    Ok(RecordingOptions {
+        include_microphone: settings.mic_enabled,
+        include_camera: settings.camera_enabled,
+        screen_target: None,
+        capture_mode: None,
+        device_offsets: settings.device_offsets,
+        camera_overlay: settings.camera_overlay,
+        frame_rate: settings.frame_rate,
+        output_scale: settings.output_scale,
+        encoder: settings.encoder,
+    })
+}
+
+fn done(result: AppResult<()>) -> Result<Value, RpcError> {
+    result.map(|()| json!({ "ok": true })).map_err(RpcError::server)
+}
+
+/// Binds the control socket and mirrors [`FORWARDED_EVENTS`] to subscribers.
+/// The server is kept in managed state so it lives as long as the app.
+pub fn start(app: &AppHandle) -> AppResult<()> {
+    let path = control::default_socket_path()?;
+    let handler = Arc::new(AppControlHandler { app: app.clone() });
+    let server = ControlServer::bind(path, handler)?;
+
+    let hub = server.events();
+    for &event_name in FORWARDED_EVENTS {
+        let hub = hub.clone();
+        app.listen_any(event_name, move |event| {
+            let payload = serde_json::from_str(event.payload()).unwrap_or(Value::Null);
+            hub.broadcast(event_name, payload);
+        });
+    }
+
+    app.manage(server);
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use momentum_core::events::{CoreEvent, EventSink};
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Forwards core events to every webview as Tauri events of the same name.
+pub struct TauriEventSink<R: Runtime> {
+    app: AppHandle<R>,
+}
+
+impl<R: Runtime> TauriEventSink<R> {
+    pub fn new(app: AppHandle<R>) -> Self {
+        Self { app }
+    }
+}
+
+impl<R: Runtime> EventSink for TauriEventSink<R> {
+    fn emit(&self, event: CoreEvent) {
+        if let Err(err) = self.app.emit(event.name(), event.payload()) {
+            eprintln!("[Events] Failed to emit {}: {}", event.name(), err);
+        }
+    }
+}
+
+pub fn event_sink<R: Runtime>(app: &AppHandle<R>) -> Arc<dyn EventSink> {
+    Arc::new(TauriEventSink::new(app.clone()))
+}
@@ -1,15 +1,36 @@
+#[cfg_attr(not(target_os = "macos"), allow(unused_imports))]
 use crate::error::{AppError, AppResult};
 use std::sync::Arc;
 
 pub type HotkeyCallback = Arc<dyn Fn() + Send + Sync + 'static>;
 
+#[cfg(target_os = "macos")]
 pub fn register_hotkey(shortcut: &str, callback: HotkeyCallback) -> AppResult<()> {
     macos::register_hotkey(shortcut, callback)
 }
 
+#[cfg(target_os = "macos")]
 pub fn unregister_hotkey() -> AppResult<()> {
     macos::unregister_hotkey()
 }
+
+/// Global hotkeys rely on the Carbon event API; elsewhere the shortcut is only
+/// reachable through the app menu accelerator.
+#[cfg(not(target_os = "macos"))]
+pub fn register_hotkey(shortcut: &str, _callback: HotkeyCallback) -> AppResult<()> {
+    println!(
+        "[Hotkey] Global shortcuts are not supported on this platform; '{}' only works from the menu",
+        shortcut
+    );
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn unregister_hotkey() -> AppResult<()> {
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
 mod macos {
     use super::{AppError, AppResult, HotkeyCallback};
     use std::{
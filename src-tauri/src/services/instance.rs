@@ -0,0 +1,62 @@
+use momentum_core::control;
+use momentum_core::instance::{self, InstanceLock, LaunchAction};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::services::control::dispatch;
+
+pub enum LaunchRole {
+    /// This process runs the app. The lock is `None` only if the lock file
+    /// could not be created, in which case we run unguarded rather than not
+    /// at all.
+    Primary(Option<InstanceLock>),
+    /// Another instance is running and already received our flags.
+    Secondary,
+}
+
+/// Takes the single-instance lock, or hands `actions` to whoever holds it.
+pub fn claim_or_forward(actions: &[LaunchAction]) -> LaunchRole {
+    let lock_path = match instance::default_lock_path() {
+        Ok(path) => path,
+        Err(err) => {
+            eprintln!("[Instance] Running without single-instance lock: {}", err);
+            return LaunchRole::Primary(None);
+        }
+    };
+
+    match InstanceLock::acquire(&lock_path) {
+        Ok(Some(lock)) => LaunchRole::Primary(Some(lock)),
+        Ok(None) => {
+            if actions.is_empty() {
+                println!("[Instance] Momentum is already running");
+                return LaunchRole::Secondary;
+            }
+            let forwarded = control::default_socket_path()
+                .and_then(|path| instance::forward_to_running_instance(&path, actions));
+            if let Err(err) = forwarded {
+                eprintln!("[Instance] Failed to forward {:?}: {}", actions, err);
+            }
+            LaunchRole::Secondary
+        }
+        Err(err) => {
+            eprintln!("[Instance] Running without single-instance lock: {}", err);
+            LaunchRole::Primary(None)
+        }
+    }
+}
+
+/// Runs the flags the primary instance itself was launched with, through the
+/// same handlers a forwarded launch would reach.
+pub fn apply_launch_actions(app: &AppHandle, actions: Vec<LaunchAction>) {
+    if actions.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for action in actions {
+            if let Err(err) = dispatch(&app, action.control_method(), Value::Null) {
+                eprintln!("[Instance] Failed to apply {:?}: {}", action, err);
+            }
+        }
+    });
+}
@@ -1,16 +1,7 @@
-/// Global multiplier applied to microphone samples before they are encoded into
-/// the final recording. Increase to make mic audio louder, decrease to quiet
-/// it down. Keep within a reasonable range (e.g. `0.8..=2.0`) to avoid harsh
-/// dynamics.
-pub const MIC_VOLUME_GAIN: f32 = 1.8;
-
-pub mod recording;
-pub mod camera;
-pub mod settings;
-pub mod platform;
+#[cfg(unix)]
+pub mod control;
+pub mod events;
 pub mod immersive;
+#[cfg(unix)]
+pub mod instance;
 pub mod hotkey;
-pub mod time;
-
-pub use recording::Recorder;
-pub use camera::CameraPreview;
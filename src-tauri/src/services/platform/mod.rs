@@ -1,3 +0,0 @@
-pub mod device_resolver;
-pub mod macos;
-pub mod screencapturekit_recorder;
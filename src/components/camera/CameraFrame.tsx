@@ -41,7 +41,7 @@ export function CameraFrame() {
 
   if (!hasReceivedFrame) {
     return (
-      <div className="w-full h-full flex items-center justify-center bg-black rounded-full pointer-events-none">
+      <div className="w-full h-full flex items-center justify-center bg-black pointer-events-none">
         <span className="text-neutral-500 text-sm pointer-events-none">
           Camera Loading...
         </span>
@@ -51,7 +51,7 @@ export function CameraFrame() {
 
   if (!currentFrameUrl) {
     return (
-      <div className="w-full h-full flex items-center justify-center bg-black rounded-full pointer-events-none">
+      <div className="w-full h-full flex items-center justify-center bg-black pointer-events-none">
         <span className="text-neutral-500 text-sm pointer-events-none">
           Camera Off
         </span>
@@ -63,7 +63,7 @@ export function CameraFrame() {
     <img
       src={currentFrameUrl}
       alt="Camera preview"
-      className="w-full h-full object-cover rounded-full select-none pointer-events-none"
+      className="w-full h-full object-cover select-none pointer-events-none"
       style={{
         userSelect: 'none',
         WebkitUserSelect: 'none',
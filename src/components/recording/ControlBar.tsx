@@ -76,7 +76,12 @@ export function ControlBar() {
             console.log('Recording command sent successfully')
           } catch (err: any) {
             console.error('Failed to start recording:', err)
-            setError(err.message || 'Failed to start recording')
+            // Commands reject with the AppError message as a plain string
+            setError(
+              typeof err === 'string'
+                ? err
+                : err.message || 'Failed to start recording'
+            )
             useRecordingStore.getState().reset()
           }
           return
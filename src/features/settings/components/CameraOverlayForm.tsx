@@ -0,0 +1,143 @@
+import { useCallback, useState } from 'react'
+import { useSettingsStore } from '../../../state/settingsStore'
+import { updateSettings } from '../../../tauri/commands'
+import type {
+  CameraCorner,
+  CameraOutput,
+  CameraOverlay,
+  CameraShape
+} from '../../../types'
+
+const DEFAULT_OVERLAY: CameraOverlay = {
+  corner: 'bottomRight',
+  size: 0.15,
+  shape: 'circle',
+  output: 'composite'
+}
+
+const CORNERS: Array<{ value: CameraCorner; label: string }> = [
+  { value: 'topLeft', label: 'Top left' },
+  { value: 'topRight', label: 'Top right' },
+  { value: 'bottomLeft', label: 'Bottom left' },
+  { value: 'bottomRight', label: 'Bottom right' }
+]
+
+const SHAPES: Array<{ value: CameraShape; label: string }> = [
+  { value: 'rectangle', label: 'Rectangle' },
+  { value: 'rounded', label: 'Rounded' },
+  { value: 'circle', label: 'Circle' }
+]
+
+const OUTPUTS: Array<{ value: CameraOutput; label: string }> = [
+  { value: 'composite', label: 'In the video' },
+  { value: 'track', label: 'Separate track' },
+  { value: 'sidecar', label: 'Separate file' }
+]
+
+const SELECT_CLASS =
+  'text-sm bg-neutral-800 border border-neutral-700 rounded-lg px-2 py-1 focus:outline-none focus:ring-2 focus:ring-blue-500'
+
+export function CameraOverlayForm() {
+  const { settings, setSettings } = useSettingsStore()
+  const [errorMessage, setErrorMessage] = useState<string | null>(null)
+  const overlay = settings.cameraOverlay ?? DEFAULT_OVERLAY
+
+  const save = useCallback(
+    async (patch: Partial<CameraOverlay>) => {
+      const next = {
+        ...settings,
+        cameraOverlay: { ...overlay, ...patch }
+      }
+      setErrorMessage(null)
+      try {
+        await updateSettings(next)
+        setSettings(next)
+      } catch (err) {
+        console.error('Failed to update camera overlay', err)
+        setErrorMessage(
+          err instanceof Error ? err.message : 'Unable to update the camera.'
+        )
+      }
+    },
+    [overlay, setSettings, settings]
+  )
+
+  return (
+    <div className="flex flex-col gap-4 bg-neutral-900/80 p-4 rounded-2xl border border-neutral-800">
+      <div className="grid grid-cols-[6rem_1fr] gap-3 items-center text-sm">
+        <label htmlFor="camera-output" className="text-neutral-400">
+          Recording
+        </label>
+        <select
+          id="camera-output"
+          value={overlay.output ?? 'composite'}
+          onChange={event =>
+            save({ output: event.target.value as CameraOutput })
+          }
+          className={SELECT_CLASS}
+        >
+          {OUTPUTS.map(output => (
+            <option key={output.value} value={output.value}>
+              {output.label}
+            </option>
+          ))}
+        </select>
+
+        <label htmlFor="camera-corner" className="text-neutral-400">
+          Corner
+        </label>
+        <select
+          id="camera-corner"
+          value={overlay.corner}
+          onChange={event =>
+            save({ corner: event.target.value as CameraCorner })
+          }
+          className={SELECT_CLASS}
+        >
+          {CORNERS.map(corner => (
+            <option key={corner.value} value={corner.value}>
+              {corner.label}
+            </option>
+          ))}
+        </select>
+
+        <label htmlFor="camera-shape" className="text-neutral-400">
+          Shape
+        </label>
+        <select
+          id="camera-shape"
+          value={overlay.shape}
+          onChange={event => save({ shape: event.target.value as CameraShape })}
+          className={SELECT_CLASS}
+        >
+          {SHAPES.map(shape => (
+            <option key={shape.value} value={shape.value}>
+              {shape.label}
+            </option>
+          ))}
+        </select>
+
+        <label htmlFor="camera-size" className="text-neutral-400">
+          Size
+        </label>
+        <div className="flex items-center gap-3">
+          <input
+            id="camera-size"
+            type="range"
+            min={0.05}
+            max={0.5}
+            step={0.01}
+            value={overlay.size}
+            onChange={event => save({ size: Number(event.target.value) })}
+            className="flex-1"
+          />
+          <span className="w-12 text-right text-neutral-400">
+            {Math.round(overlay.size * 100)}%
+          </span>
+        </div>
+      </div>
+
+      {errorMessage && <p className="text-sm text-red-400">{errorMessage}</p>}
+    </div>
+  )
+}
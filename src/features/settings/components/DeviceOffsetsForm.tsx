@@ -0,0 +1,200 @@
+import { useCallback, useEffect, useMemo, useState } from 'react'
+import { useSettingsStore } from '../../../state/settingsStore'
+import { updateSettings } from '../../../tauri/commands'
+import type { DeviceOffsets } from '../../../types'
+
+type Status = 'idle' | 'saving' | 'success' | 'error'
+
+interface Row {
+  deviceId: string
+  audioMs: string
+  videoMs: string
+}
+
+export function DeviceOffsetsForm() {
+  const { settings, setSettings } = useSettingsStore()
+  const [rows, setRows] = useState<Row[]>(() =>
+    toRows(settings.deviceOffsets)
+  )
+  const [status, setStatus] = useState<Status>('idle')
+  const [errorMessage, setErrorMessage] = useState<string | null>(null)
+
+  useEffect(() => {
+    setRows(toRows(settings.deviceOffsets))
+  }, [settings.deviceOffsets])
+
+  const updateRow = useCallback((index: number, patch: Partial<Row>) => {
+    setRows(current =>
+      current.map((row, i) => (i === index ? { ...row, ...patch } : row))
+    )
+    setStatus('idle')
+  }, [])
+
+  const addRow = useCallback(() => {
+    setRows(current => [...current, { deviceId: '', audioMs: '0', videoMs: '0' }])
+    setStatus('idle')
+  }, [])
+
+  const removeRow = useCallback((index: number) => {
+    setRows(current => current.filter((_, i) => i !== index))
+    setStatus('idle')
+  }, [])
+
+  const parsed = useMemo(() => fromRows(rows), [rows])
+
+  const handleSave = useCallback(async () => {
+    if (!parsed) {
+      return
+    }
+    setStatus('saving')
+    setErrorMessage(null)
+    const next = { ...settings, deviceOffsets: parsed }
+    try {
+      await updateSettings(next)
+      setSettings(next)
+      setStatus('success')
+      setTimeout(() => setStatus('idle'), 2000)
+    } catch (err) {
+      console.error('Failed to update device offsets', err)
+      setStatus('error')
+      setErrorMessage(
+        err instanceof Error ? err.message : 'Unable to update device offsets.'
+      )
+    }
+  }, [parsed, setSettings, settings])
+
+  const statusText = useMemo(() => {
+    switch (status) {
+      case 'saving':
+        return 'Saving…'
+      case 'success':
+        return 'Offsets updated. They apply from the next recording.'
+      case 'error':
+        return errorMessage ?? 'Unable to update device offsets.'
+      default:
+        return null
+    }
+  }, [errorMessage, status])
+
+  return (
+    <div className="flex flex-col gap-4 bg-neutral-900/80 p-4 rounded-2xl border border-neutral-800">
+      <p className="text-sm text-neutral-400">
+        Milliseconds a device delivers late (negative if early). Device ids
+        are printed in the recording log, e.g. a Pulse source name or{' '}
+        <span className="font-mono">avfoundation:audio:0</span>.
+      </p>
+
+      {rows.length > 0 && (
+        <div className="flex flex-col gap-2">
+          <div className="grid grid-cols-[1fr_5rem_5rem_2rem] gap-2 text-xs uppercase tracking-wide text-neutral-500">
+            <span>Device id</span>
+            <span>Audio ms</span>
+            <span>Video ms</span>
+            <span />
+          </div>
+          {rows.map((row, index) => (
+            <div
+              key={index}
+              className="grid grid-cols-[1fr_5rem_5rem_2rem] gap-2 items-center"
+            >
+              <input
+                value={row.deviceId}
+                onChange={event =>
+                  updateRow(index, { deviceId: event.target.value })
+                }
+                placeholder="Device id"
+                className="font-mono text-sm bg-neutral-800 border border-neutral-700 rounded-lg px-2 py-1 focus:outline-none focus:ring-2 focus:ring-blue-500"
+              />
+              <input
+                value={row.audioMs}
+                inputMode="decimal"
+                onChange={event =>
+                  updateRow(index, { audioMs: event.target.value })
+                }
+                className="text-sm bg-neutral-800 border border-neutral-700 rounded-lg px-2 py-1 focus:outline-none focus:ring-2 focus:ring-blue-500"
+              />
+              <input
+                value={row.videoMs}
+                inputMode="decimal"
+                onChange={event =>
+                  updateRow(index, { videoMs: event.target.value })
+                }
+                className="text-sm bg-neutral-800 border border-neutral-700 rounded-lg px-2 py-1 focus:outline-none focus:ring-2 focus:ring-blue-500"
+              />
+              <button
+                type="button"
+                onClick={() => removeRow(index)}
+                aria-label={`Remove ${row.deviceId || 'device'}`}
+                className="text-neutral-400 hover:text-neutral-100"
+              >
+                ×
+              </button>
+            </div>
+          ))}
+        </div>
+      )}
+
+      <div className="flex gap-3">
+        <button
+          type="button"
+          disabled={!parsed || status === 'saving'}
+          onClick={handleSave}
+          className="px-4 py-2 rounded-xl bg-blue-500 disabled:bg-neutral-700 disabled:text-neutral-400 text-white transition-colors"
+        >
+          Save Offsets
+        </button>
+        <button
+          type="button"
+          onClick={addRow}
+          className="px-4 py-2 rounded-xl border border-neutral-700 text-neutral-200 hover:bg-neutral-800 transition-colors"
+        >
+          Add Device
+        </button>
+      </div>
+
+      {!parsed && (
+        <p className="text-sm text-red-400">
+          Every row needs a unique device id and numeric offsets.
+        </p>
+      )}
+      {statusText && (
+        <p
+          className={`text-sm ${
+            status === 'error' ? 'text-red-400' : 'text-green-400'
+          }`}
+        >
+          {statusText}
+        </p>
+      )}
+    </div>
+  )
+}
+
+function toRows(offsets: DeviceOffsets | undefined): Row[] {
+  return Object.entries(offsets ?? {}).map(([deviceId, offset]) => ({
+    deviceId,
+    audioMs: String(offset.audioMs),
+    videoMs: String(offset.videoMs)
+  }))
+}
+
+function fromRows(rows: Row[]): DeviceOffsets | null {
+  const offsets: DeviceOffsets = {}
+  for (const row of rows) {
+    const deviceId = row.deviceId.trim()
+    const audioMs = Number(row.audioMs)
+    const videoMs = Number(row.videoMs)
+    if (
+      !deviceId ||
+      deviceId in offsets ||
+      row.audioMs.trim() === '' ||
+      row.videoMs.trim() === '' ||
+      !Number.isFinite(audioMs) ||
+      !Number.isFinite(videoMs)
+    ) {
+      return null
+    }
+    offsets[deviceId] = { audioMs, videoMs }
+  }
+  return offsets
+}
@@ -0,0 +1,251 @@
+import { useCallback, useEffect, useState } from 'react'
+import { useSettingsStore } from '../../../state/settingsStore'
+import {
+  getEncoderPresets,
+  getFfmpegCapabilities,
+  updateSettings
+} from '../../../tauri/commands'
+import type {
+  EncoderPreset,
+  EncoderProfile,
+  EncoderSpeed,
+  RateControl,
+  VideoCodec
+} from '../../../types'
+
+// Same as the "Balanced" preset
+const DEFAULT_PROFILE: EncoderProfile = {
+  codec: 'h264',
+  rateControl: { crf: 23 },
+  preset: 'ultrafast',
+  keyframeIntervalSecs: null,
+  lossless: false
+}
+
+// `encoder` is the FFmpeg encoder each codec records with
+const CODECS: Array<{ value: VideoCodec; label: string; encoder: string }> = [
+  { value: 'h264', label: 'H.264', encoder: 'libx264' },
+  { value: 'hevc', label: 'HEVC', encoder: 'libx265' },
+  { value: 'vp9', label: 'VP9', encoder: 'libvpx-vp9' },
+  { value: 'av1', label: 'AV1', encoder: 'libsvtav1' }
+]
+
+const SPEEDS: Array<{ value: EncoderSpeed; label: string }> = [
+  { value: 'ultrafast', label: 'Ultrafast' },
+  { value: 'veryfast', label: 'Very fast' },
+  { value: 'fast', label: 'Fast' },
+  { value: 'medium', label: 'Medium' },
+  { value: 'slow', label: 'Slow' }
+]
+
+const DEFAULT_BITRATE_KBPS = 8000
+
+const SELECT_CLASS =
+  'text-sm bg-neutral-800 border border-neutral-700 rounded-lg px-2 py-1 focus:outline-none focus:ring-2 focus:ring-blue-500'
+
+const sameProfile = (a: EncoderProfile, b: EncoderProfile) =>
+  JSON.stringify(a) === JSON.stringify(b)
+
+export function EncoderForm() {
+  const { settings, setSettings } = useSettingsStore()
+  const [presets, setPresets] = useState<EncoderPreset[]>([])
+  // Unknown until probed; nothing is disabled before that
+  const [encoders, setEncoders] = useState<string[] | null>(null)
+  const [errorMessage, setErrorMessage] = useState<string | null>(null)
+  const profile = settings.encoder ?? DEFAULT_PROFILE
+  const presetId =
+    presets.find(preset => sameProfile(preset.profile, profile))?.id ?? 'custom'
+
+  useEffect(() => {
+    getEncoderPresets()
+      .then(setPresets)
+      .catch(err => console.error('Failed to load encoder presets', err))
+    getFfmpegCapabilities()
+      .then(capabilities => setEncoders(capabilities.encoders))
+      .catch(err => console.error('Failed to probe FFmpeg', err))
+  }, [])
+
+  const unavailable = (codec: VideoCodec) => {
+    const encoder = CODECS.find(candidate => candidate.value === codec)?.encoder
+    return (
+      encoders !== null &&
+      encoder !== undefined &&
+      !encoders.includes(encoder)
+    )
+  }
+
+  const save = useCallback(
+    async (next: EncoderProfile) => {
+      const nextSettings = { ...settings, encoder: next }
+      setErrorMessage(null)
+      try {
+        await updateSettings(nextSettings)
+        setSettings(nextSettings)
+      } catch (err) {
+        console.error('Failed to update encoder', err)
+        setErrorMessage(
+          err instanceof Error ? err.message : 'Unable to update the encoder.'
+        )
+      }
+    },
+    [setSettings, settings]
+  )
+
+  const update = (patch: Partial<EncoderProfile>) =>
+    save({ ...profile, ...patch })
+
+  const choosePreset = (id: string) => {
+    const preset = presets.find(candidate => candidate.id === id)
+    if (preset) {
+      save(preset.profile)
+    }
+  }
+
+  const chooseRateMode = (mode: 'crf' | 'bitrate') => {
+    const rateControl: RateControl =
+      mode === 'crf' ? { crf: 23 } : { bitrateKbps: DEFAULT_BITRATE_KBPS }
+    update({ rateControl })
+  }
+
+  const rateMode = 'crf' in profile.rateControl ? 'crf' : 'bitrate'
+  const rateValue =
+    'crf' in profile.rateControl
+      ? profile.rateControl.crf
+      : profile.rateControl.bitrateKbps
+
+  return (
+    <div className="flex flex-col gap-4 bg-neutral-900/80 p-4 rounded-2xl border border-neutral-800">
+      <div className="grid grid-cols-[6rem_1fr] gap-3 items-center text-sm">
+        <label htmlFor="encoder-preset" className="text-neutral-400">
+          Preset
+        </label>
+        <select
+          id="encoder-preset"
+          value={presetId}
+          onChange={event => choosePreset(event.target.value)}
+          className={SELECT_CLASS}
+        >
+          {presets.map(preset => (
+            <option key={preset.id} value={preset.id}>
+              {preset.label}
+            </option>
+          ))}
+          <option value="custom" disabled>
+            Custom
+          </option>
+        </select>
+
+        <label htmlFor="encoder-codec" className="text-neutral-400">
+          Codec
+        </label>
+        <select
+          id="encoder-codec"
+          value={profile.codec}
+          onChange={event =>
+            update({ codec: event.target.value as VideoCodec })
+          }
+          className={SELECT_CLASS}
+        >
+          {CODECS.map(codec => (
+            <option
+              key={codec.value}
+              value={codec.value}
+              disabled={unavailable(codec.value)}
+            >
+              {codec.label}
+              {unavailable(codec.value) ? ' (not in your FFmpeg)' : ''}
+            </option>
+          ))}
+        </select>
+
+        <label htmlFor="encoder-rate" className="text-neutral-400">
+          Quality
+        </label>
+        <div className="flex items-center gap-3">
+          <select
+            id="encoder-rate"
+            value={rateMode}
+            disabled={profile.lossless}
+            onChange={event =>
+              chooseRateMode(event.target.value as 'crf' | 'bitrate')
+            }
+            className={SELECT_CLASS}
+          >
+            <option value="crf">CRF</option>
+            <option value="bitrate">Bitrate (kbps)</option>
+          </select>
+          <input
+            type="number"
+            min={rateMode === 'crf' ? 0 : 100}
+            value={rateValue}
+            disabled={profile.lossless}
+            onChange={event => {
+              const value = Number(event.target.value)
+              update({
+                rateControl:
+                  rateMode === 'crf' ? { crf: value } : { bitrateKbps: value }
+              })
+            }}
+            className={`w-24 ${SELECT_CLASS}`}
+          />
+        </div>
+
+        <label htmlFor="encoder-speed" className="text-neutral-400">
+          Speed
+        </label>
+        <select
+          id="encoder-speed"
+          value={profile.preset}
+          onChange={event =>
+            update({ preset: event.target.value as EncoderSpeed })
+          }
+          className={SELECT_CLASS}
+        >
+          {SPEEDS.map(speed => (
+            <option key={speed.value} value={speed.value}>
+              {speed.label}
+            </option>
+          ))}
+        </select>
+
+        <label htmlFor="encoder-keyframes" className="text-neutral-400">
+          Keyframes
+        </label>
+        <div className="flex items-center gap-3">
+          <input
+            id="encoder-keyframes"
+            type="number"
+            min={1}
+            max={60}
+            placeholder="Auto"
+            value={profile.keyframeIntervalSecs ?? ''}
+            onChange={event =>
+              update({
+                keyframeIntervalSecs:
+                  event.target.value === '' ? null : Number(event.target.value)
+              })
+            }
+            className={`w-24 ${SELECT_CLASS}`}
+          />
+          <span className="text-neutral-400">seconds apart</span>
+        </div>
+
+        <label htmlFor="encoder-lossless" className="text-neutral-400">
+          Lossless
+        </label>
+        <label className="flex items-center gap-2 text-neutral-400">
+          <input
+            id="encoder-lossless"
+            type="checkbox"
+            checked={profile.lossless}
+            disabled={profile.codec === 'av1'}
+            onChange={event => update({ lossless: event.target.checked })}
+          />
+          Archival quality, very large files
+        </label>
+      </div>
+
+      {errorMessage && <p className="text-sm text-red-400">{errorMessage}</p>}
+    </div>
+  )
+}
@@ -0,0 +1,109 @@
+import { useCallback, useState } from 'react'
+import { useSettingsStore } from '../../../state/settingsStore'
+import { updateSettings } from '../../../tauri/commands'
+import type { AppSettings, FrameRate, OutputScale } from '../../../types'
+
+const FRAME_RATES: FrameRate[] = [15, 24, 30, 60]
+
+type ScalePreset = 'native' | '1440p' | '1080p' | '720p' | 'width'
+
+const SCALES: Array<{ value: ScalePreset; label: string }> = [
+  { value: 'native', label: 'Native' },
+  { value: '1440p', label: '1440p' },
+  { value: '1080p', label: '1080p' },
+  { value: '720p', label: '720p' },
+  { value: 'width', label: 'Fixed width' }
+]
+
+const DEFAULT_WIDTH = 1600
+
+const SELECT_CLASS =
+  'text-sm bg-neutral-800 border border-neutral-700 rounded-lg px-2 py-1 focus:outline-none focus:ring-2 focus:ring-blue-500'
+
+export function VideoForm() {
+  const { settings, setSettings } = useSettingsStore()
+  const [errorMessage, setErrorMessage] = useState<string | null>(null)
+  const frameRate = settings.frameRate ?? 30
+  const outputScale = settings.outputScale ?? 'native'
+  const preset: ScalePreset =
+    typeof outputScale === 'object' ? 'width' : outputScale
+
+  const save = useCallback(
+    async (patch: Partial<AppSettings>) => {
+      const next = { ...settings, ...patch }
+      setErrorMessage(null)
+      try {
+        await updateSettings(next)
+        setSettings(next)
+      } catch (err) {
+        console.error('Failed to update video settings', err)
+        setErrorMessage(
+          err instanceof Error ? err.message : 'Unable to update the video.'
+        )
+      }
+    },
+    [setSettings, settings]
+  )
+
+  const saveScale = (value: ScalePreset) => {
+    const scale: OutputScale =
+      value === 'width' ? { width: DEFAULT_WIDTH } : value
+    save({ outputScale: scale })
+  }
+
+  return (
+    <div className="flex flex-col gap-4 bg-neutral-900/80 p-4 rounded-2xl border border-neutral-800">
+      <div className="grid grid-cols-[6rem_1fr] gap-3 items-center text-sm">
+        <label htmlFor="video-frame-rate" className="text-neutral-400">
+          Frame rate
+        </label>
+        <select
+          id="video-frame-rate"
+          value={frameRate}
+          onChange={event =>
+            save({ frameRate: Number(event.target.value) as FrameRate })
+          }
+          className={SELECT_CLASS}
+        >
+          {FRAME_RATES.map(rate => (
+            <option key={rate} value={rate}>
+              {rate} fps
+            </option>
+          ))}
+        </select>
+
+        <label htmlFor="video-scale" className="text-neutral-400">
+          Resolution
+        </label>
+        <div className="flex items-center gap-3">
+          <select
+            id="video-scale"
+            value={preset}
+            onChange={event => saveScale(event.target.value as ScalePreset)}
+            className={SELECT_CLASS}
+          >
+            {SCALES.map(scale => (
+              <option key={scale.value} value={scale.value}>
+                {scale.label}
+              </option>
+            ))}
+          </select>
+          {typeof outputScale === 'object' && (
+            <input
+              type="number"
+              min={320}
+              step={2}
+              value={outputScale.width}
+              onChange={event =>
+                save({ outputScale: { width: Number(event.target.value) } })
+              }
+              className={`w-24 ${SELECT_CLASS}`}
+            />
+          )}
+        </div>
+      </div>
+
+      {errorMessage && <p className="text-sm text-red-400">{errorMessage}</p>}
+    </div>
+  )
+}
@@ -1,5 +1,12 @@
 import { invoke } from '@tauri-apps/api/core'
-import type { RecordingOptions, AppSettings } from '../types'
+import type {
+  RecordingOptions,
+  AppSettings,
+  CalibrationReport,
+  CaptureTargetInfo,
+  EncoderPreset,
+  FfmpegCapabilities
+} from '../types'
 
 export const startRecording = async (
   options: RecordingOptions
@@ -27,6 +34,10 @@ export const updateSettings = async (settings: AppSettings): Promise<void> => {
   await invoke('update_settings', { settings })
 }
 
+export const getEncoderPresets = async (): Promise<EncoderPreset[]> => {
+  return await invoke('get_encoder_presets')
+}
+
 export const setCameraOverlayVisible = async (
   visible: boolean
 ): Promise<void> => {
@@ -60,3 +71,17 @@ export const updateImmersiveShortcut = async (
 ): Promise<void> => {
   await invoke('update_immersive_shortcut', { shortcut })
 }
+
+export const runAvCalibration = async (
+  save: boolean
+): Promise<CalibrationReport> => {
+  return await invoke('run_av_calibration', { save })
+}
+
+export const listCaptureTargets = async (): Promise<CaptureTargetInfo[]> => {
+  return await invoke('list_capture_targets')
+}
+
+export const getFfmpegCapabilities = async (): Promise<FfmpegCapabilities> => {
+  return await invoke('get_ffmpeg_capabilities')
+}
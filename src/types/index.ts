@@ -8,7 +8,72 @@ export type RecordingState =
 export interface RecordingOptions {
   includeMicrophone: boolean
   includeCamera: boolean
-  screenTarget?: string // For future: specific screen/window
+  screenTarget?: string // display:<id>, window:<id> or region:<display>:<x>,<y>,<w>x<h>
+  captureMode?: CaptureMode // Defaults to MOMENTUM_CAPTURE_MODE, then 'native'
+  // Frame rate, output scale and encoder always come from AppSettings
+}
+
+export type CaptureMode = 'native' | 'synthetic'
+
+export type FrameRate = 15 | 24 | 30 | 60
+
+// Output height, or a fixed width; keeps the aspect ratio and never upscales
+export type OutputScale =
+  | 'native'
+  | '1440p'
+  | '1080p'
+  | '720p'
+  | { width: number }
+
+export type VideoCodec = 'h264' | 'hevc' | 'vp9' | 'av1'
+
+export type RateControl = { crf: number } | { bitrateKbps: number }
+
+// x264 preset names; VP9 and AV1 map them to their own speed settings
+export type EncoderSpeed =
+  | 'ultrafast'
+  | 'veryfast'
+  | 'fast'
+  | 'medium'
+  | 'slow'
+
+export interface EncoderProfile {
+  codec: VideoCodec
+  rateControl: RateControl
+  preset: EncoderSpeed
+  keyframeIntervalSecs: number | null // Encoder default when null
+  lossless: boolean // Overrides rateControl; not available for AV1
+}
+
+// Named profile from get_encoder_presets
+export interface EncoderPreset {
+  id: string
+  label: string
+  profile: EncoderProfile
+}
+
+// Fixed latency of one capture device; positive means it delivers late
+export interface DeviceOffset {
+  audioMs: number
+  videoMs: number
+}
+
+// Keyed by device id, as logged by the capture backends
+export type DeviceOffsets = Record<string, DeviceOffset>
+
+export type CameraCorner = 'topLeft' | 'topRight' | 'bottomLeft' | 'bottomRight'
+
+export type CameraShape = 'rectangle' | 'rounded' | 'circle'
+
+// Composited into the screen, a second video track, or a .camera.mp4 sidecar
+export type CameraOutput = 'composite' | 'track' | 'sidecar'
+
+// Camera picture-in-picture, on screen and in recordings
+export interface CameraOverlay {
+  corner: CameraCorner
+  size: number // Fraction of the screen width
+  shape: CameraShape
+  output: CameraOutput
 }
 
 export interface AppSettings {
@@ -16,6 +81,46 @@ export interface AppSettings {
   cameraEnabled: boolean
   immersiveShortcut: string
   saveLocation?: string // Defaults to Downloads
+  deviceOffsets?: DeviceOffsets
+  cameraOverlay?: CameraOverlay
+  frameRate?: FrameRate
+  outputScale?: OutputScale
+  encoder?: EncoderProfile
+}
+
+// Positive offsets mean the audio plays late
+export interface CalibrationReport {
+  flashes: number
+  systemAudioBeeps: number
+  micBeeps: number
+  systemAudioOffsetMs: number | null
+  micOffsetMs: number | null
+  systemAudioDevice: string // Device ids the offsets were measured on
+  micDevice: string | null
+}
+
+export type CaptureTargetKind = 'display' | 'window'
+
+// Something the recorder can capture, from list_capture_targets
+export interface CaptureTargetInfo {
+  kind: CaptureTargetKind
+  id: number
+  target: string // Value for RecordingOptions.screenTarget
+  title: string
+  owningApp: string | null
+  bounds: { x: number; y: number; width: number; height: number }
+  thumbnailBase64: string | null // PNG
+}
+
+// What the resolved FFmpeg build supports, from get_ffmpeg_capabilities
+export interface FfmpegCapabilities {
+  ffmpegPath: string
+  version: string | null
+  encoders: string[]
+  decoders: string[]
+  demuxers: string[] // Input devices included
+  filters: string[]
+  hwaccels: string[]
 }
 
 export interface CameraFrame {
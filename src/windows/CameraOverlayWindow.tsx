@@ -1,17 +1,32 @@
 import { CameraFrame } from '../components/camera/CameraFrame'
 import { useEffect } from 'react'
+import { useSettingsStore } from '../state/settingsStore'
+import { getSettings } from '../tauri/commands'
+import type { CameraShape } from '../types'
 import '../App.css'
 
+// Same corner radius as the camera composited into recordings (12% of the side)
+const SHAPE_CLASSES: Record<CameraShape, string> = {
+  rectangle: 'rounded-none',
+  rounded: 'rounded-[12%]',
+  circle: 'rounded-full'
+}
+
 export function CameraOverlayWindow() {
+  const { settings, setSettings } = useSettingsStore()
+  const shape = settings.cameraOverlay?.shape ?? 'circle'
+
   useEffect(() => {
     console.log('[CameraOverlayWindow] Component mounted')
-  }, [])
+    getSettings()
+      .then(setSettings)
+      .catch(err => console.error('Failed to load settings', err))
+  }, [setSettings])
 
   return (
-    <div className="w-full h-full bg-transparent flex items-center justify-center p-2">
+    <div className="w-full h-full bg-transparent flex items-center justify-center">
       <div
-        className="w-full h-full rounded-full overflow-hidden bg-black aspect-square select-none"
-        data-tauri-drag-region="true"
+        className={`w-full h-full overflow-hidden bg-black select-none ${SHAPE_CLASSES[shape]}`}
         style={{ userSelect: 'none', WebkitUserSelect: 'none' }}
       >
         <div
@@ -2,6 +2,10 @@ import { useEffect, useState } from 'react'
 import { useSettingsStore } from '../state/settingsStore'
 import { getSettings } from '../tauri/commands'
 import { ImmersiveShortcutForm } from '../features/settings/components/ImmersiveShortcutForm'
+import { DeviceOffsetsForm } from '../features/settings/components/DeviceOffsetsForm'
+import { CameraOverlayForm } from '../features/settings/components/CameraOverlayForm'
+import { VideoForm } from '../features/settings/components/VideoForm'
+import { EncoderForm } from '../features/settings/components/EncoderForm'
 
 export function SettingsWindow() {
   const { settings, setSettings } = useSettingsStore()
@@ -59,6 +63,46 @@ export function SettingsWindow() {
           </div>
           <ImmersiveShortcutForm />
         </section>
+
+        <section className="flex flex-col gap-4">
+          <div>
+            <h2 className="text-lg font-medium">Video</h2>
+            <p className="text-sm text-neutral-500">
+              Frame rate and resolution of new recordings.
+            </p>
+          </div>
+          <VideoForm />
+        </section>
+
+        <section className="flex flex-col gap-4">
+          <div>
+            <h2 className="text-lg font-medium">Encoding</h2>
+            <p className="text-sm text-neutral-500">
+              Codec and quality of new recordings.
+            </p>
+          </div>
+          <EncoderForm />
+        </section>
+
+        <section className="flex flex-col gap-4">
+          <div>
+            <h2 className="text-lg font-medium">Camera</h2>
+            <p className="text-sm text-neutral-500">
+              Where the camera sits, on screen and in recordings.
+            </p>
+          </div>
+          <CameraOverlayForm />
+        </section>
+
+        <section className="flex flex-col gap-4">
+          <div>
+            <h2 className="text-lg font-medium">Device Latency</h2>
+            <p className="text-sm text-neutral-500">
+              Compensates headsets and interfaces that add a fixed delay.
+            </p>
+          </div>
+          <DeviceOffsetsForm />
+        </section>
       </div>
     </div>
   )